@@ -0,0 +1,303 @@
+use std::path::Path;
+use serde::Deserialize;
+
+use crate::fetch::Fetcher;
+use crate::sandbox::Sandbox;
+use crate::templates::{Answers, Patch};
+use crate::trace;
+
+// Extensions of the files that are always handled as text
+const TEXT_EXTENSIONS: &[&str] = &[
+    "tex", "cls", "sty", "bib", "bst", "bbx", "cbx", "csl", "md", "yaml", "yml",
+    "txt", "json", "toml", "lua", "sh", "py", "pl", "mk",
+];
+
+// Extensions of the files that are always handled as binary
+const BINARY_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "pdf", "eps", "svgz", "ttf", "otf", "woff", "woff2",
+    "pfb", "zip", "gz", "tar",
+];
+
+// Names of the files that are always handled as text
+const TEXT_NAMES: &[&str] = &["Makefile", "makefile", ".gitignore", ".latexmkrc"];
+
+// Extensions of the scripts that need the executable bit
+const SCRIPT_EXTENSIONS: &[&str] = &["sh", "py", "pl"];
+
+/// Encoding of an asset
+/// `Auto` detects the encoding using the name and the content of the file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    #[default]
+    Auto,
+    Text,
+    Binary,
+}
+
+/// Struct to describe a file of a template
+///
+/// # Example
+/// ```toml
+/// [[files]]
+/// path = "lib/preamble.tex"   # Path inside the new project
+/// source = "preamble.tex"     # Path relative to the template source, defaults to `path`
+/// encoding = "text"           # auto, text or binary
+/// executable = false          # Defaults to true for scripts
+/// render = true               # Replace the `{{variable}}` placeholders with the answers
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Asset {
+    pub path: String,
+    pub source: Option<String>,
+    pub content: Option<String>,
+    #[serde(default)]
+    pub encoding: Encoding,
+    pub executable: Option<bool>,
+    /// Replace the `{{variable}}` placeholders of a text file with the answers of the user
+    #[serde(default)]
+    pub render: bool,
+}
+
+/// Implementation of the Asset struct to download and write the files of a template
+impl Asset {
+    /// Return the URL where the asset is downloaded from
+    ///
+    /// # Arguments
+    /// * `base` - The URL of the template source
+    ///
+    /// # Example
+    /// ```
+    /// let url = asset.url("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math");
+    /// ```
+    pub fn url(&self, base: &str) -> String {
+        let source = self.source.as_deref().unwrap_or(&self.path);
+        format!("{}/{}", base.trim_end_matches('/'), source.trim_start_matches('/'))
+    }
+
+    /// Get the bytes of the asset, from its inline content or from the template source
+    ///
+    /// # Arguments
+    /// * `base` - The URL of the template source
+    /// * `fetcher` - Where the files of the template source come from
+    ///
+    /// # Errors
+    /// This function returns an error if the asset can't be downloaded
+    pub fn load(&self, base: &str, fetcher: &dyn Fetcher) -> Result<Vec<u8>, String> {
+        match &self.content {
+            Some(content) => Ok(content.as_bytes().to_vec()),
+            None => fetcher.fetch(&self.url(base)),
+        }
+    }
+
+    /// Resolve the encoding of the asset using its declaration, its name and its content
+    ///
+    /// # Arguments
+    /// * `bytes` - The content of the asset
+    ///
+    /// # Example
+    /// ```
+    /// let encoding = asset.encoding_of(&bytes);
+    /// ```
+    pub fn encoding_of(&self, bytes: &[u8]) -> Encoding {
+        match self.encoding {
+            Encoding::Auto => detect_encoding(&self.path, bytes),
+            encoding => encoding,
+        }
+    }
+
+    /// Check if the asset must be written with the executable bit
+    /// Scripts are detected by their extension or by a shebang
+    ///
+    /// # Arguments
+    /// * `bytes` - The content of the asset
+    pub fn is_executable(&self, bytes: &[u8]) -> bool {
+        self.executable.unwrap_or_else(|| {
+            SCRIPT_EXTENSIONS.contains(&extension(&self.path).as_str()) || bytes.starts_with(b"#!")
+        })
+    }
+
+    /// Download the asset and write it inside the given project
+    /// Text files get their line endings normalized, binary files are written unchanged
+    ///
+    /// # Arguments
+    /// * `root` - The path to the new project
+    /// * `base` - The URL of the template source
+    /// * `fetcher` - Where the files of the template source come from
+    /// * `answers` - The answers of the user, used by the files with `render = true`
+    /// * `patches` - The lines added by the enabled features, only for text files
+    ///
+    /// # Example
+    /// ```
+    /// asset.install("path/to/project", &template.source, &HttpFetcher, &answers, &[])?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the asset can't be downloaded or written,
+    /// or if its path goes outside of the project
+    pub fn install(&self, root: &str, base: &str, fetcher: &dyn Fetcher, answers: &Answers, patches: &[Patch]) -> Result<(), String> {
+        let bytes = self.load(base, fetcher)?;
+
+        let bytes = match self.encoding_of(&bytes) {
+            Encoding::Binary => bytes,
+            _ if self.render => apply_patches(render_placeholders(&normalize_line_endings(&bytes), answers), patches),
+            _ => apply_patches(normalize_line_endings(&bytes), patches),
+        };
+
+        // The path comes from the manifest, so it can't leave the project
+        let path = Sandbox::new(root).create_file(&self.path, &bytes)?;
+
+        if self.is_executable(&bytes) {
+            set_executable(&path.to_string_lossy())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Detect if a file is text or binary
+/// Known names and extensions are used first, then the content is checked:
+/// a file with NUL bytes or with too many control characters is binary
+///
+/// # Arguments
+/// * `path` - The path of the file
+/// * `bytes` - The content of the file
+///
+/// # Example
+/// ```
+/// assert_eq!(detect_encoding("main.tex", b"\\documentclass{article}"), Encoding::Text);
+/// ```
+pub fn detect_encoding(path: &str, bytes: &[u8]) -> Encoding {
+    let name = Path::new(path).file_name().and_then(|name| name.to_str()).unwrap_or(path);
+    let extension = extension(path);
+
+    if TEXT_NAMES.contains(&name) || TEXT_EXTENSIONS.contains(&extension.as_str()) {
+        return Encoding::Text;
+    }
+
+    if BINARY_EXTENSIONS.contains(&extension.as_str()) {
+        return Encoding::Binary;
+    }
+
+    // Check only the beginning of the file, like most tools do
+    let sample = &bytes[..bytes.len().min(8000)];
+
+    if sample.contains(&0) {
+        return Encoding::Binary;
+    }
+
+    // Latin-1 files are not valid UTF-8 but they are still text,
+    // so only count the control characters that text files don't use
+    let control = sample.iter()
+        .filter(|byte| **byte < 0x20 && !matches!(byte, b'\n' | b'\r' | b'\t' | 0x0c | 0x1b))
+        .count();
+
+    if control * 10 > sample.len() {
+        Encoding::Binary
+    } else {
+        Encoding::Text
+    }
+}
+
+/// Replace the CRLF and CR line endings of a text file with LF
+/// The rest of the bytes are kept unchanged, so any encoding is preserved
+///
+/// # Arguments
+/// * `bytes` - The content of the file
+///
+/// # Example
+/// ```
+/// assert_eq!(normalize_line_endings(b"a\r\nb\rc"), b"a\nb\nc");
+/// ```
+pub fn normalize_line_endings(bytes: &[u8]) -> Vec<u8> {
+    let mut normalized = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter().peekable();
+
+    while let Some(byte) = iter.next() {
+        if *byte == b'\r' {
+            // Skip the LF of a CRLF, the CR is replaced below
+            if iter.peek() == Some(&&b'\n') {
+                iter.next();
+            }
+            normalized.push(b'\n');
+        } else {
+            normalized.push(*byte);
+        }
+    }
+
+    normalized
+}
+
+/// Replace the `{{variable}}` placeholders of a text file with the answers
+/// Placeholders of unknown variables are kept, so the file still shows what is missing,
+/// and files that are not valid UTF-8 are kept unchanged
+///
+/// # Arguments
+/// * `bytes` - The content of the file
+/// * `answers` - The answers of the user, by variable name
+///
+/// # Example
+/// ```
+/// let bytes = render_placeholders(b"\\title{{{title}}}", &answers); // \title{My thesis}
+/// ```
+pub fn render_placeholders(bytes: &[u8], answers: &Answers) -> Vec<u8> {
+    let mut content = match String::from_utf8(bytes.to_vec()) {
+        Ok(content) => content,
+        Err(_) => return bytes.to_vec(),
+    };
+
+    for (name, value) in answers {
+        content = content.replace(&format!("{{{{{}}}}}", name), value);
+    }
+
+    content.into_bytes()
+}
+
+/// Add the lines of the patches to a text file
+/// Files that are not valid UTF-8 are kept unchanged
+fn apply_patches(bytes: Vec<u8>, patches: &[Patch]) -> Vec<u8> {
+    if patches.is_empty() {
+        return bytes;
+    }
+
+    match String::from_utf8(bytes) {
+        Ok(content) => patches.iter().fold(content, |content, patch| patch.apply(&content)).into_bytes(),
+        Err(err) => err.into_bytes(),
+    }
+}
+
+/// Set the executable bits of a file, on systems that have them
+///
+/// # Arguments
+/// * `path` - The path to the file
+///
+/// # Errors
+/// This function returns an error if the permissions of the file can't be changed
+pub fn set_executable(path: &str) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        trace!("chmod +x {}", path);
+        let mut permissions = std::fs::metadata(path)
+            .map_err(|err| format!("Failed to read the permissions of {}: {}", path, err))?
+            .permissions();
+        // Add the execute bit wherever the read bit is set
+        permissions.set_mode(permissions.mode() | ((permissions.mode() & 0o444) >> 2));
+        std::fs::set_permissions(path, permissions).map_err(|err| format!("Failed to set the permissions of {}: {}", path, err))?;
+    }
+
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
+
+/// Return the lowercase extension of a path, or an empty string
+fn extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
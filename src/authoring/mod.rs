@@ -0,0 +1,798 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use semver::Version;
+use serde::Serialize;
+
+use crate::doctor::find_executable;
+use crate::preflight::{check_target, Collision};
+use crate::registry::TemplateRegistry;
+use crate::sandbox::check_relative;
+use crate::scaffold::{Project, Scaffolder};
+use crate::templates::{Template, TemplateFile, VariableKind, MANIFEST};
+use crate::trace;
+
+// const map to store the files of a new template skeleton and their content
+// `{name}` is replaced by the name of the template
+const SKELETON: &[(&str, &str)] = &[
+    (MANIFEST, r#"name = "{name}"
+description = "Describe the template in one line"
+version = "0.1.0"
+authors = []
+tags = ["latex"]
+backend = "latex"
+folders = ["images"]
+preview = ["main.tex"]
+
+[requirements]
+executables = ["pdflatex", "make"]
+
+[[files]]
+path = "main.tex"
+
+[[files]]
+path = "Makefile"
+
+[[files]]
+path = "README.md"
+when = "readme"
+
+[[files]]
+path = ".gitignore"
+when = "git"
+
+[[variables]]
+name = "git"
+prompt = "Do you want to initialize a git repository?"
+kind = "confirm"
+default = "yes"
+
+[[variables]]
+name = "readme"
+prompt = "Do you want to create a README.md file?"
+kind = "confirm"
+default = "yes"
+"#),
+    ("main.tex", r"\documentclass{article}
+
+\title{{name}}
+\author{}
+\date{\today}
+
+\begin{document}
+\maketitle
+
+\section{Introduction}
+
+\end{document}
+"),
+    ("Makefile", "main.pdf: main.tex\n\tpdflatex -interaction=nonstopmode -halt-on-error main.tex\n\nclean:\n\trm -f *.aux *.log *.out *.toc *.pdf\n"),
+    ("README.md", "# {name}\n\nBuild the document with `make`.\n"),
+    (".gitignore", "*.aux\n*.log\n*.out\n*.toc\n*.pdf\n"),
+];
+
+// Number of lines of the build output shown when the build fails
+const BUILD_LOG_LINES: usize = 20;
+
+// Variables detected by `import`, with the question asked to the user
+const IMPORTED_VARIABLES: &[(&str, &str)] = &[
+    ("title", "Title of the document"),
+    ("author", "Author of the document"),
+    ("date", "Date of the document"),
+];
+
+// Extensions of the files created by LaTeX, latexmk, biber and the editors, never imported
+const ARTIFACT_EXTENSIONS: &[&str] = &[
+    "aux", "log", "out", "toc", "lof", "lot", "bbl", "blg", "bcf", "run.xml", "fls", "fdb_latexmk",
+    "synctex", "synctex.gz", "nav", "snm", "vrb", "idx", "ilg", "ind", "xdv", "dvi", "swp",
+];
+
+// Folders with build outputs or editor data, never imported
+const ARTIFACT_FOLDERS: &[&str] = &[".git", "build", "auto", "_minted", ".latexmk"];
+
+// Files created by the operating systems, never imported
+const ARTIFACT_NAMES: &[&str] = &[".DS_Store", "Thumbs.db"];
+
+/// How bad a problem found by the linter is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The template can't be used as it is
+    Error,
+    /// The template works, but something is probably a mistake
+    Warning,
+}
+
+/// Struct with a problem found in a template by `lint`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Implementation of the Issue struct to create the issues
+impl Issue {
+    fn error(message: String) -> Issue {
+        Issue { severity: Severity::Error, message }
+    }
+
+    fn warning(message: String) -> Issue {
+        Issue { severity: Severity::Warning, message }
+    }
+}
+
+/// Create the skeleton of a new template: a manifest, a main.tex, a Makefile, a README.md and a .gitignore
+///
+/// # Arguments
+/// * `path` - The path where the folder of the template is created
+/// * `name` - The name of the template, also the name of its folder
+///
+/// # Example
+/// ```
+/// let dir = skeleton("~/.config/lth/templates", "thesis")?;
+/// ```
+///
+/// # Errors
+/// This function returns an error if the folder already exists or if a file can't be written
+pub fn skeleton(path: &str, name: &str) -> Result<PathBuf, String> {
+    check_target(path, name, Collision::Abort)?;
+
+    let dir = Path::new(path).join(name);
+    trace!("mkdir {}", dir.display());
+    std::fs::create_dir_all(dir.join("images")).map_err(|err| format!("Failed to create the folder {}: {}", dir.display(), err))?;
+
+    for (file, content) in SKELETON {
+        let file = dir.join(file);
+        trace!("write {}", file.display());
+        std::fs::write(&file, content.replace("{name}", name)).map_err(|err| format!("Failed to write the file {}: {}", file.display(), err))?;
+    }
+
+    Ok(dir)
+}
+
+/// Check the manifest of a local template and its files
+///
+/// # Arguments
+/// * `dir` - The folder of the template, with its `template.toml` manifest
+///
+/// # Example
+/// ```
+/// let issues = lint(Path::new("thesis"));
+/// ```
+pub fn lint(dir: &Path) -> Vec<Issue> {
+    match load(dir) {
+        Ok(template) => lint_template(&template),
+        Err(err) => vec![Issue::error(err)],
+    }
+}
+
+/// Load a local template with the template it extends and its layers,
+/// found in the built-in templates and the templates of the user
+///
+/// # Arguments
+/// * `dir` - The folder of the template, with its `template.toml` manifest
+///
+/// # Errors
+/// This function returns an error if the manifest is not valid or if the template can't be resolved
+pub fn load(dir: &Path) -> Result<Template, String> {
+    TemplateRegistry::discover().compose(&Template::load(dir)?)
+}
+
+/// Check a template, its files are only checked if they are local files
+/// Only the files declared by the template itself are compared with its folder
+///
+/// # Arguments
+/// * `template` - The template to check
+pub fn lint_template(template: &Template) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let root = template.local_root();
+
+    // `@` separates the name from the version, like `ieee@1.2`
+    if template.name.trim().is_empty() || template.name.contains(['/', '@']) {
+        issues.push(Issue::error(format!("Invalid template name '{}'", template.name)));
+    }
+
+    if template.description.trim().is_empty() {
+        issues.push(Issue::warning("The template has no description".to_string()));
+    }
+
+    if template.files.is_empty() {
+        issues.push(Issue::error("The template has no files".to_string()));
+    }
+
+    // Versions
+    match &template.version {
+        Some(version) => if let Err(err) = Version::parse(version) {
+            issues.push(Issue::error(format!("Invalid version {}, use a semantic version like 1.0.0: {}", version, err)));
+        },
+        None => issues.push(Issue::warning("The template has no version, so it can't be pinned".to_string())),
+    }
+
+    let mut versions: HashSet<&str> = template.version.iter().map(String::as_str).collect();
+    for release in &template.releases {
+        if let Err(err) = release.validate() {
+            issues.push(Issue::error(err));
+        }
+
+        if !versions.insert(&release.version) {
+            issues.push(Issue::error(format!("The version {} is declared twice", release.version)));
+        }
+
+        if release.tag.is_some() && template.repository.is_none() {
+            issues.push(Issue::error(format!("The release {} has a tag, but the template has no repository", release.version)));
+        }
+    }
+
+    // Variables
+    let questions = template.questions();
+    let mut names = HashSet::new();
+    for variable in &template.variables {
+        if !names.insert(variable.name.as_str()) {
+            issues.push(Issue::error(format!("The variable {} is declared twice", variable.name)));
+        }
+
+        let has_options = matches!(variable.kind, VariableKind::Select | VariableKind::MultiSelect);
+        if has_options && variable.options.is_empty() {
+            issues.push(Issue::error(format!("The {} variable {} has no options", variable.kind.name(), variable.name)));
+        } else if !has_options && !variable.options.is_empty() {
+            issues.push(Issue::warning(format!("The options of the {} variable {} are ignored", variable.kind.name(), variable.name)));
+        }
+
+        if let Some(default) = variable.default.as_deref().filter(|default| !default.is_empty()) {
+            if let Err(err) = variable.resolve(default) {
+                issues.push(Issue::error(format!("Invalid default value of {}: {}", variable.name, err)));
+            }
+        }
+
+        // The git variable is used to initialize the repository, the others enable files
+        // or fill the placeholders of the rendered files
+        let used = variable.name == "git"
+            || template.files.iter().any(|file| file.when.as_deref() == Some(&variable.name))
+            || template.hooks.iter().any(|hook| hook.when.as_deref() == Some(&variable.name))
+            || template.files.iter().any(|file| file.asset.render && uses_placeholder(template, file, &variable.name));
+        if !used {
+            issues.push(Issue::warning(format!("The variable {} is not used by any file", variable.name)));
+        }
+    }
+
+    // Features, asked like the variables so they share their names
+    for feature in &template.features {
+        if !names.insert(feature.name.as_str()) {
+            issues.push(Issue::error(format!("The feature {} has the name of another variable or feature", feature.name)));
+        }
+
+        let used = !feature.folders.is_empty() || !feature.patches.is_empty()
+            || template.files.iter().any(|file| file.when.as_deref() == Some(&feature.name))
+            || template.hooks.iter().any(|hook| hook.when.as_deref() == Some(&feature.name));
+        if !used {
+            issues.push(Issue::warning(format!("The feature {} has no files, folders, patches or hooks", feature.name)));
+        }
+
+        for patch in &feature.patches {
+            match template.files.iter().find(|file| file.asset.path == patch.file) {
+                None => issues.push(Issue::error(format!("The feature {} patches {}, which is not a file of the template", feature.name, patch.file))),
+                Some(file) => if let Some(marker) = patch.before.as_ref().or(patch.after.as_ref()) {
+                    if !file_contains(template, file, marker) {
+                        issues.push(Issue::warning(format!("The feature {} adds lines next to '{}', not found in {}", feature.name, marker, patch.file)));
+                    }
+                },
+            }
+        }
+
+        for folder in &feature.folders {
+            if let Err(err) = check_relative(folder) {
+                issues.push(Issue::error(format!("Invalid folder of the feature {}: {}", feature.name, err)));
+            }
+        }
+    }
+
+    // Hooks
+    for hook in &template.hooks {
+        if let Err(err) = hook.validate() {
+            issues.push(Issue::error(format!("{}: {}", err, hook.describe())));
+        }
+
+        match hook.when.as_deref().map(|name| questions.iter().find(|variable| variable.name == name)) {
+            Some(None) => issues.push(Issue::error(format!("The hook {} depends on the unknown variable {}", hook.describe(), hook.when.as_deref().unwrap_or_default()))),
+            Some(Some(variable)) if variable.kind != VariableKind::Confirm => {
+                issues.push(Issue::error(format!("The hook {} depends on {}, which is not a confirm variable", hook.describe(), variable.name)));
+            },
+            _ => {},
+        }
+    }
+
+    // Folders
+    for folder in &template.folders {
+        if let Err(err) = check_relative(folder) {
+            issues.push(Issue::error(format!("Invalid folder {}", err)));
+        }
+    }
+
+    // Files
+    let mut paths = HashSet::new();
+    for file in &template.files {
+        let path = &file.asset.path;
+
+        if let Err(err) = check_relative(path) {
+            issues.push(Issue::error(format!("Invalid file path {}", err)));
+        }
+
+        if !paths.insert(path.as_str()) {
+            issues.push(Issue::error(format!("The file {} is declared twice", path)));
+        }
+
+        if let Some(source) = &file.asset.source {
+            if let Err(err) = check_relative(source) {
+                issues.push(Issue::error(format!("Invalid source of {}: {}", path, err)));
+            }
+        }
+
+        match file.when.as_deref().map(|name| questions.iter().find(|variable| variable.name == name)) {
+            Some(None) => issues.push(Issue::error(format!("The file {} depends on the unknown variable {}", path, file.when.as_deref().unwrap_or_default()))),
+            Some(Some(variable)) if variable.kind != VariableKind::Confirm => {
+                issues.push(Issue::error(format!("The file {} depends on {}, which is not a confirm variable", path, variable.name)));
+            },
+            _ => {},
+        }
+
+        // Files without an inline content must exist next to the manifest
+        if let (Some(root), None) = (local_root_of(template, file), &file.asset.content) {
+            let source = file.asset.source.as_deref().unwrap_or(path);
+            if check_relative(source).is_ok() && !root.join(source).is_file() {
+                issues.push(Issue::error(format!("The file {} is missing, expected at {}", path, root.join(source).display())));
+            }
+        }
+    }
+
+    for path in &template.preview {
+        if !paths.contains(path.as_str()) {
+            issues.push(Issue::error(format!("The preview file {} is not a file of the template", path)));
+        }
+    }
+
+    // Files of the folder that are never copied
+    if let Some(root) = &root {
+        let sources: HashSet<&str> = template.files.iter()
+            .filter(|file| file.base.is_none())
+            .map(|file| file.asset.source.as_deref().unwrap_or(&file.asset.path))
+            .collect();
+
+        for file in folder_files(root, root) {
+            if file != MANIFEST && !sources.contains(file.as_str()) {
+                issues.push(Issue::warning(format!("{} is not a file of the template", file)));
+            }
+        }
+    }
+
+    issues
+}
+
+/// Check if a rendered file uses the placeholder of a variable
+fn uses_placeholder(template: &Template, file: &TemplateFile, name: &str) -> bool {
+    file_contains(template, file, &format!("{{{{{}}}}}", name))
+}
+
+/// Check if a file of a template contains a text
+/// The files of the remote templates can't be read, so they are supposed to contain it
+fn file_contains(template: &Template, file: &TemplateFile, text: &str) -> bool {
+    match (&file.asset.content, local_root_of(template, file)) {
+        (Some(content), _) => content.contains(text),
+        (None, Some(root)) => std::fs::read_to_string(root.join(file.asset.source.as_deref().unwrap_or(&file.asset.path)))
+            .is_ok_and(|content| content.contains(text)),
+        (None, None) => true,
+    }
+}
+
+/// Return the folder of a file of a template, if it is read from the disk
+/// Inherited files are in the folder of the template that declared them
+fn local_root_of(template: &Template, file: &TemplateFile) -> Option<PathBuf> {
+    template.base_of(file).strip_prefix("file://").map(PathBuf::from)
+}
+
+/// Return the files inside a folder, relative to the root, without the hidden git folder
+fn folder_files(root: &Path, dir: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return files,
+    };
+
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.file_name().is_some_and(|name| name == ".git") {
+            continue;
+        }
+
+        match path.is_dir() {
+            true => files.extend(folder_files(root, &path)),
+            false => if let Ok(relative) = path.strip_prefix(root) {
+                files.push(relative.to_string_lossy().replace('\\', "/"));
+            },
+        }
+    }
+
+    files.sort();
+    files
+}
+
+/// Create a project with a template in a temporary folder, answering its variables with their defaults
+/// No git repository is initialized
+///
+/// # Arguments
+/// * `template` - The template to render
+///
+/// # Example
+/// ```
+/// let project = render(&Template::load(Path::new("thesis"))?)?;
+/// ```
+///
+/// # Errors
+/// This function returns an error if the temporary folder can't be created,
+/// or if the project can't be created
+pub fn render(template: &Template) -> Result<Project, String> {
+    let dir = std::env::temp_dir().join(format!("lth-test-{}-{}", template.name, std::process::id()));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(|err| format!("Failed to remove {}: {}", dir.display(), err))?;
+    }
+    std::fs::create_dir_all(&dir).map_err(|err| format!("Failed to create the folder {}: {}", dir.display(), err))?;
+
+    let mut scaffolder = Scaffolder::new(template.clone()).path(dir.to_string_lossy());
+    if template.variables.iter().any(|variable| variable.name == "git") {
+        scaffolder = scaffolder.answer("git", "no");
+    }
+
+    scaffolder.create()
+}
+
+/// Build the documents of a project, with `make` if it has a Makefile,
+/// otherwise with latexmk or pdflatex on its main.tex
+///
+/// # Arguments
+/// * `path` - The folder of the project
+///
+/// # Example
+/// ```
+/// build(&project.path)?;
+/// ```
+///
+/// # Errors
+/// This function returns an error with the end of the build output if the build fails,
+/// or if there is nothing to build
+pub fn build(path: &str) -> Result<(), String> {
+    let dir = Path::new(path);
+
+    let (program, args): (&str, &[&str]) = if dir.join("Makefile").is_file() {
+        ("make", &[])
+    } else if dir.join("main.tex").is_file() && find_executable("latexmk").is_some() {
+        ("latexmk", &["-pdf", "-interaction=nonstopmode", "-halt-on-error", "main.tex"])
+    } else if dir.join("main.tex").is_file() {
+        ("pdflatex", &["-interaction=nonstopmode", "-halt-on-error", "main.tex"])
+    } else {
+        return Err(format!("Nothing to build in {}, there is no Makefile and no main.tex", path));
+    };
+
+    if find_executable(program).is_none() {
+        return Err(format!("{} is needed to build the project", program));
+    }
+
+    trace!("{} {} in {}", program, args.join(" "), path);
+    let output = std::process::Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|err| format!("Failed to run {}: {}", program, err))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    // Show the end of the output, where LaTeX prints the error
+    let log = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    let lines: Vec<&str> = log.lines().collect();
+    let tail = lines[lines.len().saturating_sub(BUILD_LOG_LINES)..].join("\n");
+
+    Err(format!("{} failed ({})\n{}", program, output.status, tail))
+}
+
+/// Struct with a file found by `import`
+struct ImportedFile {
+    path: String,
+    /// New content of the file, with the placeholders of the detected variables
+    rendered: Option<String>,
+}
+
+/// Struct with a variable found by `import`, its default is the value found in the project
+struct ImportedVariable {
+    name: &'static str,
+    default: String,
+}
+
+/// Create a template from an existing project, in a folder of the user templates
+/// The build artifacts are skipped, and the title, the author and the date found
+/// in the LaTeX sources or in metadata.yaml become variables of the template
+///
+/// # Arguments
+/// * `project` - The folder of the existing project
+/// * `path` - The folder where the template is written, like `~/.config/lth/templates`
+/// * `name` - The name of the new template, also the name of its folder
+///
+/// # Example
+/// ```
+/// let dir = import(Path::new("~/lab/thesis"), "~/.config/lth/templates", "lab-thesis")?;
+/// ```
+///
+/// # Errors
+/// This function returns an error if the project can't be read,
+/// or if the template folder already exists or can't be written
+pub fn import(project: &Path, path: &str, name: &str) -> Result<PathBuf, String> {
+    if !project.is_dir() {
+        return Err(format!("{} is not a directory", project.display()));
+    }
+
+    check_target(path, name, Collision::Abort)?;
+
+    // Collect the files of the project, and the variables of the first file that has each one
+    let mut files = Vec::new();
+    let mut variables: Vec<ImportedVariable> = Vec::new();
+
+    for file in project_files(project, project) {
+        let content = match is_source(&file) {
+            true => std::fs::read_to_string(project.join(&file)).ok(),
+            false => None,
+        };
+
+        let rendered = content.and_then(|mut content| {
+            let mut found = false;
+
+            for (variable, _) in IMPORTED_VARIABLES {
+                if variables.iter().any(|existing| existing.name == *variable) {
+                    continue;
+                }
+
+                let value = match file.ends_with(".tex") {
+                    true => replace_latex_command(&mut content, variable),
+                    false => replace_yaml_key(&mut content, variable),
+                };
+
+                if let Some(default) = value {
+                    variables.push(ImportedVariable { name: variable, default });
+                    found = true;
+                }
+            }
+
+            found.then_some(content)
+        });
+
+        files.push(ImportedFile { path: file, rendered });
+    }
+
+    if files.is_empty() {
+        return Err(format!("{} has no files to import", project.display()));
+    }
+
+    // Write the files of the template
+    let dir = Path::new(path).join(name);
+    for file in &files {
+        let target = dir.join(&file.path);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| format!("Failed to create the folder {}: {}", parent.display(), err))?;
+        }
+
+        trace!("write {}", target.display());
+        let result = match &file.rendered {
+            Some(content) => std::fs::write(&target, content),
+            None => std::fs::copy(project.join(&file.path), &target).map(|_| ()),
+        };
+        result.map_err(|err| format!("Failed to write the file {}: {}", target.display(), err))?;
+    }
+
+    let manifest = import_manifest(project, name, &files, &variables);
+    std::fs::write(dir.join(MANIFEST), manifest).map_err(|err| format!("Failed to write the manifest of {}: {}", name, err))?;
+
+    Ok(dir)
+}
+
+/// Return the files of a project, relative to the root, without the build artifacts
+/// A PDF next to a source with the same name is the built document, so it is skipped too
+///
+/// # Arguments
+/// * `root` - The folder of the project
+/// * `dir` - The folder to list, the root or one of its sub-folders
+///
+/// # Example
+/// ```
+/// let files = project_files(Path::new("paper"), Path::new("paper")); // ["main.tex", "lib/bibliography.bib"]
+/// ```
+pub fn project_files(root: &Path, dir: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return files,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+
+        // The symlinks are not followed, a link to a parent folder would never end
+        let file_type = match entry.file_type() {
+            Ok(file_type) if file_type.is_symlink() => continue,
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+
+        if file_type.is_dir() {
+            if !ARTIFACT_FOLDERS.iter().any(|folder| name == *folder || name.starts_with("_minted-")) {
+                files.extend(project_files(root, &path));
+            }
+            continue;
+        }
+
+        let artifact = ARTIFACT_NAMES.contains(&name.as_str())
+            || name.ends_with('~')
+            || ARTIFACT_EXTENSIONS.iter().any(|extension| name.ends_with(&format!(".{}", extension)))
+            || (name.ends_with(".pdf") && ["tex", "md"].iter().any(|source| path.with_extension(source).is_file()));
+
+        if !artifact {
+            if let Ok(relative) = path.strip_prefix(root) {
+                files.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+/// Check if a file may have the title, the author or the date of the document
+fn is_source(path: &str) -> bool {
+    path.ends_with(".tex") || path.ends_with("metadata.yaml") || path.ends_with("metadata.yml")
+}
+
+/// Return a line of LaTeX without its comment, `\%` is not a comment
+///
+/// # Example
+/// ```
+/// assert_eq!(strip_comment("50\\% done % TODO"), "50\\% done ");
+/// ```
+pub fn strip_comment(line: &str) -> &str {
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '%' if !escaped => return &line[..index],
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    line
+}
+
+/// Replace the argument of a LaTeX command, like `\title{...}`, with the placeholder of its variable
+/// The commands inside comments, like `% \title{Old title}`, are skipped
+///
+/// # Returns
+/// The original argument, or `None` if the command is not found or spans several lines
+fn replace_latex_command(content: &mut String, command: &str) -> Option<String> {
+    let pattern = format!("\\{}{{", command);
+    let position = content.match_indices(&pattern).map(|(position, _)| position).find(|position| {
+        let line = &content[content[..*position].rfind('\n').map_or(0, |newline| newline + 1)..*position];
+        strip_comment(line).len() == line.len()
+    })?;
+    let start = position + pattern.len();
+
+    // Find the closing brace, the argument may have groups like \textbf{...}
+    let mut depth = 1;
+    let mut end = None;
+    for (index, character) in content[start..].char_indices() {
+        match character {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            '\n' => return None,
+            _ => {},
+        }
+        if depth == 0 {
+            end = Some(start + index);
+            break;
+        }
+    }
+
+    let end = end?;
+    let value = content[start..end].trim().to_string();
+    if value.is_empty() {
+        return None;
+    }
+
+    content.replace_range(start..end, &format!("{{{{{}}}}}", command));
+    Some(value)
+}
+
+/// Replace the value of a top-level YAML key, like `title: ...`, with the placeholder of its variable
+/// Quoted values keep their quotes
+///
+/// # Returns
+/// The original value, or `None` if the key is not found or its value is a list or a block
+fn replace_yaml_key(content: &mut String, key: &str) -> Option<String> {
+    let prefix = format!("{}:", key);
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        if let Some(rest) = line.strip_prefix(&prefix) {
+            let raw = rest.trim();
+            let value = raw.trim_matches(|character| character == '"' || character == '\'');
+            if value.is_empty() || raw.starts_with('|') || raw.starts_with('>') || raw.starts_with('[') {
+                return None;
+            }
+
+            let start = offset + prefix.len() + rest.find(value)?;
+            let value = value.to_string();
+            content.replace_range(start..start + value.len(), &format!("{{{{{}}}}}", key));
+            return Some(value);
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Write the manifest of an imported template
+fn import_manifest(project: &Path, name: &str, files: &[ImportedFile], variables: &[ImportedVariable]) -> String {
+    let has = |path: &str| files.iter().any(|file| file.path == path);
+    let pandoc = files.iter().any(|file| file.path.ends_with(".md") && file.path != "README.md")
+        && files.iter().any(|file| file.path.ends_with("metadata.yaml") || file.path.ends_with("metadata.yml"));
+
+    let mut executables = vec![if pandoc { "pandoc" } else { "pdflatex" }];
+    if has("Makefile") {
+        executables.push("make");
+    }
+
+    // The build folder is kept empty, the Makefiles usually write into it
+    let folders = match project.join("build").is_dir() {
+        true => vec!["build"],
+        false => Vec::new(),
+    };
+
+    let mut preview: Vec<&str> = files.iter().filter(|file| file.rendered.is_some()).map(|file| file.path.as_str()).collect();
+    if preview.is_empty() && has("main.tex") {
+        preview.push("main.tex");
+    }
+
+    let folder = project.canonicalize().ok()
+        .and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| project.display().to_string());
+
+    let mut manifest = format!("name = {}\n", quote(name));
+    manifest += &format!("description = {}\n", quote(&format!("Imported from {}", folder)));
+    manifest += "version = \"0.1.0\"\n";
+    manifest += "authors = []\n";
+    manifest += &format!("tags = [{}]\n", quote(if pandoc { "pandoc" } else { "latex" }));
+    manifest += &format!("backend = {}\n", quote(if pandoc { "pandoc" } else { "latex" }));
+    manifest += &format!("folders = [{}]\n", list(&folders));
+    manifest += &format!("preview = [{}]\n", list(&preview));
+    manifest += &format!("\n[requirements]\nexecutables = [{}]\n", list(&executables));
+
+    for file in files {
+        manifest += &format!("\n[[files]]\npath = {}\n", quote(&file.path));
+        if file.rendered.is_some() {
+            manifest += "render = true\n";
+        }
+        if file.path == ".gitignore" {
+            manifest += "when = \"git\"\n";
+        }
+    }
+
+    for variable in variables {
+        let prompt = IMPORTED_VARIABLES.iter().find(|(name, _)| *name == variable.name).map(|(_, prompt)| *prompt).unwrap_or(variable.name);
+        manifest += &format!("\n[[variables]]\nname = {}\nprompt = {}\nkind = \"text\"\ndefault = {}\n", quote(variable.name), quote(prompt), quote(&variable.default));
+    }
+
+    manifest += "\n[[variables]]\nname = \"git\"\nprompt = \"Do you want to initialize a git repository?\"\nkind = \"confirm\"\ndefault = \"yes\"\n";
+    manifest
+}
+
+/// Quote a string for the manifest
+fn quote(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+/// Write a list of strings for the manifest, without the brackets
+fn list(values: &[&str]) -> String {
+    values.iter().map(|value| quote(value)).collect::<Vec<_>>().join(", ")
+}
@@ -0,0 +1,715 @@
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+
+use crate::authoring::{project_files, strip_comment};
+use crate::fetch::Fetcher;
+use crate::trace;
+
+// File of the bibliography in the built-in templates, relative to the project
+pub const DEFAULT_BIBLIOGRAPHY: &str = "lib/bibliography.bib";
+
+// URL of the BibTeX of a DOI, from Crossref
+const CROSSREF_URL: &str = "https://api.crossref.org/works/{}/transform/application/x-bibtex";
+
+// URL of the BibTeX of an arXiv paper
+const ARXIV_URL: &str = "https://arxiv.org/bibtex/{}";
+
+// Words skipped when a key is made from the title
+const STOP_WORDS: &[&str] = &["a", "an", "the", "on", "of", "in", "for", "and", "to", "with", "from", "at", "by"];
+
+// Prefixes of the pandoc-crossref references, which look like citations
+const CROSSREF_PREFIXES: &[&str] = &["fig:", "sec:", "tbl:", "eq:", "lst:"];
+
+// Files of a project that can cite references
+const SOURCE_EXTENSIONS: &[&str] = &["tex", "ltx", "md", "markdown", "yaml", "yml"];
+
+/// Struct with an entry of a BibTeX database
+///
+/// # Example
+/// ```bibtex
+/// @book{knuth1984texbook,
+///   author    = {Donald E. Knuth},
+///   title     = {The {\TeX}book},
+///   year      = {1984},
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Type of the entry in lowercase, like article or book
+    pub kind: String,
+    pub key: String,
+    /// Fields of the entry in their order, with their names in lowercase and their values without the outer braces
+    pub fields: Vec<(String, String)>,
+}
+
+/// Identifier of a work that a resolver can turn into BibTeX
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identifier {
+    /// A DOI, like `10.1145/359545.359563`
+    Doi(String),
+    /// An arXiv id, like `1706.03762` or `hep-th/9901001`
+    Arxiv(String),
+}
+
+/// Source of the BibTeX of the works given by their DOI or arXiv id
+/// The resolvers are tried in order, so a local database can answer before the web
+pub trait Resolver {
+    /// Get the BibTeX of a work
+    ///
+    /// # Arguments
+    /// * `id` - The DOI or arXiv id of the work
+    ///
+    /// # Returns
+    /// `None` if the resolver doesn't know the work
+    ///
+    /// # Errors
+    /// This function returns an error if the resolver can't be read
+    fn resolve(&self, id: &Identifier) -> Result<Option<String>, String>;
+}
+
+/// Resolver that downloads the BibTeX from Crossref for the DOIs and from arXiv for the arXiv ids
+///
+/// # Example
+/// ```
+/// let resolver = WebResolver::new(&SourceFetcher);
+/// ```
+pub struct WebResolver<'a> {
+    fetcher: &'a dyn Fetcher,
+}
+
+/// Resolver that finds the works in a local BibTeX database, by their `doi` and `eprint` fields
+/// It works offline, with the database of the `[bibliography]` settings
+///
+/// # Example
+/// ```
+/// let resolver = LocalResolver::new("/home/me/references.bib");
+/// ```
+#[derive(Debug, Clone)]
+pub struct LocalResolver {
+    path: PathBuf,
+}
+
+/// Struct with a BibTeX file and its entries
+/// New entries are appended, so the rest of the file is kept as it is
+pub struct Bibliography {
+    pub path: PathBuf,
+    pub entries: Vec<Entry>,
+    content: String,
+}
+
+/// Struct with a citation found in the sources of a project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Citation {
+    pub key: String,
+    /// File of the citation, relative to the project
+    pub file: String,
+    pub line: usize,
+}
+
+/// Struct with the result of `lth bib check`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+    /// Citations of keys that are not in the bibliography
+    pub missing: Vec<Citation>,
+    /// Keys of the entries that are never cited
+    pub unused: Vec<String>,
+}
+
+/// Implementation of the Entry struct to read and write the entries
+impl Entry {
+    /// Return the value of a field, the names are compared without case
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Return the key the entry should have: the last name of the first author,
+    /// the year and the first significant word of the title, like `knuth1984texbook`
+    ///
+    /// # Example
+    /// ```
+    /// assert_eq!(entry.normalized_key(), "vaswani2017attention");
+    /// ```
+    pub fn normalized_key(&self) -> String {
+        let author = self.field("author").or_else(|| self.field("editor"))
+            .map(|authors| {
+                let first = authors.split(" and ").next().unwrap_or(authors).trim();
+                let last = match first.split_once(',') {
+                    Some((last, _)) => last,
+                    None => first.split_whitespace().last().unwrap_or(first),
+                };
+                simplify(last)
+            })
+            .filter(|author| !author.is_empty())
+            .unwrap_or_else(|| "anon".to_string());
+
+        let year = self.field("year")
+            .or_else(|| self.field("date").and_then(|date| date.get(..4)))
+            .map(simplify)
+            .unwrap_or_default();
+
+        let word = self.field("title")
+            .and_then(|title| title.split_whitespace().map(simplify).find(|word| !word.is_empty() && !STOP_WORDS.contains(&word.as_str())))
+            .unwrap_or_default();
+
+        format!("{}{}{}", author, year, word)
+    }
+
+    /// Write the entry in BibTeX, with the values in braces and the equal signs aligned
+    pub fn to_bibtex(&self) -> String {
+        let width = self.fields.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        let mut bibtex = format!("@{}{{{},\n", self.kind, self.key);
+        for (name, value) in &self.fields {
+            bibtex += &format!("  {:width$} = {{{}}},\n", name, value, width = width);
+        }
+        bibtex + "}\n"
+    }
+
+    /// Return the DOI of the entry, in lowercase and without the URL
+    fn doi(&self) -> Option<String> {
+        self.field("doi").map(|doi| normalize_doi(doi).to_lowercase())
+    }
+
+    /// Return the arXiv id of the entry, without its version
+    fn arxiv(&self) -> Option<String> {
+        self.field("eprint").map(|eprint| strip_arxiv_version(eprint.trim().trim_start_matches("arXiv:")).to_lowercase())
+    }
+
+    /// Check if two entries describe the same work: the same DOI, the same arXiv id,
+    /// or the same title and year when they have neither
+    pub fn same_work(&self, other: &Entry) -> bool {
+        if let (Some(doi), Some(other)) = (self.doi(), other.doi()) {
+            return doi == other;
+        }
+        if let (Some(arxiv), Some(other)) = (self.arxiv(), other.arxiv()) {
+            return arxiv == other;
+        }
+
+        let title = |entry: &Entry| entry.field("title").map(simplify).filter(|title| !title.is_empty());
+        title(self).is_some() && title(self) == title(other) && self.field("year") == other.field("year")
+    }
+}
+
+/// Implementation of the Identifier enum to read the identifiers
+impl Identifier {
+    /// Read a DOI or an arXiv id, with or without its prefix or its URL
+    ///
+    /// # Example
+    /// ```
+    /// assert_eq!(Identifier::parse("https://doi.org/10.1145/359545.359563"), Some(Identifier::Doi("10.1145/359545.359563".to_string())));
+    /// assert_eq!(Identifier::parse("arXiv:1706.03762"), Some(Identifier::Arxiv("1706.03762".to_string())));
+    /// ```
+    pub fn parse(text: &str) -> Option<Identifier> {
+        let text = text.trim();
+        let lower = text.to_lowercase();
+
+        let doi = normalize_doi(text);
+        if doi.starts_with("10.") && doi.contains('/') {
+            return Some(Identifier::Doi(doi.to_string()));
+        }
+
+        let arxiv = ["arxiv:", "https://arxiv.org/abs/", "http://arxiv.org/abs/"].iter()
+            .find_map(|prefix| lower.starts_with(prefix).then(|| &text[prefix.len()..]))
+            .unwrap_or(text);
+
+        // New ids like 1706.03762v2, old ids like hep-th/9901001
+        let new = arxiv.split_once('.').is_some_and(|(month, number)| {
+            let number = strip_arxiv_version(number);
+            month.len() == 4 && (4..=5).contains(&number.len()) && format!("{}{}", month, number).chars().all(|c| c.is_ascii_digit())
+        });
+        let old = arxiv.split_once('/').is_some_and(|(archive, number)| {
+            let number = strip_arxiv_version(number);
+            !archive.is_empty() && archive.chars().all(|c| c.is_ascii_alphabetic() || c == '-' || c == '.')
+                && number.len() == 7 && number.chars().all(|c| c.is_ascii_digit())
+        });
+
+        (new || old).then(|| Identifier::Arxiv(arxiv.to_string()))
+    }
+}
+
+/// Implementation of the Display trait to show the identifiers in the messages
+impl std::fmt::Display for Identifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Identifier::Doi(doi) => write!(f, "DOI {}", doi),
+            Identifier::Arxiv(id) => write!(f, "arXiv {}", id),
+        }
+    }
+}
+
+/// Implementation of the WebResolver struct to create it
+impl<'a> WebResolver<'a> {
+    /// Create a resolver that downloads the BibTeX with a fetcher
+    ///
+    /// # Arguments
+    /// * `fetcher` - Where the BibTeX comes from, like `SourceFetcher`
+    pub fn new(fetcher: &'a dyn Fetcher) -> WebResolver<'a> {
+        WebResolver { fetcher }
+    }
+}
+
+/// Implementation of the Resolver trait for the WebResolver struct
+impl Resolver for WebResolver<'_> {
+    fn resolve(&self, id: &Identifier) -> Result<Option<String>, String> {
+        let url = match id {
+            Identifier::Doi(doi) => CROSSREF_URL.replace("{}", doi),
+            Identifier::Arxiv(arxiv) => ARXIV_URL.replace("{}", arxiv),
+        };
+
+        let bytes = self.fetcher.fetch(&url).map_err(|err| format!("Failed to resolve the {}: {}", id, err))?;
+        String::from_utf8(bytes).map(Some).map_err(|_| format!("The BibTeX of the {} is not UTF-8", id))
+    }
+}
+
+/// Implementation of the LocalResolver struct to create it
+impl LocalResolver {
+    /// Create a resolver that reads a local BibTeX database
+    ///
+    /// # Arguments
+    /// * `path` - The BibTeX file
+    pub fn new(path: impl Into<PathBuf>) -> LocalResolver {
+        LocalResolver { path: path.into() }
+    }
+}
+
+/// Implementation of the Resolver trait for the LocalResolver struct
+impl Resolver for LocalResolver {
+    fn resolve(&self, id: &Identifier) -> Result<Option<String>, String> {
+        let content = std::fs::read_to_string(&self.path).map_err(|err| format!("Failed to read {}: {}", self.path.display(), err))?;
+        let entries = parse(&content).map_err(|err| format!("{}: {}", self.path.display(), err))?;
+
+        let found = entries.iter().find(|entry| match id {
+            Identifier::Doi(doi) => entry.doi().as_deref() == Some(doi.to_lowercase().as_str()),
+            Identifier::Arxiv(arxiv) => entry.arxiv().as_deref() == Some(strip_arxiv_version(arxiv).to_lowercase().as_str()),
+        });
+
+        trace!("{} {} in {}", if found.is_some() { "found" } else { "no" }, id, self.path.display());
+        Ok(found.map(Entry::to_bibtex))
+    }
+}
+
+/// Implementation of the Bibliography struct to add entries to a BibTeX file
+impl Bibliography {
+    /// Read a BibTeX file, a missing file is an empty bibliography
+    ///
+    /// # Example
+    /// ```
+    /// let bibliography = Bibliography::load(Path::new("lib/bibliography.bib"))?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the file can't be read or is not valid BibTeX
+    pub fn load(path: &Path) -> Result<Bibliography, String> {
+        let content = match path.exists() {
+            true => std::fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?,
+            false => String::new(),
+        };
+        let entries = parse(&content).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+        Ok(Bibliography { path: path.to_path_buf(), entries, content })
+    }
+
+    /// Return the entry of the bibliography that describes the same work, see `Entry::same_work`
+    pub fn duplicate_of(&self, entry: &Entry) -> Option<&Entry> {
+        self.entries.iter().find(|existing| existing.same_work(entry))
+    }
+
+    /// Add an entry with its normalized key, a letter is added to the key if another entry has it
+    ///
+    /// # Returns
+    /// The key of the new entry
+    ///
+    /// # Errors
+    /// This function returns an error if the bibliography already has the work
+    pub fn add(&mut self, mut entry: Entry) -> Result<String, String> {
+        if let Some(existing) = self.duplicate_of(&entry) {
+            let title = entry.field("title").unwrap_or(&entry.key);
+            return Err(format!("{} is already in the bibliography as {}", title, existing.key));
+        }
+
+        let base = entry.normalized_key();
+        let taken = |key: &str| self.entries.iter().any(|existing| existing.key.eq_ignore_ascii_case(key));
+        entry.key = match taken(&base) {
+            false => base,
+            true => ('a'..='z').map(|letter| format!("{}{}", base, letter))
+                .find(|key| !taken(key))
+                .unwrap_or_else(|| format!("{}{}", base, self.entries.len())),
+        };
+
+        // An empty line between the entries
+        match self.content.trim_end().is_empty() {
+            true => self.content.clear(),
+            false => self.content = format!("{}\n\n", self.content.trim_end()),
+        }
+        self.content += &entry.to_bibtex();
+
+        let key = entry.key.clone();
+        self.entries.push(entry);
+        Ok(key)
+    }
+
+    /// Write the bibliography to its file, creating its folder
+    ///
+    /// # Errors
+    /// This function returns an error if the file can't be written
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|err| format!("Failed to create the folder {}: {}", parent.display(), err))?;
+        }
+        std::fs::write(&self.path, &self.content).map_err(|err| format!("Failed to write {}: {}", self.path.display(), err))
+    }
+}
+
+/// Parse the entries of a BibTeX file
+/// The `@comment`, `@string` and `@preamble` blocks and the text between the entries are skipped
+///
+/// # Arguments
+/// * `content` - The content of the file
+///
+/// # Errors
+/// This function returns an error with the line of the entry that is not valid
+pub fn parse(content: &str) -> Result<Vec<Entry>, String> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut entries = Vec::new();
+    let mut i = 0;
+
+    while let Some(at) = chars[i..].iter().position(|&c| c == '@').map(|offset| i + offset) {
+        i = at + 1;
+        let line = chars[..at].iter().filter(|&&c| c == '\n').count() + 1;
+        let kind = read_while(&chars, &mut i, |c| c.is_ascii_alphanumeric() || c == '_').to_lowercase();
+        skip_whitespace(&chars, &mut i);
+
+        // An @ that doesn't start an entry, like in a comment
+        let close = match chars.get(i) {
+            Some('{') if !kind.is_empty() => '}',
+            Some('(') if !kind.is_empty() => ')',
+            _ => continue,
+        };
+
+        if matches!(kind.as_str(), "comment" | "string" | "preamble") {
+            i = matching(&chars, i, close).ok_or_else(|| format!("Line {}: the @{} is not closed", line, kind))? + 1;
+            continue;
+        }
+
+        i += 1;
+        skip_whitespace(&chars, &mut i);
+        let key = read_while(&chars, &mut i, |c| c != ',' && c != close && !c.is_whitespace());
+        if key.is_empty() {
+            return Err(format!("Line {}: the entry has no key", line));
+        }
+
+        let mut fields = Vec::new();
+        loop {
+            skip_whitespace(&chars, &mut i);
+            match chars.get(i) {
+                Some(',') => {
+                    i += 1;
+                    continue;
+                },
+                Some(&c) if c == close => {
+                    i += 1;
+                    break;
+                },
+                None => return Err(format!("Line {}: the entry {} is not closed", line, key)),
+                _ => {},
+            }
+
+            let name = read_while(&chars, &mut i, |c| c.is_ascii_alphanumeric() || "_-:.".contains(c)).to_lowercase();
+            skip_whitespace(&chars, &mut i);
+            if name.is_empty() || chars.get(i) != Some(&'=') {
+                return Err(format!("Line {}: the entry {} has an invalid field", line, key));
+            }
+
+            i += 1;
+            let value = read_value(&chars, &mut i).ok_or_else(|| format!("Line {}: the field {} of {} has no value", line, name, key))?;
+            fields.push((name, value));
+        }
+
+        entries.push(Entry { kind, key, fields });
+    }
+
+    Ok(entries)
+}
+
+/// Return the entries of a source given to `lth bib add`
+///
+/// # Arguments
+/// * `source` - A BibTeX snippet, a `.bib` file, a DOI or an arXiv id
+/// * `resolvers` - The resolvers of the DOIs and the arXiv ids, tried in order
+///
+/// # Example
+/// ```
+/// let entries = entries_of("10.1145/359545.359563", &[&LocalResolver::new("references.bib"), &WebResolver::new(&SourceFetcher)])?;
+/// ```
+///
+/// # Errors
+/// This function returns an error if the source is not valid or no resolver knows the work
+pub fn entries_of(source: &str, resolvers: &[&dyn Resolver]) -> Result<Vec<Entry>, String> {
+    let bibtex = match (source.trim_start().starts_with('@'), Identifier::parse(source)) {
+        (true, _) => source.to_string(),
+        (false, _) if Path::new(source).is_file() => std::fs::read_to_string(source).map_err(|err| format!("Failed to read {}: {}", source, err))?,
+        (false, Some(id)) => {
+            let mut found = None;
+            for resolver in resolvers {
+                if let Some(bibtex) = resolver.resolve(&id)? {
+                    found = Some(bibtex);
+                    break;
+                }
+            }
+            found.ok_or_else(|| format!("No resolver knows the {}", id))?
+        },
+        (false, None) => return Err(format!("{} is not a BibTeX entry, a .bib file, a DOI or an arXiv id", source)),
+    };
+
+    let entries = parse(&bibtex)?;
+    match entries.is_empty() {
+        true => Err(format!("There is no BibTeX entry in {}", source)),
+        false => Ok(entries),
+    }
+}
+
+/// Find the bibliography of a project: `lib/bibliography.bib`, or its only `.bib` file
+///
+/// # Example
+/// ```
+/// let path = find_bibliography(Path::new("paper")).unwrap_or_else(|| PathBuf::from("paper/lib/bibliography.bib"));
+/// ```
+pub fn find_bibliography(dir: &Path) -> Option<PathBuf> {
+    if dir.join(DEFAULT_BIBLIOGRAPHY).is_file() {
+        return Some(dir.join(DEFAULT_BIBLIOGRAPHY));
+    }
+
+    let files: Vec<String> = project_files(dir, dir).into_iter().filter(|file| file.ends_with(".bib")).collect();
+    match files.as_slice() {
+        [file] => Some(dir.join(file)),
+        _ => None,
+    }
+}
+
+/// Return the citations in the sources of a project:
+/// the `\cite` commands of LaTeX and biblatex, and the `[@key]` citations of pandoc in the Markdown and YAML files
+///
+/// # Example
+/// ```
+/// let citations = citations(Path::new("paper"));
+/// ```
+pub fn citations(dir: &Path) -> Vec<Citation> {
+    let mut citations = Vec::new();
+
+    for file in project_files(dir, dir) {
+        let extension = Path::new(&file).extension().map(|extension| extension.to_string_lossy().to_lowercase()).unwrap_or_default();
+        if !SOURCE_EXTENSIONS.contains(&extension.as_str()) {
+            continue;
+        }
+        let content = match std::fs::read_to_string(dir.join(&file)) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+
+        let latex = matches!(extension.as_str(), "tex" | "ltx");
+        for (number, line) in content.lines().enumerate() {
+            let mut keys = match latex {
+                true => latex_citations(strip_comment(line)),
+                false => latex_citations(line),
+            };
+            if !latex {
+                keys.extend(pandoc_citations(line));
+            }
+
+            citations.extend(keys.into_iter().map(|key| Citation { key, file: file.clone(), line: number + 1 }));
+        }
+    }
+
+    citations
+}
+
+/// Compare the citations of a project with its bibliography
+/// `\nocite{*}` and `nocite: '@*'` cite every entry
+///
+/// # Arguments
+/// * `dir` - The folder of the project
+/// * `bibliography` - The bibliography of the project
+///
+/// # Example
+/// ```
+/// let report = check(Path::new("paper"), &Bibliography::load(&path)?);
+/// ```
+pub fn check(dir: &Path, bibliography: &Bibliography) -> Report {
+    let citations = citations(dir);
+    let everything = citations.iter().any(|citation| citation.key == "*");
+
+    Report {
+        missing: citations.iter()
+            .filter(|citation| citation.key != "*" && !bibliography.entries.iter().any(|entry| entry.key == citation.key))
+            .cloned()
+            .collect(),
+        unused: bibliography.entries.iter()
+            .filter(|entry| !everything && !citations.iter().any(|citation| citation.key == entry.key))
+            .map(|entry| entry.key.clone())
+            .collect(),
+    }
+}
+
+/// Return the keys cited with the LaTeX commands that have `cite` in their name, like `\citep[p. 3]{a,b}`
+fn latex_citations(line: &str) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut keys = Vec::new();
+    let mut i = 0;
+
+    while let Some(slash) = chars[i..].iter().position(|&c| c == '\\').map(|offset| i + offset) {
+        i = slash + 1;
+        let command = read_while(&chars, &mut i, |c| c.is_ascii_alphabetic());
+        if !command.to_lowercase().contains("cite") {
+            continue;
+        }
+        if chars.get(i) == Some(&'*') {
+            i += 1;
+        }
+
+        // The optional arguments, then one group of keys, or several for commands like \cites
+        loop {
+            skip_whitespace(&chars, &mut i);
+            let close = match chars.get(i) {
+                Some('[') => ']',
+                Some('{') => '}',
+                _ => break,
+            };
+            let end = match matching(&chars, i, close) {
+                Some(end) => end,
+                None => break,
+            };
+            if close == '}' {
+                let group: String = chars[i + 1..end].iter().collect();
+                keys.extend(group.split(',').map(str::trim).filter(|key| !key.is_empty()).map(String::from));
+            }
+            i = end + 1;
+        }
+    }
+
+    keys
+}
+
+/// Return the keys cited with the syntax of pandoc, like `[@key, p. 3]`, `@key` or `@{key}`
+fn pandoc_citations(line: &str) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut keys = Vec::new();
+
+    for (at, _) in chars.iter().enumerate().filter(|(_, &c)| c == '@') {
+        // An email address or an escaped @ is not a citation
+        if at > 0 && (chars[at - 1].is_alphanumeric() || chars[at - 1] == '\\') {
+            continue;
+        }
+
+        let mut i = at + 1;
+        let key = match chars.get(i) {
+            Some('*') => "*".to_string(),
+            Some('{') => match matching(&chars, i, '}') {
+                Some(end) => chars[i + 1..end].iter().collect(),
+                None => continue,
+            },
+            Some(&c) if c.is_alphanumeric() || c == '_' => {
+                let key = read_while(&chars, &mut i, |c| c.is_alphanumeric() || "_:.#$%&-+?<>~/".contains(c));
+                key.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_').to_string()
+            },
+            _ => continue,
+        };
+
+        if !key.is_empty() && !CROSSREF_PREFIXES.iter().any(|prefix| key.starts_with(prefix)) {
+            keys.push(key);
+        }
+    }
+
+    keys
+}
+
+/// Read the value of a field: a group in braces, a string in quotes, a number or a macro,
+/// or several of them joined with `#`
+fn read_value(chars: &[char], i: &mut usize) -> Option<String> {
+    let mut value = String::new();
+    loop {
+        skip_whitespace(chars, i);
+        match chars.get(*i) {
+            Some('{') => {
+                let end = matching(chars, *i, '}')?;
+                value.extend(&chars[*i + 1..end]);
+                *i = end + 1;
+            },
+            Some('"') => {
+                // Quotes inside braces don't end the string
+                let mut depth = 0;
+                let mut end = *i + 1;
+                while let Some(&c) = chars.get(end) {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        '"' if depth == 0 => break,
+                        _ => {},
+                    }
+                    end += 1;
+                }
+                chars.get(end)?;
+                value.extend(&chars[*i + 1..end]);
+                *i = end + 1;
+            },
+            Some(c) if c.is_ascii_alphanumeric() => value += &read_while(chars, i, |c| c.is_ascii_alphanumeric() || "_-:.".contains(c)),
+            _ => return None,
+        }
+
+        skip_whitespace(chars, i);
+        match chars.get(*i) {
+            Some('#') => *i += 1,
+            _ => return Some(value),
+        }
+    }
+}
+
+/// Return the position of the character that closes the one at `open`, counting the nested braces
+fn matching(chars: &[char], open: usize, close: char) -> Option<usize> {
+    let mut depth = 0;
+    for (index, &c) in chars.iter().enumerate().skip(open + 1) {
+        match c {
+            c if c == close && depth == 0 => return Some(index),
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {},
+        }
+    }
+    None
+}
+
+/// Read the characters that match a condition, moving the position after them
+fn read_while(chars: &[char], i: &mut usize, condition: impl Fn(char) -> bool) -> String {
+    let start = *i;
+    while chars.get(*i).is_some_and(|&c| condition(c)) {
+        *i += 1;
+    }
+    chars[start..*i].iter().collect()
+}
+
+/// Move the position after the whitespace
+fn skip_whitespace(chars: &[char], i: &mut usize) {
+    read_while(chars, i, char::is_whitespace);
+}
+
+/// Return the letters and digits of a text in lowercase, without the LaTeX commands and braces
+fn simplify(text: &str) -> String {
+    text.chars().filter(char::is_ascii_alphanumeric).map(|c| c.to_ascii_lowercase()).collect()
+}
+
+/// Return a DOI without its `doi:` prefix or its URL
+fn normalize_doi(doi: &str) -> &str {
+    let doi = doi.trim();
+    ["https://doi.org/", "http://doi.org/", "https://dx.doi.org/", "http://dx.doi.org/", "doi:"].iter()
+        .find_map(|prefix| doi.get(..prefix.len()).filter(|start| start.eq_ignore_ascii_case(prefix)).map(|_| &doi[prefix.len()..]))
+        .unwrap_or(doi)
+        .trim()
+}
+
+/// Return an arXiv id without its version, like `1706.03762` for `1706.03762v5`
+fn strip_arxiv_version(id: &str) -> &str {
+    match id.rsplit_once('v') {
+        Some((base, version)) if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) && !base.is_empty() => base,
+        _ => id,
+    }
+}
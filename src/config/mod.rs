@@ -1,14 +1,120 @@
-use crate::utils::*;
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+
+use lth::authoring::{build, import, lint, load, render, skeleton, Severity};
+use lth::bib::{check, entries_of, find_bibliography, Bibliography, LocalResolver, Resolver, WebResolver, DEFAULT_BIBLIOGRAPHY};
+use lth::doctor::*;
+use lth::hooks::{enabled_hooks, hook_sources, run_hooks};
+use lth::index::{combined, find_template, Filters};
+use lth::info::{show_info, TemplateInfo};
+use lth::output::{self, paint, print_json, Color, Output, OutputFormat, Verbosity};
+use lth::preflight::*;
+use lth::prompt::confirm;
+use lth::publish::{publish, Bump, Target};
+use lth::trust::TrustStore;
+use lth::versions::available;
+use lth::serve::Server;
+use lth::settings::{template_dirs, Settings};
+use lth::styles::{find_style, set_style, styles, Style};
+use lth::{detail, error, status, trace, warning};
+use lth::git::commit_changes;
+use lth::templates::{Answers, Backend};
+use lth::{Event, Scaffolder, SourceFetcher, Template, TemplateRegistry};
+
+/// Commands of the application
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Create a new project
+    New,
+    /// Check the environment, or the requirements of a template
+    Doctor,
+    /// Show the information of a template
+    Info,
+    /// Show the available templates
+    List,
+    /// Show the version of the program
+    Version,
+    /// Create the skeleton of a new template
+    TemplateNew,
+    /// Create a template from an existing project
+    TemplateImport,
+    /// Check the manifest and the files of a template
+    TemplateLint,
+    /// Create a project with a template in a temporary folder, and optionally build it
+    TemplateTest,
+    /// Publish a new version of a template to a git registry or a server
+    TemplatePublish,
+    /// Trust a template source to run hooks, or show the trust decisions
+    Trust,
+    /// Forget the trust decision of a template source, so it is asked again
+    Untrust,
+    /// Search the templates of the registry and of the configured indexes
+    Search,
+    /// Share a folder of templates over HTTP
+    Serve,
+    /// Add references to the bibliography of a project
+    BibAdd,
+    /// Compare the citations of a project with its bibliography
+    BibCheck,
+    /// Show the available citation styles
+    StyleList,
+    /// Change the citation style of a pandoc project
+    StyleSet,
+}
 
 /// Struct to manage initial configuration of the application
 pub struct Config {
-    template : String,
+    command: Command,
+    template : Option<String>,
     path: String,
+    name: Option<String>,
+    collision: Collision,
+    dry_run: bool,
+    /// Format of the dry run plan, the output format if it is not given
+    plan_format: Option<OutputFormat>,
+    ignore_requirements: bool,
+    /// Build the test project of `lth template test`
+    build: bool,
+    /// Keep the test project of `lth template test`
+    keep: bool,
+    /// Features turned on with `--with` and off with `--without`, by name
+    features: Answers,
+    /// Never run the hooks of the template
+    no_hooks: bool,
+    /// Show the available versions in the list of templates
+    versions: bool,
+    /// Tags, language and document type given to `lth search`
+    filters: Filters,
+    /// Port of `lth serve`
+    port: u16,
+    /// Part of the version increased by `lth template publish`
+    bump: Bump,
+    /// Where `lth template publish` sends the template, instead of the `publish` setting
+    to: Option<String>,
+    /// Token of the uploads to `lth serve`, `LTH_TOKEN` if it is not given
+    token: Option<String>,
+    /// BibTeX file of `lth bib`, instead of the bibliography found in the project
+    bib: Option<String>,
+    /// Citation style installed in the new pandoc project
+    style: Option<String>,
+}
+
+/// Struct with the version and build information shown by `lth --version --output json`
+#[derive(Debug, Serialize)]
+pub struct VersionInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub profile: &'static str,
 }
 
 /// Implementation of the Config struct to manage initial configuration of the application
 impl Config {
     /// Create a new Config struct, in case of error return the error
+    /// The output format given with `--output` is set for the whole program
     ///
     /// # Arguments
     /// * `args` - A vector of `String` containing the arguments passed to the program
@@ -21,31 +127,162 @@ impl Config {
     /// # Panics
     /// This function will panic if the arguments are not valid
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        // Check if the user is asking for the program version
-        if args.len() == 2 && (args[1] == "-v" || args[1] == "--version") {
-            show_version();
-            std::process::exit(0);
-        }
-
         // Check if the user is asking for the program help
         if args.contains(&String::from("-h")) || args.contains(&String::from("--help")) {
             return Err("help");
         }
 
-        // Check if the user is asking for the available templates
-        if args.contains(&String::from("-l")) || args.contains(&String::from("--list")) {
-            return Err("list");
-        }
+        // Split the options from the positional arguments
+        let mut positional = Vec::new();
+        let mut command = None;
+        let mut name = None;
+        let mut collision = Collision::Abort;
+        let mut dry_run = false;
+        let mut plan_format = None;
+        let mut output = OutputFormat::Text;
+        let mut verbosity = Verbosity::Normal;
+        let mut log_file = None;
+        let mut ignore_requirements = false;
+        let mut build = false;
+        let mut keep = false;
+        let mut features = Answers::new();
+        let mut no_hooks = false;
+        let mut versions = false;
+        let mut filters = Filters::default();
+        let mut port = 8080;
+        let mut bump = Bump::Patch;
+        let mut to = None;
+        let mut token = None;
+        let mut bib = None;
+        let mut style = None;
 
-        // Check if the user do a correct call to the program
-        if args.len() != 3 {
-            return Err("bad usage");
+        let mut iter = args.iter().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-v" | "--version" => command = Some(Command::Version),
+                "-l" | "--list" => command = Some(Command::List),
+                "--name" => name = Some(iter.next().ok_or("missing value for --name")?.clone()),
+                "--force" | "--merge" if collision != Collision::Abort => {
+                    return Err("--force and --merge can't be used together");
+                },
+                "--force" => collision = Collision::Force,
+                "--merge" => collision = Collision::Merge,
+                "--dry-run" => dry_run = true,
+                "--plan-format" => {
+                    plan_format = Some(iter.next().and_then(|value| OutputFormat::parse(value)).ok_or("--plan-format must be text or json")?);
+                },
+                "--output" => output = iter.next().and_then(|value| OutputFormat::parse(value)).ok_or("--output must be text or json")?,
+                "--json" => output = OutputFormat::Json,
+                "-q" | "--quiet" => verbosity = Verbosity::Quiet,
+                // --verbose twice is the same as -vv
+                "--verbose" if verbosity == Verbosity::Verbose => verbosity = Verbosity::Trace,
+                "--verbose" => verbosity = Verbosity::Verbose,
+                "-vv" => verbosity = Verbosity::Trace,
+                "--log-file" => log_file = Some(iter.next().ok_or("missing value for --log-file")?.clone()),
+                "--ignore-requirements" => ignore_requirements = true,
+                "--build" => build = true,
+                "--keep" => keep = true,
+                "--no-hooks" => no_hooks = true,
+                "--versions" => versions = true,
+                // --tag can be repeated, or take several tags separated by commas
+                "--tag" => {
+                    let tags = iter.next().ok_or("missing value for --tag")?;
+                    filters.tags.extend(tags.split(',').map(str::trim).filter(|tag| !tag.is_empty()).map(String::from));
+                },
+                "--lang" => filters.language = Some(iter.next().ok_or("missing value for --lang")?.clone()),
+                "--type" => filters.document = Some(iter.next().ok_or("missing value for --type")?.clone()),
+                "--port" => port = iter.next().and_then(|value| value.parse().ok()).ok_or("--port must be a number between 0 and 65535")?,
+                "--bump" => bump = iter.next().and_then(|value| Bump::parse(value)).ok_or("--bump must be major, minor, patch or none")?,
+                "--to" => to = Some(iter.next().ok_or("missing value for --to")?.clone()),
+                "--token" => token = Some(iter.next().ok_or("missing value for --token")?.clone()),
+                "--bib" => bib = Some(iter.next().ok_or("missing value for --bib")?.clone()),
+                "--style" => style = Some(iter.next().ok_or("missing value for --style")?.clone()),
+                // --with and --without take one feature, or several separated by commas
+                "--with" | "--without" => {
+                    let value = if arg == "--with" { "yes" } else { "no" };
+                    let names = iter.next().ok_or("missing value for --with or --without")?;
+                    for name in names.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+                        features.insert(name.to_string(), value.to_string());
+                    }
+                },
+                option if option.starts_with("--") => return Err("unknown option"),
+                _ => positional.push(arg.clone()),
+            }
         }
 
-        let template = args[1].clone();
-        let path = args[2].clone();
+        let log_file = log_file.map(|path| output::open_log_file(&path).unwrap_or_else(|err| {
+            eprintln!("{}", output::paint_err(Color::Red, &err));
+            std::process::exit(1);
+        }));
+
+        output::init(Output { format: output, verbosity, log_file });
 
-        Ok(Config { template , path })
+        let (command, template, path) = match (command, positional.first().map(String::as_str)) {
+            // The version and the list don't take arguments
+            (Some(command), _) if !positional.is_empty() => return Err(match command {
+                Command::Version => "--version doesn't take arguments",
+                _ => "--list doesn't take arguments",
+            }),
+            (Some(command), _) => (command, None, String::from(".")),
+            // lth list, the same as --list
+            (None, Some("list")) if positional.len() == 1 => (Command::List, None, String::from(".")),
+            // lth doctor [TEMPLATE]
+            (None, Some("doctor")) if positional.len() <= 2 => (Command::Doctor, positional.get(1).cloned(), String::from(".")),
+            // lth info TEMPLATE
+            (None, Some("info")) if positional.len() == 2 => (Command::Info, positional.get(1).cloned(), String::from(".")),
+            // lth new [TEMPLATE] [PATH], without a template the template picker is shown
+            (None, Some("new")) if positional.len() <= 3 => {
+                let path = positional.get(2).cloned().unwrap_or_else(|| String::from("."));
+                (Command::New, positional.get(1).cloned(), path)
+            },
+            // lth template new NAME [PATH]
+            (None, Some("template")) if positional.get(1).map(String::as_str) == Some("new") && (3..=4).contains(&positional.len()) => {
+                let path = positional.get(3).cloned().unwrap_or_else(|| String::from("."));
+                (Command::TemplateNew, positional.get(2).cloned(), path)
+            },
+            // lth template import DIR
+            (None, Some("template")) if positional.get(1).map(String::as_str) == Some("import") && positional.len() == 3 => {
+                (Command::TemplateImport, None, positional[2].clone())
+            },
+            // lth template lint [DIR], lth template test [DIR] and lth template publish [DIR]
+            (None, Some("template")) if positional.len() <= 3 => {
+                let command = match positional.get(1).map(String::as_str) {
+                    Some("lint") => Command::TemplateLint,
+                    Some("test") => Command::TemplateTest,
+                    Some("publish") => Command::TemplatePublish,
+                    _ => return Err("bad usage"),
+                };
+                (command, None, positional.get(2).cloned().unwrap_or_else(|| String::from(".")))
+            },
+            // lth search [QUERY...], the words of the query don't need quotes
+            (None, Some("search")) => (Command::Search, Some(positional[1..].join(" ")), String::from(".")),
+            // lth serve [DIR]
+            (None, Some("serve")) if positional.len() <= 2 => (Command::Serve, None, positional.get(1).cloned().unwrap_or_else(|| String::from("."))),
+            // lth trust [SOURCE] and lth untrust SOURCE, the source can be the name of a template
+            (None, Some("trust")) if positional.len() <= 2 => (Command::Trust, positional.get(1).cloned(), String::from(".")),
+            (None, Some("untrust")) if positional.len() == 2 => (Command::Untrust, positional.get(1).cloned(), String::from(".")),
+            // lth bib add SOURCE and lth bib check [DIR], the bibliography is searched in the current folder
+            (None, Some("bib")) if positional.get(1).map(String::as_str) == Some("add") && positional.len() == 3 => {
+                (Command::BibAdd, positional.get(2).cloned(), String::from("."))
+            },
+            (None, Some("bib")) if positional.get(1).map(String::as_str) == Some("check") && positional.len() <= 3 => {
+                (Command::BibCheck, None, positional.get(2).cloned().unwrap_or_else(|| String::from(".")))
+            },
+            // lth style list and lth style set STYLE [DIR], the style goes in the template
+            (None, Some("style")) if positional.get(1).map(String::as_str) == Some("list") && positional.len() == 2 => {
+                (Command::StyleList, None, String::from("."))
+            },
+            (None, Some("style")) if positional.get(1).map(String::as_str) == Some("set") && (3..=4).contains(&positional.len()) => {
+                (Command::StyleSet, positional.get(2).cloned(), positional.get(3).cloned().unwrap_or_else(|| String::from(".")))
+            },
+            (None, Some("bib" | "doctor" | "info" | "list" | "new" | "serve" | "style" | "template" | "trust" | "untrust")) => return Err("bad usage"),
+            // lth TEMPLATE PATH
+            (None, _) if positional.len() == 2 => (Command::New, Some(positional[0].clone()), positional[1].clone()),
+            (None, _) => return Err("bad usage"),
+        };
+
+        Ok(Config { command, template, path, name, collision, dry_run, plan_format, ignore_requirements, build, keep, features, no_hooks, versions, filters, port, bump, to,
+            token: token.or_else(|| std::env::var("LTH_TOKEN").ok()), bib, style })
     }
 
     /// Run the application
@@ -55,11 +292,621 @@ impl Config {
     /// config.run();
     /// ```
     pub fn run(&self) {
+        if self.command == Command::Doctor {
+            let healthy = match &self.template {
+                Some(template) => doctor_template(&check_template(template)),
+                None => doctor(),
+            };
+
+            std::process::exit(if healthy { 0 } else { 1 });
+        }
+
+        if self.command == Command::Info {
+            if let Some(template) = &self.template {
+                show_info(&check_template(template), output::is_json());
+            }
+            return;
+        }
+
+        if self.command == Command::List {
+            template_list(self.versions);
+            return;
+        }
+
+        if self.command == Command::Version {
+            show_version();
+            return;
+        }
+
+        match self.command {
+            Command::TemplateNew => return self.template_new(),
+            Command::TemplateImport => return self.template_import(),
+            Command::TemplateLint => std::process::exit(if template_lint(&self.path) { 0 } else { 1 }),
+            Command::TemplateTest => return self.template_test(),
+            Command::TemplatePublish => return self.template_publish(),
+            Command::Serve => return serve(&self.path, self.port, self.token.as_deref()),
+            Command::Search => return search(self.template.as_deref().unwrap_or(""), &self.filters),
+            Command::BibAdd => return self.bib_add(),
+            Command::BibCheck => return self.bib_check(),
+            Command::StyleList => return style_list(),
+            Command::StyleSet => return self.style_set(),
+            Command::Trust => return trust(self.template.as_deref(), true),
+            Command::Untrust => return trust(self.template.as_deref(), false),
+            _ => {},
+        }
+
         // Check if path is a directory or a file
         check_path(&self.path);
 
-        // Check if the option is valid
-        check_template(&self.template, &self.path);
+        // Check the folder given with --name before any prompt
+        if let Some(name) = &self.name {
+            check_target(&self.path, name, self.collision).unwrap_or_else(|err| {
+                error!("{}", err);
+                std::process::exit(1);
+            });
+        }
+
+        let (template, name, answers) = match &self.template {
+            Some(template) => {
+                // Check if the option is valid
+                let template = check_template(template);
+                self.check_features(&template);
+                self.check_style(&template);
+                self.check_requirements(&template);
+
+                // A dry run or a JSON output for tooling can't ask anything,
+                // so the folder is named after the template and the variables take their defaults
+                let interactive = !(self.dry_run || output::is_json()) || std::io::stdin().is_terminal();
+
+                let name = match &self.name {
+                    Some(name) => name.clone(),
+                    None if interactive => ask_folder_name(&self.path, self.collision),
+                    None => template.name.clone(),
+                };
+
+                if !self.dry_run {
+                    show_summary(&format!("{}/{}", self.path, name), &template);
+                }
+
+                let answers = match interactive {
+                    true => template.ask(&self.features),
+                    false => template.defaults().into_iter().chain(self.features.clone()).collect(),
+                };
+
+                (template, name, answers)
+            },
+            // The template picker uses stdout, so it can't be used with the JSON output
+            None if output::is_json() => {
+                error!("A template is needed with --output json");
+                std::process::exit(1);
+            },
+            // Let the user choose the template
+            None => match pick_template(&self.path, self.name.as_deref(), &self.features, self.collision) {
+                Some(mut selection) => {
+                    self.check_features(&selection.template);
+                    self.check_style(&selection.template);
+                    selection.answers.extend(self.features.clone());
+                    self.check_requirements(&selection.template);
+                    if !self.dry_run {
+                        show_summary(&format!("{}/{}", self.path, selection.name), &selection.template);
+                    }
+                    (selection.template, selection.name, selection.answers)
+                },
+                None => return,
+            },
+        };
+
+        let scaffolder = Scaffolder::new(template.clone())
+            .path(self.path.as_str())
+            .name(name)
+            .answers(answers.clone())
+            .collision(self.collision)
+            .on_event(print_event);
+
+        // Print the plan without writing anything
+        if self.dry_run {
+            let plan = scaffolder.measured_plan().unwrap_or_else(|err| {
+                error!("{}", err);
+                std::process::exit(1);
+            });
+            plan.print(self.plan_format.unwrap_or(output::format()));
+            return;
+        }
+
+        status!("Creating the new template at {}", self.path);
+        let mut project = scaffolder.create().unwrap_or_else(|err| {
+            error!("{}", err);
+            std::process::exit(1);
+        });
+
+        status!("Created the new folder at {}", project.path);
+
+        // The style goes in its own commit, on top of the files of the template
+        if let Some(style) = self.check_style(&template) {
+            let (file, commit) = install_style(&project.path, &style, project.git_commit.is_some());
+            if !project.files.contains(&file) {
+                project.files.push(file);
+            }
+            project.git_commit = commit.or(project.git_commit);
+        }
+
+        match output::format() {
+            OutputFormat::Json => print_json(&project),
+            OutputFormat::Text if output::verbosity() > Verbosity::Quiet => show_tree(&project.path),
+            OutputFormat::Text => {},
+        }
+
+        self.run_hooks(&template, &answers, &project.path);
+    }
+
+    /// Run the hooks of the template in the new project, after the user confirms them
+    /// The hooks of a source run only if the user trusts it, the decision is asked once and saved
+    /// Without a terminal the hooks can't be confirmed, so they are skipped
+    ///
+    /// # Arguments
+    /// * `template` - The template used to create the project
+    /// * `answers` - The answers of the user, some hooks depend on them
+    /// * `path` - The folder of the new project
+    ///
+    /// # Panics
+    /// This function will exit the program if a hook fails
+    fn run_hooks(&self, template: &Template, answers: &Answers, path: &str) {
+        let hooks = enabled_hooks(template, answers);
+        if hooks.is_empty() {
+            return;
+        }
+
+        if self.no_hooks {
+            detail!("Skipped the {} hook(s) of {}", hooks.len(), template.name);
+            return;
+        }
+
+        if !std::io::stdin().is_terminal() {
+            warning!("Skipped the hooks of {}, they can only be confirmed in a terminal", template.name);
+            return;
+        }
+
+        let mut store = TrustStore::load().unwrap_or_else(|err| {
+            warning!("{}", err);
+            TrustStore::default()
+        });
+
+        status!("The template {} wants to run these commands in {}:", template.name, path);
+        for hook in &hooks {
+            status!("\t{}", hook.describe());
+        }
+
+        // Ask once for each new source, a template can inherit hooks from other sources
+        let sources = hook_sources(&hooks, template);
+        let unknown: Vec<&str> = sources.iter().copied().filter(|source| store.decision(source).is_none()).collect();
+        for source in &unknown {
+            warning!("The hooks come from {}, a source you haven't trusted yet", source);
+            store.set(source, confirm("Do you trust this source to run commands on your computer?", false));
+        }
+
+        if !unknown.is_empty() {
+            store.save().unwrap_or_else(|err| warning!("{}", err));
+        }
+
+        for source in sources.iter().filter(|source| !store.is_trusted(source)) {
+            warning!("Skipped the hooks from {}, it isn't trusted (use lth trust to trust it)", source);
+        }
+
+        let hooks: Vec<_> = hooks.into_iter().filter(|hook| store.is_trusted(template.hook_source(hook))).collect();
+        if hooks.is_empty() || !confirm("Do you want to run them?", false) {
+            return;
+        }
+
+        run_hooks(&hooks, path, template).unwrap_or_else(|err| {
+            error!("{}", err);
+            std::process::exit(1);
+        });
+    }
+
+    /// Check the requirements of the template before asking anything
+    /// Missing optional requirements are only warnings, the dry runs are never stopped
+    ///
+    /// # Arguments
+    /// * `template` - The template used to create the project
+    ///
+    /// # Panics
+    /// This function will exit the program if a required executable, package or font is missing,
+    /// unless `--ignore-requirements` is used
+    fn check_requirements(&self, template: &Template) {
+        if self.dry_run || meets_requirements(template) {
+            return;
+        }
+
+        if self.ignore_requirements {
+            warning!("Creating {} without its requirements, the documents may not build", template.name);
+            return;
+        }
+
+        error!("Install the missing requirements, or use --ignore-requirements to create the project anyway");
+        std::process::exit(1);
+    }
+
+    /// Check that the features given with `--with` and `--without` exist in the template
+    ///
+    /// # Panics
+    /// This function will exit the program if a feature doesn't exist
+    fn check_features(&self, template: &Template) {
+        for name in self.features.keys() {
+            if template.feature(name).is_none() {
+                let available: Vec<&str> = template.features.iter().map(|feature| feature.name.as_str()).collect();
+                error!("The template {} has no feature called {}", template.name, name);
+                eprintln!("Available features: {}", if available.is_empty() { "none".to_string() } else { available.join(", ") });
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Check the style given with `--style`: it must exist and the template must use pandoc
+    ///
+    /// # Returns
+    /// The style to install in the new project, if one was given
+    ///
+    /// # Panics
+    /// This function will exit the program if the style can't be used with the template
+    fn check_style(&self, template: &Template) -> Option<Style> {
+        let name = self.style.as_deref()?;
+
+        if template.backend() != Backend::Pandoc {
+            error!("The template {} uses {}, the citation styles are only for the pandoc templates", template.name, template.backend().name());
+            std::process::exit(1);
+        }
+
+        let style = styles().and_then(|styles| find_style(&styles, name).cloned()).unwrap_or_else(|err| {
+            error!("{}", err);
+            std::process::exit(1);
+        });
+
+        Some(style)
+    }
+
+    /// Change the citation style of a pandoc project with `lth style set`
+    /// The style is committed when the project is a git repository
+    ///
+    /// # Output
+    /// ```text
+    /// Set the citation style of paper to APA 7 (lth) (lib/apa.csl)
+    /// ```
+    ///
+    /// # Panics
+    /// This function will exit the program if the style can't be installed
+    fn style_set(&self) {
+        let name = self.template.as_deref().unwrap_or_default();
+        let style = styles().and_then(|styles| find_style(&styles, name).cloned()).unwrap_or_else(|err| {
+            error!("{}", err);
+            std::process::exit(1);
+        });
+
+        let commit = Path::new(&self.path).join(".git").exists();
+        let (file, _) = install_style(&self.path, &style, commit);
+
+        if output::is_json() {
+            print_json(&BTreeMap::from([("style", style.name.as_str()), ("file", file.as_str())]));
+        }
+    }
+
+    /// Create the skeleton of a new template with `lth template new`
+    ///
+    /// # Panics
+    /// This function will exit the program if the skeleton can't be created
+    fn template_new(&self) {
+        let name = self.template.as_deref().unwrap_or_default();
+        let dir = skeleton(&self.path, name).unwrap_or_else(|err| {
+            error!("{}", err);
+            std::process::exit(1);
+        });
+
+        status!("Created the template {} at {}", name, dir.display());
+        status!("Edit its template.toml, then check it with lth template lint {}", dir.display());
+    }
+
+    /// Create a template from an existing project with `lth template import`
+    /// The template is written in `~/.config/lth/templates`, named after the project unless `--name` is given
+    ///
+    /// # Panics
+    /// This function will exit the program if the template can't be created
+    fn template_import(&self) {
+        let project = Path::new(&self.path);
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => project.canonicalize().ok()
+                .and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+                .unwrap_or_default(),
+        };
+
+        let templates = template_dirs().into_iter().next().unwrap_or_else(|| {
+            error!("Can't find the templates folder, HOME is not set");
+            std::process::exit(1);
+        });
+
+        let dir = import(project, &templates.to_string_lossy(), &name).unwrap_or_else(|err| {
+            error!("{}", err);
+            std::process::exit(1);
+        });
+
+        status!("Imported {} as the template {} at {}", self.path, name, dir.display());
+        template_lint(&dir.to_string_lossy());
+        status!("Create a project with it using lth new {}", name);
+    }
+
+    /// Render a template in a temporary folder with `lth template test`, and build it with `--build`
+    /// The temporary folder is removed unless `--keep` is used
+    ///
+    /// # Panics
+    /// This function will exit the program if the template has errors, or if it can't be rendered or built
+    fn template_test(&self) {
+        if !template_lint(&self.path) {
+            std::process::exit(1);
+        }
+
+        let template = load(Path::new(&self.path)).unwrap_or_else(|err| {
+            error!("{}", err);
+            std::process::exit(1);
+        });
+
+        let project = render(&template).unwrap_or_else(|err| {
+            error!("{}", err);
+            std::process::exit(1);
+        });
+        status!("Rendered {} at {}", template.name, project.path);
+
+        let result = match self.build {
+            true => build(&project.path),
+            false => Ok(()),
+        };
+
+        let root = Path::new(&project.path).parent().unwrap_or(Path::new(&project.path));
+        match self.keep {
+            true => status!("Kept the test project at {}", project.path),
+            false => {
+                let _ = std::fs::remove_dir_all(root);
+            },
+        }
+
+        match result {
+            Ok(()) if self.build => status!("Built {} successfully", template.name),
+            Ok(()) => {},
+            Err(err) => {
+                error!("{}", err);
+                std::process::exit(1);
+            },
+        }
+
+        if output::is_json() {
+            print_json(&project);
+        }
+    }
+}
+
+/// Implementation of the Config struct to publish the templates
+impl Config {
+    /// Publish a new version of a template with `lth template publish`
+    /// The template goes to `--to`, or to the `publish` setting of the `[registry]` section;
+    /// with `--dry-run` the archive is only packed and the manifest is not changed
+    ///
+    /// # Output
+    /// ```text
+    /// Published thesis 1.3.0 to git@github.com:lab/latex-templates.git with the tag thesis-v1.3.0
+    /// thesis-1.3.0.tar.gz sha256 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08
+    /// ```
+    ///
+    /// # Panics
+    /// This function will exit the program if the template can't be published
+    fn template_publish(&self) {
+        let settings = Settings::load().unwrap_or_else(|err| {
+            warning!("{}", err);
+            Settings::default()
+        });
+
+        let target = match (&self.to, settings.registry.publish) {
+            (Some(to), _) => Some(Target::parse(to)),
+            (None, Some(publish)) => Some(Target::parse(&publish)),
+            (None, None) if self.dry_run => None,
+            (None, None) => {
+                error!("There is nowhere to publish the template, use --to or set publish in the [registry] section of the configuration");
+                std::process::exit(1);
+            },
+        };
+        let target = target.filter(|_| !self.dry_run);
+
+        let package = publish(Path::new(&self.path), self.bump, target.as_ref(), self.token.as_deref()).unwrap_or_else(|err| {
+            error!("{}", err);
+            std::process::exit(1);
+        });
+
+        match &target {
+            Some(Target::Git(repository)) => status!("Published {} {} to {} with the tag {}-v{}", package.name, package.version, repository, package.name, package.version),
+            Some(Target::Server(server)) => status!("Published {} {} to {}", package.name, package.version, server),
+            None => status!("Packed {} {} without publishing it ({} bytes)", package.name, package.version, package.archive.len()),
+        }
+
+        match output::is_json() {
+            true => print_json(&package),
+            false => println!("{} sha256 {}", package.file, package.sha256),
+        }
+    }
+}
+
+/// Implementation of the Config struct to manage the bibliography of a project
+impl Config {
+    /// Return the bibliography of `--bib`, or the one found in the project
+    ///
+    /// # Arguments
+    /// * `create` - Use `lib/bibliography.bib` if the project has no bibliography, instead of exiting
+    fn bibliography_path(&self, create: bool) -> PathBuf {
+        if let Some(bib) = &self.bib {
+            return PathBuf::from(bib);
+        }
+
+        match find_bibliography(Path::new(&self.path)) {
+            Some(path) => path,
+            None if create => Path::new(&self.path).join(DEFAULT_BIBLIOGRAPHY),
+            None => {
+                error!("There is no bibliography in {}, use --bib FILE", self.path);
+                std::process::exit(1);
+            },
+        }
+    }
+
+    /// Add references to the bibliography with `lth bib add`
+    /// The DOIs and the arXiv ids are searched in the `database` of the `[bibliography]` settings, then on the web
+    ///
+    /// # Output
+    /// ```text
+    /// Added vaswani2017attention to ./lib/bibliography.bib
+    /// ```
+    ///
+    /// # Panics
+    /// This function will exit the program if no reference can be added
+    fn bib_add(&self) {
+        let source = self.template.as_deref().unwrap_or_default();
+        let settings = Settings::load().unwrap_or_else(|err| {
+            warning!("{}", err);
+            Settings::default()
+        });
+
+        let local = settings.bibliography.database.map(LocalResolver::new);
+        let web = WebResolver::new(&SourceFetcher);
+        let resolvers: Vec<&dyn Resolver> = local.iter().map(|local| local as &dyn Resolver).chain([&web as &dyn Resolver]).collect();
+
+        let path = self.bibliography_path(true);
+        let result = Bibliography::load(&path).and_then(|bibliography| Ok((bibliography, entries_of(source, &resolvers)?)));
+        let (mut bibliography, entries) = result.unwrap_or_else(|err| {
+            error!("{}", err);
+            std::process::exit(1);
+        });
+
+        // The duplicates are skipped, the rest of the entries are still added
+        let mut added = Vec::new();
+        for entry in entries {
+            match bibliography.add(entry) {
+                Ok(key) => added.push(key),
+                Err(err) => warning!("{}", err),
+            }
+        }
+
+        if added.is_empty() {
+            error!("No reference was added to {}", path.display());
+            std::process::exit(1);
+        }
+
+        bibliography.save().unwrap_or_else(|err| {
+            error!("{}", err);
+            std::process::exit(1);
+        });
+
+        match output::is_json() {
+            true => print_json(&added),
+            false => for key in &added {
+                status!("Added {} to {}", key, path.display());
+            },
+        }
+    }
+
+    /// Report the citations missing from the bibliography and the entries never cited with `lth bib check`
+    ///
+    /// # Output
+    /// ```text
+    /// smith2020 is cited in src/01.md:12 but is not in the bibliography
+    /// knuth1984 is never cited
+    /// ./lib/bibliography.bib: 1 missing key(s), 1 unused entry(ies)
+    /// ```
+    ///
+    /// # Panics
+    /// This function will exit the program if a cited key is missing
+    fn bib_check(&self) {
+        let path = self.bibliography_path(false);
+        let bibliography = Bibliography::load(&path).unwrap_or_else(|err| {
+            error!("{}", err);
+            std::process::exit(1);
+        });
+
+        let report = check(Path::new(&self.path), &bibliography);
+        let healthy = report.missing.is_empty();
+
+        if output::is_json() {
+            print_json(&report);
+            std::process::exit(if healthy { 0 } else { 1 });
+        }
+
+        for citation in &report.missing {
+            error!("{} is cited in {}:{} but is not in the bibliography", citation.key, citation.file, citation.line);
+        }
+        for key in &report.unused {
+            warning!("{} is never cited", key);
+        }
+
+        status!("{}: {} missing key(s), {} unused entry(ies)", path.display(), report.missing.len(), report.unused.len());
+        std::process::exit(if healthy { 0 } else { 1 });
+    }
+}
+
+/// Check a template with `lth template lint` and print its issues
+///
+/// # Arguments
+/// * `dir` - The folder of the template
+///
+/// # Returns
+/// `false` if the template has errors, warnings alone don't make it fail
+///
+/// # Output
+/// ```text
+/// The file images/logo.png is missing, expected at thesis/images/logo.png
+/// The variable readme is not used by any file
+/// thesis: 1 error(s), 1 warning(s)
+/// ```
+pub fn template_lint(dir: &str) -> bool {
+    let issues = lint(Path::new(dir));
+    let errors = issues.iter().filter(|issue| issue.severity == Severity::Error).count();
+
+    if output::is_json() {
+        print_json(&issues);
+        return errors == 0;
+    }
+
+    for issue in &issues {
+        match issue.severity {
+            Severity::Error => error!("{}", issue.message),
+            Severity::Warning => warning!("{}", issue.message),
+        }
+    }
+
+    status!("{}: {} error(s), {} warning(s)", dir, errors, issues.len() - errors);
+    errors == 0
+}
+
+/// Print the progress of the creation of a project
+///
+/// # Arguments
+/// * `event` - The progress given by the Scaffolder
+fn print_event(event: &Event) {
+    match event {
+        Event::CreateDirectory { path } => trace!("Creating the folder {}", path),
+        Event::Download { url } => trace!("Downloading {}", url),
+        Event::WriteFile { path } => detail!("Created the new file at {}", path),
+        Event::SkipFile { path } => detail!("Skipped the existing file {}", path),
+        Event::InitGit { path } => status!("Initialized a new git repository at {}", path),
+        Event::Commit { id } => detail!("Committed the files to the repository ({})", id),
+    }
+}
+
+/// Print the structure of the new folder, using `tree -C` for the colors
+///
+/// # Arguments
+/// * `path` - The path to the new folder
+fn show_tree(path: &str) {
+    let output = std::process::Command::new("tree")
+        .arg("-C")
+        .arg(path)
+        .output();
+
+    // The structure is only a nice to have, tree may not be installed
+    if let Ok(output) = output {
+        println!("{}", String::from_utf8_lossy(&output.stdout));
     }
 }
 
@@ -73,13 +920,55 @@ impl Config {
 /// # Output
 /// ```text
 /// Usage: lth [TEMPLATE] [PATH]
+///        lth new [TEMPLATE] [PATH] [--name NAME] [--force | --merge] [--dry-run] [--with FEATURE] [--without FEATURE] [--style STYLE] [--no-hooks]
+///        lth list [--versions]
+///        lth search [QUERY] [--tag TAG] [--lang LANG] [--type TYPE]
+///        lth info TEMPLATE [--json]
+///        lth doctor [TEMPLATE]
+///        lth template new NAME [PATH]
+///        lth template import DIR [--name NAME]
+///        lth template lint [DIR]
+///        lth template test [DIR] [--build] [--keep]
+///        lth template publish [DIR] [--bump LEVEL] [--to TARGET] [--token TOKEN] [--dry-run]
+///        lth serve [DIR] [--port PORT] [--token TOKEN]
+///        lth bib add SNIPPET | FILE | DOI | ARXIV [--bib FILE]
+///        lth bib check [DIR] [--bib FILE]
+///        lth style list
+///        lth style set STYLE [DIR]
+///        lth trust [SOURCE | TEMPLATE]
+///        lth untrust SOURCE | TEMPLATE
 /// Options:
 ///    -h, --help      Display this help message
 ///    -l, --list      Display the available templates 
 ///    -v, --version   Display the version of the program
+///    --output FORMAT  Format of the results, text or json
 /// ```
 pub fn handle_bad_usage() {
     println!("Usage: lth [TEMPLATE] [PATH]");
+    println!("       lth new [TEMPLATE] [PATH] [--name NAME] [--force | --merge] [--dry-run] [--with FEATURE] [--without FEATURE] [--style STYLE] [--no-hooks]");
+    println!("       lth list [--versions]");
+    println!("       lth search [QUERY] [--tag TAG] [--lang LANG] [--type TYPE]");
+    println!("       lth info TEMPLATE [--json]");
+    println!("       lth doctor [TEMPLATE]");
+    println!("       lth template new NAME [PATH]");
+    println!("       lth template import DIR [--name NAME]");
+    println!("       lth template lint [DIR]");
+    println!("       lth template test [DIR] [--build] [--keep]");
+    println!("       lth template publish [DIR] [--bump LEVEL] [--to TARGET] [--token TOKEN] [--dry-run]");
+    println!("       lth serve [DIR] [--port PORT] [--token TOKEN]");
+    println!("       lth bib add SNIPPET | FILE | DOI | ARXIV [--bib FILE]");
+    println!("       lth bib check [DIR] [--bib FILE]");
+    println!("       lth style list");
+    println!("       lth style set STYLE [DIR]");
+    println!("       lth trust [SOURCE | TEMPLATE]");
+    println!("       lth untrust SOURCE | TEMPLATE");
+    println!("Without a template, the new command opens the template picker");
+    println!("A template can be pinned to a version with TEMPLATE@VERSION, like ieee@1.2");
+    println!("The doctor command checks the tools and TeX packages used by the templates");
+    println!("The template commands help to write templates, kept in ~/.config/lth/templates");
+    println!("The trust commands choose the template sources allowed to run hooks");
+    println!("The bib commands manage the BibTeX file of the project, lib/bibliography.bib by default");
+    println!("The style commands choose the CSL citation style of a pandoc project, kept in lib");
 
     // Display the options in the OPTIONS const map
     println!("Options:");
@@ -90,10 +979,14 @@ pub fn handle_bad_usage() {
 
 /// Display the templates in the TEMPLATES const map
 /// and the description of each template 
+/// With `--output json` the full information of each template is printed
+///
+/// # Arguments
+/// * `versions` - Show the available versions of each template, the newest first
 ///
 /// # Examples
 /// ```
-/// list_templates();
+/// template_list(false);
 /// ```
 ///
 /// # Output
@@ -102,15 +995,356 @@ pub fn handle_bad_usage() {
 ///    math        Latex report, template focused on math
 ///    ieee        Basic IEEE template, using pandoc & markdown
 /// ```
-pub fn template_list() {
+pub fn template_list(versions: bool) {
+    let templates = TemplateRegistry::discover().resolved();
+
+    if output::is_json() {
+        let infos: Vec<TemplateInfo> = templates.iter().map(TemplateInfo::new).collect();
+        print_json(&infos);
+        return;
+    }
+
     println!("List of templates:");
     // Display the templates in the TEMPLATES const map
-    for (template, description, _) in crate::TEMPLATES {
-        println!("\t\x1b[4;34m{}\x1b[0m\t\t{}", template, description);
+    for template in &templates {
+        println!("\t{}\t\t{}", paint(Color::Title, &template.name), template.description);
+        if versions {
+            let available: Vec<String> = available(template).iter().map(ToString::to_string).collect();
+            println!("\t\t\tversions: {}", if available.is_empty() { "none".to_string() } else { available.join(", ") });
+        }
+    }
+}
+
+/// Display the citation styles of `lth style set` and `--style`,
+/// the bundled ones and the `.csl` files in `~/.config/lth/styles`
+/// With `--output json` the name, the title and the file of each style are printed
+///
+/// # Output
+/// ```text
+/// Citation styles:
+///    ieee        IEEE (lth)
+///    apa         APA 7 (lth)
+/// ```
+///
+/// # Panics
+/// This function will exit the program if a local style can't be read
+pub fn style_list() {
+    let styles = styles().unwrap_or_else(|err| {
+        error!("{}", err);
+        std::process::exit(1);
+    });
+
+    if output::is_json() {
+        print_json(&styles);
+        return;
+    }
+
+    println!("Citation styles:");
+    for style in &styles {
+        let origin = style.path.as_ref().map(|path| format!(" ({})", path.display())).unwrap_or_default();
+        println!("\t{}\t\t{}{}", paint(Color::Title, &style.name), style.title, origin);
+    }
+}
+
+/// Install a citation style in a pandoc project, and commit it if the project uses git
+///
+/// # Arguments
+/// * `path` - The folder of the project
+/// * `style` - The style to install
+/// * `commit` - Commit the new style on top of the current commit
+///
+/// # Returns
+/// The path of the CSL file, relative to the project, and the id of the new commit
+///
+/// # Panics
+/// This function will exit the program if the style can't be installed
+fn install_style(path: &str, style: &Style, commit: bool) -> (String, Option<String>) {
+    let files = set_style(Path::new(path), style).unwrap_or_else(|err| {
+        error!("{}", err);
+        std::process::exit(1);
+    });
+    let file = files[0].clone();
+
+    status!("Set the citation style of {} to {} ({})", path, style.title, file);
+
+    if !commit {
+        return (file, None);
+    }
+
+    match commit_changes(path, &format!("Use the {} citation style", style.title), &files) {
+        Ok(commit) => {
+            detail!("Committed the citation style in {}", commit);
+            (file, Some(commit))
+        },
+        Err(err) => {
+            warning!("{}", err);
+            (file, None)
+        },
+    }
+}
+
+/// Search the templates with `lth search`, in the local registry and in the configured indexes
+/// The indexes that can't be read are skipped with a warning
+///
+/// # Arguments
+/// * `query` - The text to match against the names, the tags and the descriptions
+/// * `filters` - The tags, language and document type given with `--tag`, `--lang` and `--type`
+///
+/// # Example
+/// ```
+/// search("thesis", &Filters { language: Some("es".to_string()), ..Filters::default() });
+/// ```
+///
+/// # Output
+/// ```text
+/// ieeetec    1.0.0  Custom template for TEC papers (IEEE style)
+///            paper, ieee, tec, latex · en, es
+/// ```
+///
+/// # Panics
+/// This function will exit the program if no template matches
+pub fn search(query: &str, filters: &Filters) {
+    let settings = Settings::load().unwrap_or_else(|err| {
+        warning!("{}", err);
+        Settings::default()
+    });
+
+    let (index, errors) = combined(&TemplateRegistry::discover(), &settings.registry.index_urls());
+    for err in errors {
+        warning!("Skipping an index: {}", err);
+    }
+
+    let results = index.search(query, filters);
+
+    if output::is_json() {
+        print_json(&results);
+        return;
+    }
+
+    if results.is_empty() {
+        error!("No template matches the search");
+        eprintln!("Use -l or --list to see the available templates");
+        std::process::exit(1);
+    }
+
+    let width = results.iter().map(|entry| entry.name.len()).max().unwrap_or(0);
+    for entry in results {
+        let name = format!("{:width$}", entry.name, width = width);
+        println!("{}  {:7}  {}", paint(Color::Title, &name), entry.version.as_deref().unwrap_or("-"), entry.description);
+
+        let mut details = entry.tags.join(", ");
+        if !entry.languages.is_empty() {
+            details += &format!(" · {}", entry.languages.join(", "));
+        }
+        println!("{:width$}  {}", "", paint(Color::Dim, &details), width = width);
+    }
+}
+
+/// Share a folder of templates on the network with `lth serve`
+/// The other machines add the server to the `servers` of their `[registry]` settings
+///
+/// # Arguments
+/// * `dir` - The folder with the templates, one sub-folder with a manifest for each template
+/// * `port` - The port to listen on, on every network interface
+/// * `token` - The token `lth template publish` must send to upload templates, no uploads without it
+///
+/// # Example
+/// ```
+/// serve("~/.config/lth/templates", 8080, None);
+/// ```
+///
+/// # Panics
+/// This function will exit the program if the server can't start
+pub fn serve(dir: &str, port: u16, token: Option<&str>) {
+    let mut server = Server::bind(Path::new(dir), &format!("0.0.0.0:{}", port)).unwrap_or_else(|err| {
+        error!("{}", err);
+        std::process::exit(1);
+    });
+
+    // Only the clients with the token can publish templates
+    if let Some(token) = token {
+        server = server.with_token(token);
+        status!("Accepting the templates published with the token");
+    }
+
+    let count = server.index(&server.url()).templates.len();
+    status!("Serving {} template(s) of {} on {}", count, dir, server.url());
+    status!("Add servers = [\"http://<this machine>:{}\"] to the [registry] section of the configuration to use them", port);
+    server.run();
+}
+
+/// Trust a template source with `lth trust`, or forget the decision with `lth untrust`
+/// so `lth new` asks again before running the hooks of the source
+/// Without a source, the decisions are shown
+///
+/// # Arguments
+/// * `option` - The source, or the name of a template to use its source
+/// * `trusted` - Whether the source is trusted or its decision is forgotten
+///
+/// # Example
+/// ```
+/// trust(Some("https://example.com/templates/thesis"), true);
+/// ```
+///
+/// # Output
+/// ```text
+/// Template sources:
+///     trusted      https://example.com/templates/thesis
+///     untrusted    https://example.com/templates/unknown
+/// ```
+///
+/// # Panics
+/// This function will exit the program if the decisions can't be read or saved
+pub fn trust(option: Option<&str>, trusted: bool) {
+    let mut store = TrustStore::load().unwrap_or_else(|err| {
+        error!("{}", err);
+        std::process::exit(1);
+    });
+
+    let option = match option {
+        Some(option) => option,
+        None => {
+            if output::is_json() {
+                print_json(&store.sources().collect::<BTreeMap<_, _>>());
+                return;
+            }
+
+            println!("Template sources:");
+            for (source, trusted) in store.sources() {
+                let decision = if trusted { paint(Color::Green, "trusted") } else { paint(Color::Red, "untrusted") };
+                println!("\t{}\t{}", decision, source);
+            }
+            return;
+        },
+    };
+
+    // The name of a template stands for the place it was read from
+    let registry = TemplateRegistry::discover();
+    let source = registry.get(option).map(|template| template.origin().to_string()).unwrap_or_else(|| option.to_string());
+
+    match trusted {
+        true => store.set(&source, true),
+        false if !store.forget(&source) => {
+            status!("There is no decision for {}", source);
+            return;
+        },
+        false => {},
+    }
+
+    store.save().unwrap_or_else(|err| {
+        error!("{}", err);
+        std::process::exit(1);
+    });
+
+    match trusted {
+        true => status!("The hooks of {} can run now", source),
+        false => status!("The hooks of {} won't run, lth new will ask again", source),
+    }
+}
+
+/// Show the template picker to choose a template and answer its variables
+///
+/// # Arguments
+/// * `path` - The path where the new folder is created
+/// * `name` - The name of the new folder, if it was already given
+/// * `given` - The features given with `--with` and `--without`
+/// * `collision` - What to do if the folder already exists
+///
+/// # Example
+/// ```
+/// let selection = pick_template(".", None, &Answers::new(), Collision::Abort);
+/// ```
+///
+/// # Panics
+/// This function will exit the program if the terminal UI can't be used
+pub fn pick_template(path: &str, name: Option<&str>, given: &Answers, collision: Collision) -> Option<lth::tui::Selection> {
+    lth::tui::pick(TemplateRegistry::discover().resolved(), path, name, given, collision).unwrap_or_else(|err| {
+        error!("{}", err);
+        std::process::exit(1);
+    })
+}
+
+/// Check the template name according to the available templates 
+/// and return the template, at the version given after `@`
+/// The templates that are not local are searched in the indexes of the configured sources
+///
+/// # Arguments
+/// * `template` - A `String` containing the template name, like `ieee` or `ieee@1.2`
+///
+/// # Example
+/// ```
+/// let template = check_template(&self.template);
+/// ```
+///
+/// # Panics
+/// This function will exit the program if the template is not valid
+pub fn check_template(option: &str) -> Template {
+    // Check if the option is valid
+    // If it is, return the template
+    // If it is not, return an error
+    let mut registry = TemplateRegistry::discover();
+    let name = option.split('@').next().unwrap_or(option);
+
+    // The manifest of a remote template is only downloaded when there is no local template
+    if registry.get(name).is_none() {
+        let urls = Settings::load().map(|settings| settings.registry.index_urls()).unwrap_or_default();
+        match find_template(&urls, name, &SourceFetcher) {
+            Some(Ok(template)) => registry.add(template),
+            Some(Err(err)) => {
+                error!("{}", err);
+                std::process::exit(1);
+            },
+            None => {},
+        }
+    }
+
+    match registry.get(name).map(|_| registry.resolve(option)) {
+        Some(Ok(template)) => template,
+        Some(Err(err)) => {
+            error!("{}", err);
+            std::process::exit(1);
+        },
+        None => { 
+            error!("Invalid template name!");
+            eprintln!("Use -l or --list to see the available templates");
+            std::process::exit(1);
+        },
+    }
+}
+
+/// Check if path is a directory or a file
+/// If it is not a directory, exit the program
+/// If it is a directory, do nothing and continue
+///
+/// # Arguments
+/// * `path` - A string slice that holds the path to check 
+///
+/// # Panics
+/// If the path is not a directory, the program will exit 
+/// with the error code 1 
+///
+/// # Examples
+/// ```
+/// check_path("path/to/file");
+/// ```
+/// This will exit the program with the error code 1
+/// ```
+/// check_path("path/to/directory");
+/// ```
+/// This will not exit the program
+pub fn check_path(path: &str) {
+    // Check if the path is a directory
+    let is_dir = Path::new(path).is_dir();
+
+    // If it is not a directory, exit the program
+    if !is_dir {
+        error!("{}, is not a directory", path);
+        std::process::exit(1);
     }
 }
 
 /// Display the version of the program
+/// With `--output json` the build information is printed too
 ///
 /// # Examples
 /// ```
@@ -122,6 +1356,17 @@ pub fn template_list() {
 /// lth 0.1.0
 /// ```
 pub fn show_version() {
+    if output::is_json() {
+        print_json(&VersionInfo {
+            name: "lth",
+            version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            profile: if cfg!(debug_assertions) { "debug" } else { "release" },
+        });
+        return;
+    }
+
     println!("Latex Template Handler [lth] by @Johanx22x");
-    println!("Version: \x1b[4;34m{}\x1b[0m", env!("CARGO_PKG_VERSION"));
+    println!("Version: {}", paint(Color::Title, env!("CARGO_PKG_VERSION")));
 }
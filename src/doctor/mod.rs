@@ -0,0 +1,584 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::output::{paint, Color};
+use crate::{error, warning};
+use crate::templates::{Requirement, Requirements, Template};
+
+// const map to store the executables checked by `lth doctor`
+// and if they are required to build the templates
+const EXECUTABLES: &[(&str, bool)] = &[
+    ("pdflatex", true),
+    ("latexmk", false),
+    ("biber", false),
+    ("pandoc", true),
+    ("pandoc-crossref", false),
+    ("make", true),
+    ("git", false),
+    ("tree", false),
+    ("kpsewhich", true),
+];
+
+// const map to store the TeX packages checked by `lth doctor`
+const PACKAGES: &[&str] = &[
+    "amsmath.sty",
+    "amssymb.sty",
+    "geometry.sty",
+    "graphicx.sty",
+    "hyperref.sty",
+    "biblatex.sty",
+    "tikz.sty",
+    "IEEEtran.cls",
+];
+
+// const map to store the package of each executable, by distribution:
+// executable, Arch Linux, Debian/Ubuntu, Fedora, Homebrew
+const INSTALL_HINTS: &[(&str, &str, &str, &str, &str)] = &[
+    ("pdflatex", "texlive-basic", "texlive-latex-base", "texlive-scheme-basic", "--cask mactex-no-gui"),
+    ("latexmk", "texlive-binextra", "latexmk", "latexmk", "--cask mactex-no-gui"),
+    ("biber", "biber", "biber", "biber", "--cask mactex-no-gui"),
+    ("kpsewhich", "texlive-bin", "texlive-binaries", "texlive-kpathsea", "--cask mactex-no-gui"),
+    ("pandoc", "pandoc-cli", "pandoc", "pandoc", "pandoc"),
+    ("pandoc-crossref", "pandoc-crossref", "", "", "pandoc-crossref"),
+    ("make", "make", "make", "make", "make"),
+    ("git", "git", "git", "git", "git"),
+    ("tree", "tree", "tree", "tree", "tree"),
+];
+
+/// Linux distributions and systems with known install commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Distro {
+    Arch,
+    Debian,
+    Fedora,
+    MacOs,
+    Unknown,
+}
+
+/// Kind of a requirement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequirementKind {
+    Executable,
+    Package,
+    Font,
+}
+
+/// Result of checking a requirement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// The requirement is installed, with its version or its path
+    Found(String),
+    /// The installed version is older than the minimum version
+    Outdated(String),
+    Missing,
+    /// The requirement can't be checked, with the reason
+    Unchecked(String),
+}
+
+/// Struct with the result of checking a requirement
+#[derive(Debug, Clone)]
+pub struct RequirementCheck {
+    pub kind: RequirementKind,
+    pub requirement: Requirement,
+    pub status: Status,
+}
+
+/// Implementation of the Distro enum to detect the system and build install hints
+impl Distro {
+    /// Detect the distribution using `/etc/os-release`
+    ///
+    /// # Example
+    /// ```
+    /// let distro = Distro::detect();
+    /// ```
+    pub fn detect() -> Distro {
+        if cfg!(target_os = "macos") {
+            return Distro::MacOs;
+        }
+
+        let release = std::fs::read_to_string("/etc/os-release").unwrap_or_default();
+
+        // ID is the distribution, ID_LIKE its parents (ubuntu is like debian)
+        let ids: Vec<String> = release.lines()
+            .filter_map(|line| line.strip_prefix("ID=").or_else(|| line.strip_prefix("ID_LIKE=")))
+            .flat_map(|value| value.trim_matches('"').split_whitespace().map(String::from).collect::<Vec<_>>())
+            .collect();
+
+        for id in ids {
+            match id.as_str() {
+                "arch" | "manjaro" | "endeavouros" => return Distro::Arch,
+                "debian" | "ubuntu" => return Distro::Debian,
+                "fedora" | "rhel" => return Distro::Fedora,
+                _ => {},
+            }
+        }
+
+        Distro::Unknown
+    }
+
+    /// Return the name of the distribution
+    pub fn name(&self) -> &'static str {
+        match self {
+            Distro::Arch => "Arch Linux",
+            Distro::Debian => "Debian/Ubuntu",
+            Distro::Fedora => "Fedora",
+            Distro::MacOs => "macOS",
+            Distro::Unknown => "unknown system",
+        }
+    }
+
+    /// Return the command that installs an executable
+    ///
+    /// # Arguments
+    /// * `executable` - The name of the missing executable
+    ///
+    /// # Example
+    /// ```
+    /// let hint = Distro::Arch.install_executable("pandoc"); // "sudo pacman -S pandoc-cli"
+    /// ```
+    pub fn install_executable(&self, executable: &str) -> String {
+        let hint = INSTALL_HINTS.iter().find(|(name, ..)| *name == executable);
+
+        let package = match (hint, self) {
+            (Some((_, arch, _, _, _)), Distro::Arch) => arch,
+            (Some((_, _, debian, _, _)), Distro::Debian) => debian,
+            (Some((_, _, _, fedora, _)), Distro::Fedora) => fedora,
+            (Some((_, _, _, _, brew)), Distro::MacOs) => brew,
+            _ => "",
+        };
+
+        if package.is_empty() {
+            return format!("install {} with your package manager or from its website", executable);
+        }
+
+        match self {
+            Distro::Arch => format!("sudo pacman -S {}", package),
+            Distro::Debian => format!("sudo apt install {}", package),
+            Distro::Fedora => format!("sudo dnf install {}", package),
+            Distro::MacOs => format!("brew install {}", package),
+            Distro::Unknown => format!("install the {} package", package),
+        }
+    }
+
+    /// Return the command that installs a TeX package
+    ///
+    /// # Arguments
+    /// * `package` - The file of the missing package, like `tikz.sty`
+    pub fn install_package(&self, package: &str) -> String {
+        let name = package.rsplit_once('.').map_or(package, |(name, _)| name);
+
+        match self {
+            Distro::Arch => "sudo pacman -S texlive-latexextra".to_string(),
+            Distro::Debian => "sudo apt install texlive-latex-extra".to_string(),
+            // Fedora packages provide the TeX files they install
+            Distro::Fedora => format!("sudo dnf install 'tex({})'", package),
+            Distro::MacOs | Distro::Unknown => format!("tlmgr install {}", name.to_lowercase()),
+        }
+    }
+}
+
+/// Find an executable in the PATH
+///
+/// # Arguments
+/// * `name` - The name of the executable
+///
+/// # Example
+/// ```
+/// let path = find_executable("pdflatex");
+/// ```
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+
+    std::env::split_paths(&paths)
+        .flat_map(|directory| {
+            let candidates = [directory.join(name), directory.join(format!("{}.exe", name))];
+            candidates.into_iter()
+        })
+        .find(|candidate| is_executable(candidate))
+}
+
+/// Check if a path is an executable file
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata().map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0).unwrap_or(false)
+    }
+
+    #[cfg(not(unix))]
+    path.is_file()
+}
+
+/// Implementation of the RequirementCheck struct to show the result of a check
+impl RequirementCheck {
+    /// Check if the requirement is installed with a recent enough version
+    /// Requirements that can't be checked are not reported as missing
+    pub fn is_met(&self) -> bool {
+        matches!(self.status, Status::Found(_) | Status::Unchecked(_))
+    }
+
+    /// Return how to install the requirement
+    ///
+    /// # Arguments
+    /// * `distro` - The distribution of the user
+    pub fn install_hint(&self, distro: Distro) -> String {
+        match self.kind {
+            RequirementKind::Executable => distro.install_executable(&self.requirement.name),
+            RequirementKind::Package => distro.install_package(&self.requirement.name),
+            RequirementKind::Font if is_font_file(&self.requirement.name) => distro.install_package(&self.requirement.name),
+            RequirementKind::Font => format!("install the {} font with your package manager", self.requirement.name),
+        }
+    }
+
+    /// Describe the result of the check
+    ///
+    /// # Arguments
+    /// * `distro` - The distribution of the user, used in the install hints
+    ///
+    /// # Example
+    /// ```
+    /// println!("{}", check.describe(Distro::Arch)); // "pandoc 2.9.2, 2.11 or newer is needed, install it with: ..."
+    /// ```
+    pub fn describe(&self, distro: Distro) -> String {
+        match &self.status {
+            Status::Found(found) => found.clone(),
+            Status::Outdated(found) => format!(
+                "{}, {} or newer is needed, update it with: {}",
+                found,
+                self.requirement.version.as_deref().unwrap_or_default(),
+                self.install_hint(distro)
+            ),
+            Status::Missing => format!("missing, install it with: {}", self.install_hint(distro)),
+            Status::Unchecked(reason) => reason.clone(),
+        }
+    }
+}
+
+/// Check if a font is given by its file, instead of its family
+fn is_font_file(name: &str) -> bool {
+    [".otf", ".ttf", ".pfb", ".tfm"].iter().any(|extension| name.to_lowercase().ends_with(extension))
+}
+
+/// Check the requirements of a template
+///
+/// # Arguments
+/// * `requirements` - The requirements to check
+///
+/// # Example
+/// ```
+/// let checks = check_requirements(&template.requirements);
+/// ```
+pub fn check_requirements(requirements: &Requirements) -> Vec<RequirementCheck> {
+    let executables = requirements.executables.iter().map(|requirement| RequirementCheck {
+        kind: RequirementKind::Executable,
+        requirement: requirement.clone(),
+        status: check_executable(requirement),
+    });
+
+    let packages = requirements.packages.iter().map(|requirement| RequirementCheck {
+        kind: RequirementKind::Package,
+        requirement: requirement.clone(),
+        status: check_package(requirement),
+    });
+
+    let fonts = requirements.fonts.iter().map(|requirement| RequirementCheck {
+        kind: RequirementKind::Font,
+        requirement: requirement.clone(),
+        status: check_font(requirement),
+    });
+
+    executables.chain(packages).chain(fonts).collect()
+}
+
+/// Check an executable and read its version
+///
+/// # Arguments
+/// * `requirement` - The executable and its minimum version
+///
+/// # Example
+/// ```
+/// let status = check_executable(&Requirement::new("pandoc"));
+/// ```
+pub fn check_executable(requirement: &Requirement) -> Status {
+    let path = match find_executable(&requirement.name) {
+        Some(path) => path,
+        None => return Status::Missing,
+    };
+
+    // The first line of --version has the version in every checked tool
+    let line = Command::new(&path).arg("--version").output().ok().and_then(|output| {
+        let text = String::from_utf8_lossy(&output.stdout).into_owned() + &String::from_utf8_lossy(&output.stderr);
+        text.lines().map(str::trim).find(|line| !line.is_empty()).map(String::from)
+    });
+
+    let line = match line {
+        Some(line) => line,
+        None => return Status::Found(path.display().to_string()),
+    };
+
+    match (&requirement.version, extract_version(&line)) {
+        (Some(minimum), Some(version)) if !is_at_least(&version, minimum) => Status::Outdated(line),
+        (Some(_), None) => Status::Unchecked(format!("{}, the version can't be read", line)),
+        _ => Status::Found(line),
+    }
+}
+
+/// Check a TeX package using `kpsewhich`
+/// The minimum version of a package is compared with the date,
+/// or the `v` version, of its `\ProvidesPackage` or `\ProvidesClass` line
+///
+/// # Arguments
+/// * `requirement` - The file of the package, like `tikz.sty`, and its minimum version
+///
+/// # Example
+/// ```
+/// let status = check_package(&Requirement::new("IEEEtran.cls"));
+/// ```
+pub fn check_package(requirement: &Requirement) -> Status {
+    let path = match kpsewhich(&requirement.name) {
+        Ok(Some(path)) => path,
+        Ok(None) => return Status::Missing,
+        Err(reason) => return Status::Unchecked(reason),
+    };
+
+    let minimum = match &requirement.version {
+        Some(minimum) => minimum,
+        None => return Status::Found(path),
+    };
+
+    let info = std::fs::read_to_string(&path).ok().and_then(|content| provides_info(&content));
+    let version = info.as_deref().and_then(|info| {
+        // Dates are written like 2020/01/01, versions like v1.8b
+        info.split_whitespace().find(|token| match minimum.contains('/') {
+            true => token.contains('/'),
+            false => token.starts_with('v'),
+        })
+    });
+
+    match version {
+        Some(version) if !is_at_least(version.trim_start_matches('v'), minimum.trim_start_matches('v')) => {
+            Status::Outdated(format!("{} ({})", path, version))
+        },
+        Some(version) => Status::Found(format!("{} ({})", path, version)),
+        None => Status::Unchecked(format!("{}, the version can't be read", path)),
+    }
+}
+
+/// Check a font using `fc-list`, or `kpsewhich` for the font files
+///
+/// # Arguments
+/// * `requirement` - The family or the file of the font
+///
+/// # Example
+/// ```
+/// let status = check_font(&Requirement::new("TeX Gyre Termes"));
+/// ```
+pub fn check_font(requirement: &Requirement) -> Status {
+    if is_font_file(&requirement.name) {
+        return match kpsewhich(&requirement.name) {
+            Ok(Some(path)) => Status::Found(path),
+            Ok(None) => Status::Missing,
+            Err(reason) => Status::Unchecked(reason),
+        };
+    }
+
+    let output = match Command::new("fc-list").args([":", "family"]).output() {
+        Ok(output) => output,
+        Err(_) => return Status::Unchecked("fc-list is not available, the fonts can't be checked".to_string()),
+    };
+
+    // Each line has the names of a family separated by commas
+    let families = String::from_utf8_lossy(&output.stdout).into_owned();
+    let found = families.lines()
+        .flat_map(|line| line.split(','))
+        .any(|family| family.trim().eq_ignore_ascii_case(&requirement.name));
+
+    match found {
+        true => Status::Found("installed".to_string()),
+        false => Status::Missing,
+    }
+}
+
+/// Find a TeX file using `kpsewhich`
+///
+/// # Errors
+/// This function returns an error if `kpsewhich` can't be run
+fn kpsewhich(file: &str) -> Result<Option<String>, String> {
+    let output = Command::new("kpsewhich")
+        .arg(file)
+        .output()
+        .map_err(|_| "kpsewhich is not available, the TeX files can't be checked".to_string())?;
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    Ok(if output.status.success() && !path.is_empty() { Some(path) } else { None })
+}
+
+/// Return the optional argument of the `\ProvidesPackage` or `\ProvidesClass` line of a TeX file
+///
+/// # Example
+/// ```
+/// let info = provides_info("\\ProvidesPackage{tikz}[2023/01/15 v3.1.10]"); // Some("2023/01/15 v3.1.10")
+/// ```
+pub fn provides_info(content: &str) -> Option<String> {
+    let start = content.find("\\ProvidesPackage").or_else(|| content.find("\\ProvidesClass"))?;
+    let rest = &content[start..];
+    let open = rest.find('[')?;
+    let close = rest[open..].find(']')?;
+
+    Some(rest[open + 1..open + close].trim().to_string())
+}
+
+/// Extract the version number of a `--version` line
+///
+/// # Example
+/// ```
+/// let version = extract_version("pandoc 3.1.2"); // Some("3.1.2")
+/// let version = extract_version("pdfTeX 3.141592653-2.6-1.40.25 (TeX Live 2023)"); // Some("3.141592653")
+/// ```
+pub fn extract_version(line: &str) -> Option<String> {
+    line.split_whitespace()
+        .map(|token| token.trim_start_matches('v').trim_matches(|c: char| c == ',' || c == '(' || c == ')'))
+        .find(|token| token.starts_with(|c: char| c.is_ascii_digit()) && token.contains('.'))
+        .map(|token| token.split('-').next().unwrap_or(token).to_string())
+}
+
+/// Compare two versions by their numbers, dates like 2020/01/01 are compared the same way
+///
+/// # Example
+/// ```
+/// assert!(is_at_least("2.19.2", "2.11"));
+/// assert!(!is_at_least("2019/12/31", "2020/01/01"));
+/// ```
+pub fn is_at_least(version: &str, minimum: &str) -> bool {
+    // Letters and other symbols only separate the numbers, like in 1.8b
+    let numbers = |text: &str| -> Vec<u64> {
+        text.split(|c: char| !c.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .filter_map(|part| part.parse().ok())
+            .collect()
+    };
+
+    numbers(version) >= numbers(minimum)
+}
+
+/// Run all the checks and print the report
+///
+/// # Example
+/// ```
+/// let healthy = doctor();
+/// ```
+///
+/// # Output
+/// ```text
+/// Checking the environment (Arch Linux)
+/// Executables:
+///     ✔ pdflatex          pdfTeX 3.141592653-2.6-1.40.25 (TeX Live 2023/Arch Linux)
+///     ✘ pandoc-crossref   missing, install it with: sudo pacman -S pandoc-crossref
+/// TeX packages:
+///     ✔ amsmath.sty       /usr/share/texmf-dist/tex/latex/amsmath/amsmath.sty
+/// ```
+///
+/// # Returns
+/// `false` if a required executable or a package is missing
+pub fn doctor() -> bool {
+    let distro = Distro::detect();
+    println!("{}", paint(Color::Blue, &format!("Checking the environment ({})", distro.name())));
+
+    let requirements = Requirements {
+        executables: EXECUTABLES.iter()
+            .map(|(name, required)| Requirement { optional: !required, ..Requirement::new(name) })
+            .collect(),
+        packages: PACKAGES.iter().map(|name| Requirement::new(name)).collect(),
+        fonts: Vec::new(),
+    };
+
+    report(distro, &check_requirements(&requirements))
+}
+
+/// Check the requirements of a template and print the report
+///
+/// # Arguments
+/// * `template` - The template to check
+///
+/// # Example
+/// ```
+/// let healthy = doctor_template(&template);
+/// ```
+///
+/// # Returns
+/// `false` if a required executable, package or font is missing or outdated
+pub fn doctor_template(template: &Template) -> bool {
+    let distro = Distro::detect();
+    println!("{}", paint(Color::Blue, &format!("Checking the requirements of {} ({})", template.name, distro.name())));
+
+    report(distro, &check_requirements(&template.requirements))
+}
+
+/// Check the requirements of a template before creating a project
+/// Only the requirements that are not met are printed,
+/// errors for the required ones and warnings for the optional ones
+///
+/// # Arguments
+/// * `template` - The template used to create the project
+///
+/// # Example
+/// ```
+/// if !meets_requirements(&template) {
+///     std::process::exit(1);
+/// }
+/// ```
+///
+/// # Returns
+/// `false` if a required executable, package or font is missing or outdated
+pub fn meets_requirements(template: &Template) -> bool {
+    let distro = Distro::detect();
+    let mut met = true;
+
+    for check in check_requirements(&template.requirements).iter().filter(|check| !check.is_met()) {
+        match check.requirement.optional {
+            true => warning!("{} can use {}, {}", template.name, check.requirement, check.describe(distro)),
+            false => {
+                met = false;
+                error!("{} needs {}, {}", template.name, check.requirement, check.describe(distro));
+            },
+        }
+    }
+
+    met
+}
+
+/// Print the checks grouped by kind
+///
+/// # Returns
+/// `false` if a required executable, package or font is not met
+fn report(distro: Distro, checks: &[RequirementCheck]) -> bool {
+    let mut healthy = true;
+
+    let groups = [
+        (RequirementKind::Executable, "Executables:"),
+        (RequirementKind::Package, "TeX packages:"),
+        (RequirementKind::Font, "Fonts:"),
+    ];
+
+    for (kind, title) in groups {
+        let group: Vec<&RequirementCheck> = checks.iter().filter(|check| check.kind == kind).collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        println!("{}", title);
+        for check in group {
+            let mark = match (check.is_met(), check.requirement.optional) {
+                (true, _) if matches!(check.status, Status::Unchecked(_)) => paint(Color::Yellow, "?"),
+                (true, _) => paint(Color::Green, "✔"),
+                (false, true) => paint(Color::Yellow, "-"),
+                (false, false) => paint(Color::Red, "✘"),
+            };
+
+            healthy &= check.is_met() || check.requirement.optional;
+            println!("\t{} {:16}  {}", mark, check.requirement.to_string(), check.describe(distro));
+        }
+    }
+
+    healthy
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::utils::{content_length, try_download};
+
+/// Source of the files of the templates
+/// The files are always asked by their URL, each fetcher decides where the bytes come from
+pub trait Fetcher {
+    /// Get the bytes of a file
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the file
+    ///
+    /// # Errors
+    /// This function returns an error if the file can't be fetched
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, String>;
+
+    /// Get the size of a file without fetching it
+    ///
+    /// # Returns
+    /// `None` if the size is not known
+    fn size(&self, url: &str) -> Option<u64> {
+        let _ = url;
+        None
+    }
+}
+
+/// Fetcher that downloads the files using the HTTP client shared by every download
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HttpFetcher;
+
+/// Fetcher used by default: the `file://` URLs of the local templates are read from the disk,
+/// the rest are downloaded
+/// A template can inherit files from local and remote templates, so each URL picks its own way
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SourceFetcher;
+
+/// Fetcher that reads the files from a local folder
+/// The URLs starting with the prefix are mapped to the files inside the folder
+///
+/// # Example
+/// ```
+/// let fetcher = LocalFetcher::new("https://raw.githubusercontent.com/Johanx22x/latex-templates/main", "../latex-templates");
+/// ```
+#[derive(Debug, Clone)]
+pub struct LocalFetcher {
+    prefix: String,
+    root: PathBuf,
+}
+
+/// Fetcher that keeps the files in memory, by URL
+///
+/// # Example
+/// ```
+/// let mut fetcher = MemoryFetcher::new();
+/// fetcher.insert("https://example.com/math/main.tex", "\\documentclass{report}");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFetcher {
+    files: HashMap<String, Vec<u8>>,
+}
+
+/// Download the files over HTTP
+impl Fetcher for HttpFetcher {
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, String> {
+        try_download(url)
+    }
+
+    fn size(&self, url: &str) -> Option<u64> {
+        content_length(url)
+    }
+}
+
+/// Read the `file://` URLs from the disk and download the rest
+impl Fetcher for SourceFetcher {
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, String> {
+        match local_path(url) {
+            Some(path) => {
+                let path = path?;
+                std::fs::read(path).map_err(|err| format!("Failed to read the file {}: {}", path.display(), err))
+            },
+            None => try_download(url),
+        }
+    }
+
+    fn size(&self, url: &str) -> Option<u64> {
+        match local_path(url) {
+            Some(path) => std::fs::metadata(path.ok()?).ok().map(|metadata| metadata.len()),
+            None => content_length(url),
+        }
+    }
+}
+
+/// Return the path of a `file://` URL
+///
+/// # Returns
+/// `None` if the URL is not a `file://` URL, or an error if it goes up with `..`
+fn local_path(url: &str) -> Option<Result<&Path, String>> {
+    let path = url.strip_prefix("file://")?;
+
+    match path.split('/').any(|component| component == "..") {
+        true => Some(Err(format!("{} can't go up with ..", url))),
+        false => Some(Ok(Path::new(path))),
+    }
+}
+
+/// Implementation of the LocalFetcher struct to map the URLs to local files
+impl LocalFetcher {
+    /// Create a new LocalFetcher
+    ///
+    /// # Arguments
+    /// * `prefix` - The beginning of the URLs replaced by the folder
+    /// * `root` - The folder with the files
+    pub fn new(prefix: impl Into<String>, root: impl Into<PathBuf>) -> LocalFetcher {
+        LocalFetcher { prefix: prefix.into(), root: root.into() }
+    }
+
+    /// Return the local file of a URL
+    ///
+    /// # Errors
+    /// This function returns an error if the URL doesn't start with the prefix
+    /// or if it goes outside of the folder
+    pub fn path_of(&self, url: &str) -> Result<PathBuf, String> {
+        let relative = url.strip_prefix(self.prefix.trim_end_matches('/'))
+            .ok_or_else(|| format!("{} is not inside {}", url, self.prefix))?
+            .trim_start_matches('/');
+
+        if relative.split('/').any(|component| component == "..") {
+            return Err(format!("{} goes outside of {}", url, self.prefix));
+        }
+
+        Ok(self.root.join(relative))
+    }
+}
+
+/// Read the files from the local folder
+impl Fetcher for LocalFetcher {
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, String> {
+        let path = self.path_of(url)?;
+        std::fs::read(&path).map_err(|err| format!("Failed to read the file {}: {}", path.display(), err))
+    }
+
+    fn size(&self, url: &str) -> Option<u64> {
+        std::fs::metadata(self.path_of(url).ok()?).ok().map(|metadata| metadata.len())
+    }
+}
+
+/// Implementation of the MemoryFetcher struct to add the files
+impl MemoryFetcher {
+    /// Create an empty MemoryFetcher
+    pub fn new() -> MemoryFetcher {
+        MemoryFetcher::default()
+    }
+
+    /// Add a file
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the file
+    /// * `content` - The bytes of the file
+    pub fn insert(&mut self, url: impl Into<String>, content: impl Into<Vec<u8>>) {
+        self.files.insert(url.into(), content.into());
+    }
+}
+
+/// Return the files kept in memory
+impl Fetcher for MemoryFetcher {
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, String> {
+        self.files.get(url).cloned().ok_or_else(|| format!("Failed to download the file {}! (404 Not Found)", url))
+    }
+
+    fn size(&self, url: &str) -> Option<u64> {
+        self.files.get(url).map(|content| content.len() as u64)
+    }
+}
@@ -0,0 +1,80 @@
+/// Compute the fuzzy score of a query against a text
+/// The characters of the query must appear in the text in the same order,
+/// consecutive characters and characters at the start of a word score higher
+///
+/// # Arguments
+/// * `query` - The text typed by the user
+/// * `text` - The text to match
+///
+/// # Example
+/// ```
+/// assert!(score("ieee", "ieeetec").is_some());
+/// assert!(score("xyz", "math").is_none());
+/// ```
+///
+/// # Returns
+/// `None` if the query doesn't match, otherwise the score of the match
+pub fn score(query: &str, text: &str) -> Option<i64> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+
+    // An empty query matches everything
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let mut score = 0;
+    let mut matched = 0;
+    let mut previous: Option<usize> = None;
+
+    for (index, c) in text.iter().enumerate() {
+        if matched == query.len() {
+            break;
+        }
+
+        if *c != query[matched] {
+            continue;
+        }
+
+        score += 1;
+
+        // Consecutive characters
+        if previous == Some(index.wrapping_sub(1)) {
+            score += 5;
+        }
+
+        // Start of the text or of a word
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+
+        previous = Some(index);
+        matched += 1;
+    }
+
+    if matched < query.len() {
+        return None;
+    }
+
+    // Prefer the shorter texts, they are closer to the query
+    Some((score * 10 - text.len() as i64 / 4).max(1))
+}
+
+/// Compute the best fuzzy score of a query against several fields
+/// The fields are given with a weight, so a match in the name can rank
+/// higher than a match in the description
+///
+/// # Arguments
+/// * `query` - The text typed by the user
+/// * `fields` - The texts to match, with their weights
+///
+/// # Example
+/// ```
+/// let score = best_score("thesis", &[(&template.name, 3), (&template.description, 1)]);
+/// ```
+pub fn best_score(query: &str, fields: &[(&str, i64)]) -> Option<i64> {
+    fields.iter()
+        .filter_map(|(text, weight)| score(query, text).map(|score| score * weight))
+        .max()
+}
@@ -1,42 +1,107 @@
+use crate::preflight::Collision;
+
 /// Initialize a new git repository in the given path 
 /// and add the new files to the repository
+/// When merging into an existing folder only the files of the template are committed,
+/// so the files and the uncommitted work of the user stay out of the commit
+///
+/// # Returns
+/// The id of the new commit
 ///
 /// # Arguments
 /// * `path` - The path to the new git repository
+/// * `files` - The files written by the template, relative to the path
+/// * `collision` - How the project was created, `Collision::Merge` keeps the other files out of the commit
 ///
 /// # Example
 /// ```
 /// let path = "path/to/a/directory";
-/// init_git(path);
+/// let commit = init_git(path, &project.files, Collision::Abort)?;
 /// ```
 ///
-/// # Panics
-/// This function will panic if the git repository can't be initialized
+/// # Errors
+/// This function returns an error if the git repository can't be initialized
 /// or if the files can't be added to the repository
 /// or if the repository can't be commited
-pub fn init_git(path: &str) {
+pub fn init_git(path: &str, files: &[String], collision: Collision) -> Result<String, String> {
     // Initialize a new git repository
-    let repo = git2::Repository::init(path).expect("Failed to initialize the git repository");
+    let repo = git2::Repository::init(path).map_err(|err| format!("Failed to initialize the git repository: {}", err))?;
 
-    // Add all the files to the repository
-    let mut index = repo.index().expect("Failed to add the files to the repository");
+    // In a repository with commits, the new commit goes on top of the current one
+    let message = match repo.head().ok().and_then(|head| head.peel_to_commit().ok()) {
+        Some(_) => "Add the template files",
+        None => "Initial commit",
+    };
 
-    index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None).expect("Failed to add the files to the repository");
+    match collision {
+        Collision::Merge => commit_files(&repo, message, Some(files)),
+        _ => commit_files(&repo, message, None),
+    }
+}
 
-    index.write().expect("Failed to add the files to the repository");
+/// Add the given files of a repository and commit them on top of the current commit
+/// The other changes of the user are left out of the commit
+///
+/// # Returns
+/// The id of the new commit
+///
+/// # Arguments
+/// * `path` - The path to the git repository
+/// * `message` - The message of the commit
+/// * `files` - The changed files, relative to the path
+///
+/// # Example
+/// ```
+/// let commit = commit_changes("path/to/project", "Use the APA 7 (lth) citation style", &files)?;
+/// ```
+///
+/// # Errors
+/// This function returns an error if the path is not a git repository
+/// or if the files can't be added or commited
+pub fn commit_changes(path: &str, message: &str, files: &[String]) -> Result<String, String> {
+    let repo = git2::Repository::open(path).map_err(|err| format!("Failed to open the git repository: {}", err))?;
 
-    // Commit the files to the repository
-    let tree_id = index.write_tree().expect("Failed to commit the files to the repository");
+    commit_files(&repo, message, Some(files))
+}
 
-    let tree = repo.find_tree(tree_id).expect("Failed to commit the files to the repository");
+/// Add files of a repository to the index and commit them
+/// The commit goes on top of the current one, if there is one
+///
+/// # Arguments
+/// * `repo` - The git repository
+/// * `message` - The message of the commit
+/// * `files` - The files to add, relative to the repository, or `None` for every file
+fn commit_files(repo: &git2::Repository, message: &str, files: Option<&[String]>) -> Result<String, String> {
+    let mut index = repo.index().map_err(|err| format!("Failed to add the files to the repository: {}", err))?;
+
+    match files {
+        // Add all the files to the repository
+        None => index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None).map_err(|err| format!("Failed to add the files to the repository: {}", err))?,
+        // Add the given files, except the ones the repository ignores, like add_all does
+        Some(files) => for file in files {
+            if repo.is_path_ignored(file).unwrap_or(false) {
+                continue;
+            }
+            index.add_path(std::path::Path::new(file)).map_err(|err| format!("Failed to add {} to the repository: {}", file, err))?;
+        },
+    }
+
+    index.write().map_err(|err| format!("Failed to add the files to the repository: {}", err))?;
+
+    // Commit the files to the repository
+    let tree_id = index.write_tree().map_err(|err| format!("Failed to commit the files to the repository: {}", err))?;
 
-    let sig = repo.signature().expect("Failed to commit the files to the repository");
+    let tree = repo.find_tree(tree_id).map_err(|err| format!("Failed to commit the files to the repository: {}", err))?;
 
-    repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[]).expect("Failed to commit the files to the repository");
+    // Without a user in the git config, the commit is signed by lth
+    let sig = repo.signature()
+        .or_else(|_| git2::Signature::now("lth", "lth@localhost"))
+        .map_err(|err| format!("Failed to commit the files to the repository: {}", err))?;
 
-    println!("\x1b[34mInitialized a new git repository at {}\x1b[0m", path);
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
 
-    println!("\x1b[34mAdded the files to the repository\x1b[0m");
+    let commit = repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents).map_err(|err| format!("Failed to commit the files to the repository: {}", err))?;
 
-    println!("\x1b[34mCommitted the files to the repository\x1b[0m");
+    Ok(commit.to_string())
 }
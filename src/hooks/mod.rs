@@ -0,0 +1,202 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+use serde::{Deserialize, Serialize};
+
+use crate::authoring::build;
+use crate::templates::{is_enabled, Answers, Template};
+use crate::{output, status, trace};
+
+/// Built-in actions that a template can run after creating a project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookAction {
+    /// Build the documents once, with make, latexmk or pdflatex
+    Build,
+    /// Open the main file in `$VISUAL` or `$EDITOR`
+    OpenEditor,
+    /// Rebuild the documents on every change with `latexmk -pvc`
+    Watch,
+}
+
+/// Struct to describe a command run in the new project after it is created
+/// A hook is a shell command (`run`) or a built-in action (`action`)
+///
+/// # Example
+/// ```toml
+/// [[hooks]]
+/// action = "build"                    # build, open-editor or watch
+///
+/// [[hooks]]
+/// run = "tlmgr install --usermode apa7"
+/// description = "Install the apa7 class"
+/// when = "install-class"              # Only if the confirm variable or the feature is on
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Hook {
+    pub run: Option<String>,
+    pub action: Option<HookAction>,
+    pub description: Option<String>,
+    pub when: Option<String>,
+    /// Origin of the template that declares the hook, when it is inherited
+    #[serde(skip)]
+    pub base: Option<String>,
+}
+
+/// Implementation of the Hook struct to describe and run the hooks
+impl Hook {
+    /// Check that the hook has a command or an action, but not both
+    ///
+    /// # Errors
+    /// This function returns an error with the message to show to the template author
+    pub fn validate(&self) -> Result<(), String> {
+        match (&self.run, &self.action) {
+            (Some(_), Some(_)) => Err("A hook can't have both run and action".to_string()),
+            (None, None) => Err("A hook needs a run command or an action".to_string()),
+            (Some(command), None) if command.trim().is_empty() => Err("The run command of a hook is empty".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Describe what the hook does, for the plan and the confirmation prompt
+    ///
+    /// # Example
+    /// ```
+    /// let description = hook.describe(); // "Install the apa7 class (tlmgr install --usermode apa7)"
+    /// ```
+    pub fn describe(&self) -> String {
+        let command = match (&self.run, self.action) {
+            (Some(command), _) => command.clone(),
+            (None, Some(HookAction::Build)) => "build the documents once".to_string(),
+            (None, Some(HookAction::OpenEditor)) => "open the main file in $EDITOR".to_string(),
+            (None, Some(HookAction::Watch)) => "latexmk -pvc".to_string(),
+            (None, None) => "nothing".to_string(),
+        };
+
+        match &self.description {
+            Some(description) => format!("{} ({})", description, command),
+            None => command,
+        }
+    }
+
+    /// Run the hook inside the new project
+    /// The commands get the `LTH_PROJECT` and `LTH_TEMPLATE` environment variables
+    ///
+    /// # Arguments
+    /// * `path` - The folder of the new project
+    /// * `template` - The template used to create the project
+    ///
+    /// # Errors
+    /// This function returns an error if the command can't be run or fails
+    pub fn run(&self, path: &str, template: &Template) -> Result<(), String> {
+        self.validate()?;
+
+        match (&self.run, self.action) {
+            (Some(command), _) => shell(command, path, template),
+            (None, Some(HookAction::Build)) => build(path),
+            (None, Some(HookAction::OpenEditor)) => {
+                let editor = std::env::var("VISUAL").or_else(|_| std::env::var("EDITOR"))
+                    .map_err(|_| "Set the EDITOR environment variable to open the project".to_string())?;
+                let file = template.preview.first().map(String::as_str).unwrap_or("main.tex");
+                shell(&format!("{} '{}'", editor, file.replace('\'', "'\\''")), path, template)
+            },
+            (None, Some(HookAction::Watch)) => match Path::new(path).join("main.tex").is_file() {
+                true => shell("latexmk -pvc -pdf main.tex", path, template),
+                false => Err("latexmk -pvc needs a main.tex file".to_string()),
+            },
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Run a shell command inside a folder
+/// With `--output json` the output of the command goes to stderr, so stdout only has the JSON
+fn shell(command: &str, path: &str, template: &Template) -> Result<(), String> {
+    trace!("sh -c {} in {}", command, path);
+
+    let mut process = match cfg!(windows) {
+        true => {
+            let mut process = Command::new("cmd");
+            process.arg("/C").arg(command);
+            process
+        },
+        false => {
+            let mut process = Command::new("sh");
+            process.arg("-c").arg(command);
+            process
+        },
+    };
+
+    process.current_dir(path)
+        .env("LTH_PROJECT", path)
+        .env("LTH_TEMPLATE", &template.name);
+
+    if output::is_json() {
+        process.stdout(Stdio::from(std::io::stderr()));
+    }
+
+    let status = process.status().map_err(|err| format!("Failed to run {}: {}", command, err))?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(format!("The hook {} failed ({})", command, status)),
+    }
+}
+
+/// Return the hooks of a template enabled by the answers of the user
+///
+/// # Arguments
+/// * `template` - The template used to create the project
+/// * `answers` - The answers of the user to the variables and features of the template
+pub fn enabled_hooks<'a>(template: &'a Template, answers: &Answers) -> Vec<&'a Hook> {
+    template.hooks.iter()
+        .filter(|hook| match &hook.when {
+            Some(name) => is_enabled(answers, name),
+            None => true,
+        })
+        .collect()
+}
+
+/// Return the sources that declare some hooks, without duplicates
+/// The user has to trust a source before its hooks can run
+///
+/// # Arguments
+/// * `hooks` - The hooks to run
+/// * `template` - The template used to create the project
+///
+/// # Example
+/// ```
+/// let untrusted: Vec<_> = hook_sources(&hooks, &template).into_iter().filter(|source| !store.is_trusted(source)).collect();
+/// ```
+pub fn hook_sources<'a>(hooks: &[&'a Hook], template: &'a Template) -> Vec<&'a str> {
+    let mut sources = Vec::new();
+    for hook in hooks {
+        let source = template.hook_source(hook);
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+
+    sources
+}
+
+/// Run the hooks of a project, in order, stopping at the first one that fails
+///
+/// # Arguments
+/// * `hooks` - The hooks to run
+/// * `path` - The folder of the new project
+/// * `template` - The template used to create the project
+///
+/// # Example
+/// ```
+/// run_hooks(&enabled_hooks(&template, &answers), &project.path, &template)?;
+/// ```
+///
+/// # Errors
+/// This function returns an error if a hook fails
+pub fn run_hooks(hooks: &[&Hook], path: &str, template: &Template) -> Result<(), String> {
+    for hook in hooks {
+        status!("Running {}", hook.describe());
+        hook.run(path, template)?;
+    }
+
+    Ok(())
+}
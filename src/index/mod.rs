@@ -0,0 +1,329 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::fetch::{Fetcher, SourceFetcher};
+use crate::fuzzy::best_score;
+use crate::registry::TemplateRegistry;
+use crate::templates::{Template, MANIFEST};
+use crate::versions::Release;
+use crate::{trace, warning};
+
+/// Struct with the index a source publishes to describe its templates
+/// The index is a TOML or JSON file, so the templates can be searched without their manifests
+///
+/// # Example
+/// ```toml
+/// name = "TEC templates"
+///
+/// [[templates]]
+/// name = "thesis-tec"
+/// description = "Thesis of the TEC, in Spanish"
+/// version = "2.1.0"
+/// tags = ["thesis", "tec"]
+/// languages = ["es"]
+/// document = "thesis"
+/// source = "https://example.com/templates/thesis-tec"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Index {
+    /// Name of the source, shown with its templates
+    pub name: Option<String>,
+    #[serde(default)]
+    pub templates: Vec<IndexEntry>,
+}
+
+/// Struct to describe a template in an index
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IndexEntry {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub version: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Languages of the documents, as ISO 639-1 codes like `en` or `es`
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Type of document, like paper, report, thesis or slides
+    pub document: Option<String>,
+    /// URL of the folder with the manifest of the template
+    pub source: Option<String>,
+    /// Older versions of the template, added to the releases of its manifest
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub releases: Vec<Release>,
+}
+
+/// Struct with the filters of a search, every filter must match
+#[derive(Debug, Clone, Default)]
+pub struct Filters {
+    /// Tags the template must have, all of them
+    pub tags: Vec<String>,
+    pub language: Option<String>,
+    pub document: Option<String>,
+}
+
+/// Implementation of the IndexEntry struct to describe the templates
+impl IndexEntry {
+    /// Describe a template of the registry in an index
+    ///
+    /// # Example
+    /// ```
+    /// let entry = IndexEntry::new(&template);
+    /// ```
+    pub fn new(template: &Template) -> IndexEntry {
+        IndexEntry {
+            name: template.name.clone(),
+            description: template.description.clone(),
+            version: template.version.clone(),
+            tags: template.tags.clone(),
+            languages: template.languages.clone(),
+            document: template.document.clone(),
+            source: Some(template.source.clone()).filter(|source| !source.is_empty()),
+            releases: Vec::new(),
+        }
+    }
+
+    /// Download the manifest of the template, from the `template.toml` file of its source
+    /// The files of the template are read from the same source, whatever the manifest says
+    ///
+    /// # Arguments
+    /// * `fetcher` - Where the manifest comes from
+    ///
+    /// # Example
+    /// ```
+    /// let template = entry.template(&SourceFetcher)?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the entry has no source,
+    /// or if the manifest can't be downloaded or is not valid
+    pub fn template(&self, fetcher: &dyn Fetcher) -> Result<Template, String> {
+        let source = self.source.as_deref().ok_or_else(|| format!("The template {} of the index has no source", self.name))?;
+        let source = source.trim_end_matches('/');
+
+        let url = format!("{}/{}", source, MANIFEST);
+        let manifest = String::from_utf8(fetcher.fetch(&url)?).map_err(|_| format!("The manifest {} is not UTF-8", url))?;
+        let mut template = Template::parse(&manifest).map_err(|err| format!("{}: {}", url, err))?;
+
+        template.source = source.to_string();
+        template.origin = Some(source.to_string());
+        for release in &self.releases {
+            if !template.releases.iter().any(|existing| existing.version == release.version) {
+                template.releases.push(release.clone());
+            }
+        }
+
+        Ok(template)
+    }
+
+    /// Check if the template passes the filters of a search
+    /// The tags, languages and document types are compared without case
+    ///
+    /// # Arguments
+    /// * `filters` - The filters of the search
+    pub fn matches(&self, filters: &Filters) -> bool {
+        let has = |values: &[String], wanted: &str| values.iter().any(|value| value.eq_ignore_ascii_case(wanted));
+
+        filters.tags.iter().all(|tag| has(&self.tags, tag))
+            && filters.language.as_deref().is_none_or(|language| has(&self.languages, language))
+            && filters.document.as_deref().is_none_or(|document| self.document.as_deref().is_some_and(|own| own.eq_ignore_ascii_case(document)))
+    }
+}
+
+/// Implementation of the Index struct to read, write and search the indexes
+impl Index {
+    /// Create the index of the templates of a registry, as a source would publish it
+    /// The templates are described with their parents and layers
+    ///
+    /// # Example
+    /// ```
+    /// let index = Index::from_registry(&TemplateRegistry::discover());
+    /// ```
+    pub fn from_registry(registry: &TemplateRegistry) -> Index {
+        Index {
+            name: None,
+            templates: registry.resolved().iter().map(IndexEntry::new).collect(),
+        }
+    }
+
+    /// Parse an index, in JSON when it starts with `{`, otherwise in TOML
+    ///
+    /// # Arguments
+    /// * `content` - The content of the index file
+    ///
+    /// # Errors
+    /// This function returns an error if the index is not valid
+    pub fn parse(content: &str) -> Result<Index, String> {
+        match content.trim_start().starts_with('{') {
+            true => serde_json::from_str(content).map_err(|err| format!("Invalid index: {}", err)),
+            false => toml::from_str(content).map_err(|err| format!("Invalid index: {}", err)),
+        }
+    }
+
+    /// Download and parse the index published by a source
+    /// The sources and the archives of the templates can be relative to the index,
+    /// only an index read from the disk can point to local files
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the index file, `file://` URLs are read from the disk
+    /// * `fetcher` - Where the index comes from
+    ///
+    /// # Example
+    /// ```
+    /// let index = Index::fetch("https://example.com/templates/index.toml", &SourceFetcher)?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the index can't be downloaded or is not valid,
+    /// or if a remote index points to a local file
+    pub fn fetch(url: &str, fetcher: &dyn Fetcher) -> Result<Index, String> {
+        trace!("index {}", url);
+        let bytes = fetcher.fetch(url)?;
+        let content = String::from_utf8(bytes).map_err(|_| format!("The index {} is not UTF-8", url))?;
+        let mut index = Index::parse(&content).map_err(|err| format!("{}: {}", url, err))?;
+
+        let base = Url::parse(url).map_err(|err| format!("Invalid index URL {}: {}", url, err))?;
+        for entry in &mut index.templates {
+            let places = entry.source.iter_mut()
+                .chain(entry.releases.iter_mut().flat_map(|release| release.source.iter_mut().chain(release.archive.iter_mut())));
+            for place in places {
+                *place = resolve_url(&base, place).map_err(|err| format!("{}: the template {} {}", url, entry.name, err))?;
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Write the index in TOML, as a source would publish it
+    ///
+    /// # Errors
+    /// This function returns an error if the index can't be written as TOML
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string(self).map_err(|err| format!("Failed to write the index: {}", err))
+    }
+
+    /// Search the templates of the index
+    /// The query is matched fuzzily against the names, the tags and the descriptions,
+    /// the best matches come first
+    ///
+    /// # Arguments
+    /// * `query` - The text typed by the user, an empty query matches every template
+    /// * `filters` - The tags, language and document type the templates must have
+    ///
+    /// # Example
+    /// ```
+    /// let filters = Filters { tags: vec!["thesis".to_string()], language: Some("es".to_string()), document: None };
+    /// let results = index.search("tec", &filters);
+    /// ```
+    pub fn search(&self, query: &str, filters: &Filters) -> Vec<&IndexEntry> {
+        let mut scored: Vec<(usize, i64)> = self.templates.iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.matches(filters))
+            .filter_map(|(index, entry)| {
+                let tags = entry.tags.join(" ");
+                best_score(query, &[(&entry.name, 3), (&tags, 2), (&entry.description, 1)])
+                    .map(|score| (index, score))
+            })
+            .collect();
+
+        // Sort by score, keeping the order of the index on ties
+        scored.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        scored.into_iter().map(|(index, _)| &self.templates[index]).collect()
+    }
+}
+
+/// Resolve a URL of an index, relative URLs start from the folder of the index
+///
+/// # Arguments
+/// * `index` - The URL of the index
+/// * `url` - The URL written in the index
+///
+/// # Example
+/// ```
+/// let index = Url::parse("https://example.com/templates/index.toml")?;
+/// assert_eq!(resolve_url(&index, "thesis-tec")?, "https://example.com/templates/thesis-tec");
+/// ```
+///
+/// # Errors
+/// This function returns an error if the URL is not valid,
+/// or if it is a local file and the index is not
+fn resolve_url(index: &Url, url: &str) -> Result<String, String> {
+    let (resolved, text) = match Url::parse(url) {
+        // The absolute URLs are kept as they are written
+        Ok(absolute) => (absolute, url.to_string()),
+        Err(_) => {
+            let relative = index.join(url).map_err(|err| format!("has an invalid URL {}: {}", url, err))?;
+            let text = relative.to_string();
+            (relative, text)
+        },
+    };
+
+    match resolved.scheme() == "file" && index.scheme() != "file" {
+        true => Err(format!("points to the local file {}, but the index is not local", url)),
+        false => Ok(text),
+    }
+}
+
+/// Find a template in the indexes of the configured sources, and download its manifest
+/// The indexes that can't be read are skipped with a warning
+///
+/// # Arguments
+/// * `urls` - The URLs of the indexes, see `settings::RegistrySettings::index_urls`
+/// * `name` - The name of the template
+/// * `fetcher` - Where the indexes and the manifest come from
+///
+/// # Example
+/// ```
+/// let template = find_template(&settings.registry.index_urls(), "thesis-tec", &SourceFetcher);
+/// ```
+///
+/// # Returns
+/// `None` if no index has the template, otherwise the template or the error to download it
+pub fn find_template(urls: &[String], name: &str, fetcher: &dyn Fetcher) -> Option<Result<Template, String>> {
+    for url in urls {
+        let index = match Index::fetch(url, fetcher) {
+            Ok(index) => index,
+            Err(err) => {
+                warning!("Skipping an index: {}", err);
+                continue;
+            },
+        };
+
+        if let Some(entry) = index.templates.iter().find(|entry| entry.name == name && entry.source.is_some()) {
+            return Some(entry.template(fetcher));
+        }
+    }
+
+    None
+}
+
+/// Return the index of the local templates followed by the indexes of the configured sources
+/// The sources that can't be read are returned as errors, so the search still works offline
+///
+/// # Arguments
+/// * `registry` - The local templates
+/// * `urls` - The URLs of the indexes, see `settings::RegistrySettings`
+///
+/// # Example
+/// ```
+/// let (index, errors) = combined(&TemplateRegistry::discover(), &settings.registry.indexes);
+/// ```
+pub fn combined(registry: &TemplateRegistry, urls: &[String]) -> (Index, Vec<String>) {
+    let mut index = Index::from_registry(registry);
+    let mut errors = Vec::new();
+
+    for url in urls {
+        match Index::fetch(url, &SourceFetcher) {
+            // The local templates and the first sources win over the templates with the same name
+            Ok(remote) => for entry in remote.templates {
+                if !index.templates.iter().any(|existing| existing.name == entry.name) {
+                    index.templates.push(entry);
+                }
+            },
+            Err(err) => errors.push(err),
+        }
+    }
+
+    (index, errors)
+}
@@ -0,0 +1,234 @@
+use serde::Serialize;
+
+use crate::hooks::Hook;
+use crate::output::{paint, print_json, Color};
+use crate::templates::{Backend, Feature, Requirement, Requirements, Template, Variable};
+use crate::versions::available;
+
+/// Struct with the information of a template shown by `lth info`
+#[derive(Debug, Serialize)]
+pub struct TemplateInfo<'a> {
+    pub name: &'a str,
+    pub description: &'a str,
+    pub version: Option<&'a str>,
+    /// Versions that can be pinned with `name@version`, the newest first
+    pub versions: Vec<String>,
+    pub authors: &'a [String],
+    pub credits: &'a [String],
+    pub license: Option<&'a str>,
+    pub source: &'a str,
+    pub tags: &'a [String],
+    pub languages: &'a [String],
+    pub document: Option<&'a str>,
+    pub backend: Backend,
+    pub extends: Option<&'a str>,
+    pub layers: &'a [String],
+    pub files: Vec<InfoFile<'a>>,
+    /// Lines of the directory layout, like the `tree` command draws it
+    pub layout: Vec<String>,
+    pub variables: &'a [Variable],
+    pub features: &'a [Feature],
+    pub hooks: &'a [Hook],
+    pub requirements: &'a Requirements,
+}
+
+/// Struct to describe a file of a template in its information
+#[derive(Debug, Serialize)]
+pub struct InfoFile<'a> {
+    pub path: &'a str,
+    /// Name of the confirm variable that enables the file
+    pub when: Option<&'a str>,
+}
+
+/// Implementation of the TemplateInfo struct to collect and print the information of a template
+impl<'a> TemplateInfo<'a> {
+    /// Collect the information of a template
+    ///
+    /// # Arguments
+    /// * `template` - The template to describe
+    ///
+    /// # Example
+    /// ```
+    /// let info = TemplateInfo::new(&template);
+    /// ```
+    pub fn new(template: &'a Template) -> TemplateInfo<'a> {
+        TemplateInfo {
+            name: &template.name,
+            description: &template.description,
+            version: template.version.as_deref(),
+            versions: available(template).iter().map(ToString::to_string).collect(),
+            authors: &template.authors,
+            credits: &template.credits,
+            license: template.license.as_deref(),
+            source: template.origin(),
+            tags: &template.tags,
+            languages: &template.languages,
+            document: template.document.as_deref(),
+            backend: template.backend(),
+            extends: template.extends.as_deref(),
+            layers: &template.layers,
+            files: template.files.iter()
+                .map(|file| InfoFile { path: &file.asset.path, when: file.when.as_deref() })
+                .collect(),
+            layout: template.layout(),
+            variables: &template.variables,
+            features: &template.features,
+            hooks: &template.hooks,
+            requirements: &template.requirements,
+        }
+    }
+
+    /// Print the information as text
+    ///
+    /// # Output
+    /// ```text
+    /// math 1.0.0
+    /// Latex report, template focused on math
+    /// Authors:  @Johanx22x
+    /// Credits:  @gillescastel, @SirCharlieMars
+    /// License:  not specified
+    /// Source:   https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math
+    /// Tags:     report, math, latex
+    /// Document: report
+    /// Language: en
+    /// Backend:  latex
+    /// Files:
+    ///     ├── lib
+    ///     │   └── preamble.tex
+    ///     └── main.tex
+    /// Variables:
+    ///     git      confirm  Do you want to initialize a git repository? [Y/n]
+    /// Features:
+    ///     figures   on   an images folder for the figures
+    ///     listings  off  code listings with the listings package
+    /// Hooks:
+    ///     none
+    /// Requirements:
+    ///     executables   pdflatex, make
+    ///     TeX packages  amsmath.sty, amssymb.sty, tikz.sty
+    /// ```
+    pub fn print(&self) {
+        match self.version {
+            Some(version) => println!("{} {}", paint(Color::Title, self.name), version),
+            None => println!("{}", paint(Color::Title, self.name)),
+        }
+        println!("{}", self.description);
+
+        println!("Authors:  {}", join_or(self.authors, "unknown"));
+        if !self.credits.is_empty() {
+            println!("Credits:  {}", self.credits.join(", "));
+        }
+        println!("License:  {}", self.license.unwrap_or("not specified"));
+        if self.versions.len() > 1 {
+            println!("Versions: {}", self.versions.join(", "));
+        }
+        println!("Source:   {}", self.source);
+        println!("Tags:     {}", join_or(self.tags, "none"));
+        if let Some(document) = self.document {
+            println!("Document: {}", document);
+        }
+        if !self.languages.is_empty() {
+            println!("Language: {}", self.languages.join(", "));
+        }
+        println!("Backend:  {}", self.backend.name());
+        if let Some(extends) = self.extends {
+            println!("Extends:  {}", extends);
+        }
+        if !self.layers.is_empty() {
+            println!("Layers:   {}", self.layers.join(", "));
+        }
+
+        println!("Files:");
+        for line in &self.layout {
+            println!("\t{}", line);
+        }
+
+        println!("Variables:");
+        if self.variables.is_empty() {
+            println!("\tnone");
+        }
+
+        let width = self.variables.iter().map(|variable| variable.name.len()).max().unwrap_or(0);
+        for variable in self.variables {
+            println!("\t{:width$}  {:12}  {}", variable.name, variable.kind.name(), variable.message(), width = width);
+        }
+
+        println!("Features:");
+        if self.features.is_empty() {
+            println!("\tnone");
+        }
+
+        let width = self.features.iter().map(|feature| feature.name.len()).max().unwrap_or(0);
+        for feature in self.features {
+            println!("\t{:width$}  {:3}  {}", feature.name, if feature.default { "on" } else { "off" }, feature.description, width = width);
+        }
+
+        println!("Hooks:");
+        if self.hooks.is_empty() {
+            println!("\tnone");
+        }
+
+        for hook in self.hooks {
+            match &hook.when {
+                Some(when) => println!("\t{} ({})", hook.describe(), when),
+                None => println!("\t{}", hook.describe()),
+            }
+        }
+
+        println!("Requirements:");
+        let groups = [
+            ("executables", &self.requirements.executables),
+            ("TeX packages", &self.requirements.packages),
+            ("fonts", &self.requirements.fonts),
+        ];
+
+        if groups.iter().all(|(_, group)| group.is_empty()) {
+            println!("\tnone");
+        }
+
+        for (title, group) in groups.iter().filter(|(_, group)| !group.is_empty()) {
+            let items: Vec<String> = group.iter().map(describe_requirement).collect();
+            println!("\t{:12}  {}", title, items.join(", "));
+        }
+    }
+
+    /// Print the information as JSON
+    pub fn print_json(&self) {
+        print_json(self);
+    }
+}
+
+/// Join a list of names, or return the fallback if it is empty
+fn join_or(items: &[String], fallback: &str) -> String {
+    match items.is_empty() {
+        true => fallback.to_string(),
+        false => items.join(", "),
+    }
+}
+
+/// Describe a requirement as it is written in the manifest, marking the optional ones
+fn describe_requirement(requirement: &Requirement) -> String {
+    match requirement.optional {
+        true => format!("{} (optional)", requirement),
+        false => requirement.to_string(),
+    }
+}
+
+/// Display the information of a template
+///
+/// # Arguments
+/// * `template` - The template to show
+/// * `json` - If the information is printed as JSON
+///
+/// # Example
+/// ```
+/// show_info(&check_template("apa7tec"), false);
+/// ```
+pub fn show_info(template: &Template, json: bool) {
+    let info = TemplateInfo::new(template);
+
+    match json {
+        true => info.print_json(),
+        false => info.print(),
+    }
+}
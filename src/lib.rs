@@ -0,0 +1,74 @@
+//! Latex Template Handler [lth]
+//!
+//! Create LaTeX and pandoc projects from templates.
+//! The `lth` command is built on top of this library, so other tools can create projects too:
+//!
+//! ```text
+//! let registry = TemplateRegistry::builtin();
+//! let project = Scaffolder::new(registry.resolve("math")?)
+//!     .path("courses")
+//!     .name("algebra-notes")
+//!     .answer("git", "no")
+//!     .on_event(|event| println!("{:?}", event))
+//!     .create()?;
+//! ```
+
+pub mod assets;
+pub mod authoring;
+pub mod bib;
+pub mod doctor;
+pub mod fetch;
+pub mod fuzzy;
+pub mod git;
+pub mod hooks;
+pub mod index;
+pub mod info;
+pub mod network;
+pub mod output;
+pub mod plan;
+pub mod preflight;
+pub mod prompt;
+pub mod publish;
+pub mod registry;
+pub mod sandbox;
+pub mod scaffold;
+pub mod serve;
+pub mod settings;
+pub mod styles;
+pub mod templates;
+pub mod trust;
+pub mod tui;
+pub mod utils;
+pub mod versions;
+
+pub use fetch::{Fetcher, HttpFetcher, LocalFetcher, MemoryFetcher, SourceFetcher};
+pub use registry::TemplateRegistry;
+pub use scaffold::{Event, Project, Scaffolder};
+pub use templates::Template;
+
+// const map to store the template name and its manifest
+// The manifests describe the files of each template, see src/templates/builtin
+const TEMPLATES: &[(&str, &str)] = &[
+    ("math", include_str!("templates/builtin/math.toml")), // Based on @gillescastel & @SirCharlieMars
+    ("ieee", include_str!("templates/builtin/ieee.toml")),
+    ("ieeetec", include_str!("templates/builtin/ieeetec.toml")),
+    ("apa7tec", include_str!("templates/builtin/apa7tec.toml")), // Provided by @zSnails
+];
+
+// const map to store the layer name and its manifest
+// Layers hold the files shared by several templates, see src/templates/layers
+const LAYERS: &[(&str, &str)] = &[
+    ("pandoc-pipeline", include_str!("templates/layers/pandoc-pipeline.toml")),
+    ("git-setup", include_str!("templates/layers/git-setup.toml")),
+    ("bibliography", include_str!("templates/layers/bibliography.toml")),
+];
+
+// const map to store the style name and its CSL file
+// The citation styles of the pandoc templates, written after the styles of the CSL project (CC BY-SA 3.0), see src/styles/builtin
+const STYLES: &[(&str, &str)] = &[
+    ("ieee", include_str!("styles/builtin/ieee.csl")),
+    ("apa", include_str!("styles/builtin/apa.csl")),
+    ("chicago", include_str!("styles/builtin/chicago.csl")),
+    ("acm", include_str!("styles/builtin/acm.csl")),
+    ("vancouver", include_str!("styles/builtin/vancouver.csl")),
+];
@@ -1,27 +1,41 @@
 use std::env;
 
 mod config;
-mod templates;
-mod utils;
-mod git;
 
 use config::*;
-use templates::*;
 
 // const map to store the option name an its description
 const OPTIONS: &[(&str, &str)] = &[
     ("-h, --help", "Display this help message"),
     ("-l, --list", "Display the available templates"),
     ("-v, --version", "Display the version of the program"),
-];
-
-// const map to store the template name, its description and the function to execute
-const TEMPLATES: &[(&str, &str, fn(&str))] = &[
-    ("math", "Latex report, template focused on math", math), // Based on @gillescastel & @SirCharlieMars
-    ("ieee", "Basic IEEE template, using pandoc & markdown", ieee),
-    ("ieeetec", "Custo template for TEC paper (IEEE style)", ieee_tec),
-    ("apa7tec", "Custom template for TEC papers (APA style), using pandoc & markdown", apa7tec), // Provided by
-                                                                                                // @zSnails
+    ("--name NAME", "Name of the new folder, checked before any prompt"),
+    ("--force", "Create the project inside an existing empty folder"),
+    ("--merge", "Add the template to an existing folder, keeping its files"),
+    ("--dry-run", "Print the planned operations without writing anything"),
+    ("--plan-format FORMAT", "Format of the dry run plan, text or json"),
+    ("--output FORMAT", "Format of the results, text or json"),
+    ("--json", "Same as --output json"),
+    ("-q, --quiet", "Only print the errors, the warnings and the results"),
+    ("--verbose", "Print every file and git step"),
+    ("-vv", "Also print the HTTP requests and the filesystem writes"),
+    ("--log-file PATH", "Append every message to a log file"),
+    ("--with FEATURE", "Turn on features of the template, separated by commas"),
+    ("--without FEATURE", "Turn off features of the template, separated by commas"),
+    ("--versions", "Show the available versions of the templates in the list"),
+    ("--tag TAG", "Only search the templates with these tags, separated by commas"),
+    ("--lang LANG", "Only search the templates in a language, like en or es"),
+    ("--type TYPE", "Only search a type of document, like paper, report or thesis"),
+    ("--port PORT", "Port of lth serve, 8080 by default"),
+    ("--token TOKEN", "Token of the uploads to lth serve, LTH_TOKEN by default"),
+    ("--bump LEVEL", "Part of the version increased by lth template publish: major, minor, patch (default) or none"),
+    ("--to TARGET", "Git repository or lth serve URL where lth template publish sends the template"),
+    ("--bib FILE", "BibTeX file of lth bib, found in the project by default"),
+    ("--style STYLE", "Citation style of a new pandoc project, like ieee, apa, chicago, acm or vancouver"),
+    ("--no-hooks", "Don't run the commands of the template after creating the project"),
+    ("--ignore-requirements", "Create the project even if its requirements are missing"),
+    ("--build", "Build the test project of lth template test"),
+    ("--keep", "Keep the test project of lth template test"),
 ];
 
 /// Main function
@@ -33,16 +47,12 @@ fn main() {
     let config = Config::build(&args).unwrap_or_else(|err| {
         // Check if the error is help
         // If so, print the help message
-        // If is list, print the list of template available
         // Otherwise, print the error message
         if err == "help" {
             handle_bad_usage();
             std::process::exit(0);
-        } else if err == "list" {
-            template_list();
-            std::process::exit(0);
         } else {
-            eprintln!("Problem parsing arguments: \x1b[31m{}\x1b[0m", err);
+            eprintln!("Problem parsing arguments: {}", lth::output::paint_err(lth::output::Color::Red, err));
             handle_bad_usage();
             std::process::exit(1);
         }
@@ -0,0 +1,235 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+use reqwest::blocking::Client;
+use reqwest::{Certificate, Proxy};
+
+use crate::error;
+use crate::settings::{NetworkSettings, Settings};
+
+// Client shared by every download of the program
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Return the HTTP client shared by every template download
+/// The client is built the first time this function is called,
+/// using the network settings of the configuration file
+///
+/// # Example
+/// ```
+/// let response = client().get(url).send();
+/// ```
+///
+/// # Panics
+/// This function will exit the program if the settings are not valid
+/// or if the client can't be built
+pub fn client() -> &'static Client {
+    CLIENT.get_or_init(|| {
+        let settings = Settings::load().and_then(|settings| build_client(&settings.network));
+
+        match settings {
+            Ok(client) => client,
+            Err(err) => {
+                error!("{}", err);
+                std::process::exit(1);
+            },
+        }
+    })
+}
+
+/// Build a new HTTP client using the given network settings
+///
+/// # Arguments
+/// * `settings` - The network settings to apply to the client
+///
+/// # Example
+/// ```
+/// let client = build_client(&settings.network)?;
+/// ```
+///
+/// # Errors
+/// This function returns an error if the proxy URL is not valid,
+/// if a CA bundle can't be read or if the client can't be built
+pub fn build_client(settings: &NetworkSettings) -> Result<Client, String> {
+    let user_agent = settings.user_agent.clone()
+        .unwrap_or_else(|| format!("lth/{}", env!("CARGO_PKG_VERSION")));
+
+    // The proxy environment is handled below, so disable the one of reqwest
+    let mut builder = Client::builder()
+        .user_agent(user_agent)
+        .no_proxy();
+
+    if let Some(proxy) = proxy(settings)? {
+        builder = builder.proxy(proxy);
+    }
+
+    for path in &settings.ca_bundles {
+        for certificate in read_ca_bundle(path)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    if let Some(timeout) = settings.timeout {
+        builder = builder.timeout(Duration::from_secs(timeout));
+    }
+
+    if let Some(timeout) = settings.connect_timeout {
+        builder = builder.connect_timeout(Duration::from_secs(timeout));
+    }
+
+    builder.build().map_err(|err| format!("Failed to build the HTTP client: {}", err))
+}
+
+/// Struct with the proxies of the client, one for each scheme, and the hosts reached without them
+///
+/// # Example
+/// ```
+/// let routes = ProxyRoutes::new(&settings.network)?;
+/// let proxy = routes.route(&reqwest::Url::parse("http://lab-server:8080/index.toml")?);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ProxyRoutes {
+    /// Proxy of the `http://` requests
+    pub http: Option<reqwest::Url>,
+    /// Proxy of the `https://` requests
+    pub https: Option<reqwest::Url>,
+    /// `NO_PROXY` entries, see `host_matches`
+    pub bypass: Vec<String>,
+}
+
+/// Implementation of the ProxyRoutes struct to choose the proxy of each request
+impl ProxyRoutes {
+    /// Read the proxies from the settings and the environment
+    /// The proxy in the settings is used for every scheme, and has priority over the environment:
+    /// `HTTPS_PROXY` for the `https://` requests, `HTTP_PROXY` for the `http://` ones, and `ALL_PROXY` for both
+    ///
+    /// # Arguments
+    /// * `settings` - The network settings with the proxy configuration
+    ///
+    /// # Errors
+    /// This function returns an error if a proxy URL is not valid
+    pub fn new(settings: &NetworkSettings) -> Result<ProxyRoutes, String> {
+        let parse = |url: Option<String>| -> Result<Option<reqwest::Url>, String> {
+            url.map(|url| reqwest::Url::parse(&url).map_err(|err| format!("Invalid proxy URL {}: {}", url, err))).transpose()
+        };
+
+        let https = settings.proxy.clone().or_else(|| env_var(&["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]));
+        let http = settings.proxy.clone().or_else(|| env_var(&["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"]));
+
+        // Hosts from the NO_PROXY environment variable and from the settings
+        let mut bypass: Vec<String> = env_var(&["NO_PROXY", "no_proxy"])
+            .map(|value| value.split(',').map(|host| host.trim().to_string()).collect())
+            .unwrap_or_default();
+        bypass.extend(settings.no_proxy.iter().cloned());
+        bypass.retain(|host| !host.is_empty());
+
+        Ok(ProxyRoutes { http: parse(http)?, https: parse(https)?, bypass })
+    }
+
+    /// Return the proxy of a request, `None` to reach the target directly
+    ///
+    /// # Arguments
+    /// * `target` - The URL of the request
+    pub fn route(&self, target: &reqwest::Url) -> Option<reqwest::Url> {
+        if target.host_str().is_some_and(|host| self.bypass.iter().any(|pattern| host_matches(host, pattern))) {
+            return None;
+        }
+
+        match target.scheme() {
+            "https" => self.https.clone(),
+            "http" => self.http.clone(),
+            _ => None,
+        }
+    }
+}
+
+/// Create the proxy used by the client, if any, see `ProxyRoutes`
+///
+/// # Arguments
+/// * `settings` - The network settings with the proxy configuration
+///
+/// # Errors
+/// This function returns an error if a proxy URL is not valid
+fn proxy(settings: &NetworkSettings) -> Result<Option<Proxy>, String> {
+    let routes = ProxyRoutes::new(settings)?;
+    if routes.http.is_none() && routes.https.is_none() {
+        return Ok(None);
+    }
+
+    let mut proxy = Proxy::custom(move |target| routes.route(target));
+
+    if let Some(username) = &settings.proxy_username {
+        proxy = proxy.basic_auth(username, settings.proxy_password.as_deref().unwrap_or(""));
+    }
+
+    Ok(Some(proxy))
+}
+
+/// Return the value of the first environment variable that is set and not empty
+///
+/// # Arguments
+/// * `names` - The names of the environment variables to check, in order
+fn env_var(names: &[&str]) -> Option<String> {
+    names.iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+}
+
+/// Check if a host matches a `NO_PROXY` entry
+/// The entry `*` matches every host, `.example.com` and `example.com`
+/// match `example.com` and all its subdomains
+///
+/// # Arguments
+/// * `host` - The host of the request
+/// * `pattern` - The `NO_PROXY` entry
+///
+/// # Example
+/// ```
+/// assert!(host_matches("files.example.com", ".example.com"));
+/// ```
+pub fn host_matches(host: &str, pattern: &str) -> bool {
+    // Remove the port of the entry, if any
+    let pattern = pattern.rsplit_once(':')
+        .filter(|(_, port)| port.chars().all(|c| c.is_ascii_digit()))
+        .map_or(pattern, |(host, _)| host);
+
+    if pattern == "*" {
+        return true;
+    }
+
+    let domain = pattern.trim_start_matches('.');
+    host.eq_ignore_ascii_case(domain) || host.to_ascii_lowercase().ends_with(&format!(".{}", domain.to_ascii_lowercase()))
+}
+
+/// Read all the certificates of a PEM bundle
+///
+/// # Example
+/// ```
+/// let certificates = read_ca_bundle(Path::new("/etc/ssl/certs/university-root.pem"))?;
+/// ```
+///
+/// # Arguments
+/// * `path` - The path to the PEM file
+///
+/// # Errors
+/// This function returns an error if the file can't be read,
+/// if it doesn't contain certificates or if a certificate is not valid
+pub fn read_ca_bundle(path: &std::path::Path) -> Result<Vec<Certificate>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read the CA bundle {}: {}", path.display(), err))?;
+
+    const END: &str = "-----END CERTIFICATE-----";
+
+    // Split the bundle in its certificates, each one ends with the END marker
+    let certificates = content.split_inclusive(END)
+        .filter(|block| block.contains(END))
+        .map(|block| {
+            Certificate::from_pem(block.trim().as_bytes())
+                .map_err(|err| format!("Invalid certificate in {}: {}", path.display(), err))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if certificates.is_empty() {
+        return Err(format!("No certificates found in {}", path.display()));
+    }
+
+    Ok(certificates)
+}
@@ -0,0 +1,284 @@
+use std::fs::File;
+use std::io::{IsTerminal, Write};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+
+/// Format of the results printed by the program
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Colored text for humans, the default
+    #[default]
+    Text,
+    /// Structured results for editors and scripts
+    Json,
+}
+
+/// Implementation of the OutputFormat enum to read it from the arguments
+impl OutputFormat {
+    /// Parse the value of `--output`
+    ///
+    /// # Example
+    /// ```
+    /// let format = OutputFormat::parse("json"); // Some(OutputFormat::Json)
+    /// ```
+    pub fn parse(value: &str) -> Option<OutputFormat> {
+        match value {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// How much the program tells about what it is doing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only the errors, the warnings and the results (`--quiet`)
+    Quiet,
+    /// The progress of the program, the default
+    #[default]
+    Normal,
+    /// Every file and git step (`--verbose`)
+    Verbose,
+    /// The HTTP requests and the filesystem writes (`-vv`)
+    Trace,
+}
+
+/// Level of a message, each level has its own color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+    Status,
+    Detail,
+    Trace,
+}
+
+/// Colors used in the text output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Dim,
+    /// Underlined blue, used for the names of the templates
+    Title,
+}
+
+/// Struct with the output settings of the program
+#[derive(Debug, Default)]
+pub struct Output {
+    pub format: OutputFormat,
+    pub verbosity: Verbosity,
+    /// File where every message is appended, given with `--log-file`
+    pub log_file: Option<Mutex<File>>,
+}
+
+// Output settings, set once when the arguments are parsed
+static OUTPUT: OnceLock<Output> = OnceLock::new();
+
+/// Print an error to stderr, errors are always shown
+///
+/// # Example
+/// ```
+/// error!("Failed to download the file {}", url);
+/// ```
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => { $crate::output::emit($crate::output::Level::Error, &format!($($arg)*)) };
+}
+
+/// Print a warning to stderr, warnings are shown even with `--quiet`
+#[macro_export]
+macro_rules! warning {
+    ($($arg:tt)*) => { $crate::output::emit($crate::output::Level::Warning, &format!($($arg)*)) };
+}
+
+/// Print a progress message, hidden with `--quiet`
+/// With `--output json` the message goes to stderr, so stdout only has the JSON result
+///
+/// # Example
+/// ```
+/// status!("Created the new folder at {}", path);
+/// ```
+#[macro_export]
+macro_rules! status {
+    ($($arg:tt)*) => { $crate::output::emit($crate::output::Level::Status, &format!($($arg)*)) };
+}
+
+/// Print a message only shown with `--verbose`
+#[macro_export]
+macro_rules! detail {
+    ($($arg:tt)*) => { $crate::output::emit($crate::output::Level::Detail, &format!($($arg)*)) };
+}
+
+/// Print a message only shown with `-vv`, used for the HTTP requests and the filesystem writes
+///
+/// # Example
+/// ```
+/// trace!("GET {}", url);
+/// ```
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => { $crate::output::emit($crate::output::Level::Trace, &format!($($arg)*)) };
+}
+
+/// Set the output settings of the program
+/// Only the first call has an effect
+///
+/// # Arguments
+/// * `output` - The settings given with `--output`, `--quiet`, `--verbose` and `--log-file`
+pub fn init(output: Output) {
+    let _ = OUTPUT.set(output);
+}
+
+/// Return the output settings of the program
+fn settings() -> &'static Output {
+    OUTPUT.get_or_init(Output::default)
+}
+
+/// Return the output format of the program
+pub fn format() -> OutputFormat {
+    settings().format
+}
+
+/// Check if the results are printed as JSON
+pub fn is_json() -> bool {
+    format() == OutputFormat::Json
+}
+
+/// Return the verbosity of the program
+pub fn verbosity() -> Verbosity {
+    settings().verbosity
+}
+
+/// Check if colors can be used in a stream
+/// Colors are disabled when `NO_COLOR` is set, when `TERM` is `dumb`
+/// and when the stream is not a terminal
+fn use_colors(terminal: bool) -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let dumb = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+
+    terminal && !no_color && !dumb
+}
+
+/// Return the escape sequence of a color
+fn escape(color: Color) -> &'static str {
+    match color {
+        Color::Red => "\x1b[31m",
+        Color::Green => "\x1b[32m",
+        Color::Yellow => "\x1b[33m",
+        Color::Blue => "\x1b[34m",
+        Color::Dim => "\x1b[2m",
+        Color::Title => "\x1b[4;34m",
+    }
+}
+
+/// Color a text printed to stdout, if stdout can show colors
+///
+/// # Example
+/// ```
+/// println!("{} {}", paint(Color::Green, "✔"), name);
+/// ```
+pub fn paint(color: Color, text: &str) -> String {
+    match use_colors(std::io::stdout().is_terminal()) {
+        true => format!("{}{}\x1b[0m", escape(color), text),
+        false => text.to_string(),
+    }
+}
+
+/// Color a text printed to stderr, if stderr can show colors
+pub fn paint_err(color: Color, text: &str) -> String {
+    match use_colors(std::io::stderr().is_terminal()) {
+        true => format!("{}{}\x1b[0m", escape(color), text),
+        false => text.to_string(),
+    }
+}
+
+/// Print a message with the color of its level, if the verbosity allows it
+/// Every message is appended to the log file, whatever the verbosity
+/// Use the macros instead, like `status!`
+///
+/// # Arguments
+/// * `level` - The level of the message
+/// * `message` - The message, without colors
+pub fn emit(level: Level, message: &str) {
+    let output = settings();
+    log(level, message);
+
+    let (minimum, color) = match level {
+        Level::Error => (Verbosity::Quiet, Color::Red),
+        Level::Warning => (Verbosity::Quiet, Color::Yellow),
+        Level::Status => (Verbosity::Normal, Color::Blue),
+        Level::Detail => (Verbosity::Verbose, Color::Blue),
+        Level::Trace => (Verbosity::Trace, Color::Dim),
+    };
+
+    if output.verbosity < minimum {
+        return;
+    }
+
+    // Errors, warnings and traces never mix with the results,
+    // the progress messages only leave stdout for the JSON output
+    let to_stderr = matches!(level, Level::Error | Level::Warning | Level::Trace) || output.format == OutputFormat::Json;
+
+    match to_stderr {
+        true => eprintln!("{}", paint_err(color, message)),
+        false => println!("{}", paint(color, message)),
+    }
+}
+
+/// Append a message to the log file, with the time and its level
+fn log(level: Level, message: &str) {
+    let file = match &settings().log_file {
+        Some(file) => file,
+        None => return,
+    };
+
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs_f64()).unwrap_or(0.0);
+    let level = match level {
+        Level::Error => "ERROR",
+        Level::Warning => "WARN",
+        Level::Status => "INFO",
+        Level::Detail => "DEBUG",
+        Level::Trace => "TRACE",
+    };
+
+    if let Ok(mut file) = file.lock() {
+        let _ = writeln!(file, "{:.3} {:5} {}", time, level, message);
+    }
+}
+
+/// Open the log file given with `--log-file`, the messages are appended to it
+///
+/// # Arguments
+/// * `path` - The path to the log file
+///
+/// # Errors
+/// This function returns an error if the file can't be opened
+pub fn open_log_file(path: &str) -> Result<Mutex<File>, String> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map(Mutex::new)
+        .map_err(|err| format!("Failed to open the log file {}: {}", path, err))
+}
+
+/// Print a result as pretty JSON to stdout
+///
+/// # Arguments
+/// * `value` - The result to print
+///
+/// # Example
+/// ```
+/// print_json(&plan);
+/// ```
+///
+/// # Panics
+/// This function will panic if the value can't be serialized
+pub fn print_json(value: &impl Serialize) {
+    println!("{}", serde_json::to_string_pretty(value).expect("Failed to serialize the result"));
+}
@@ -0,0 +1,347 @@
+use std::path::Path;
+use serde::Serialize;
+
+use crate::assets::Asset;
+use crate::git::*;
+use crate::hooks::enabled_hooks;
+use crate::output::{paint, print_json, Color, OutputFormat};
+use crate::preflight::Collision;
+use crate::sandbox::Sandbox;
+use crate::templates::{is_enabled, Answers, Patch, Template};
+use crate::fetch::Fetcher;
+use crate::scaffold::{Event, Project};
+use crate::{trace, warning};
+
+/// Struct with all the operations needed to create a project
+/// Creating a project builds a plan and applies it,
+/// a dry run only prints it
+#[derive(Debug, Serialize)]
+pub struct Plan {
+    pub template: String,
+    pub target: String,
+    pub directories: Vec<PlannedDirectory>,
+    pub files: Vec<PlannedFile>,
+    pub git: Vec<String>,
+    pub hooks: Vec<String>,
+    /// What to do with the existing files, only the files of the template are committed when merging
+    #[serde(skip)]
+    collision: Collision,
+    /// Answers of the user, for the files with placeholders
+    #[serde(skip)]
+    answers: Answers,
+}
+
+/// Struct to describe a directory of the plan
+#[derive(Debug, Serialize)]
+pub struct PlannedDirectory {
+    pub path: String,
+    pub exists: bool,
+}
+
+/// What happens with a file of the plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileAction {
+    Create,
+    /// The file already exists and is kept, when merging
+    Skip,
+}
+
+/// Struct to describe a file of the plan
+#[derive(Debug, Serialize)]
+pub struct PlannedFile {
+    pub path: String,
+    /// URL of the file, or `inline` for files with their content in the manifest
+    pub source: String,
+    /// Size in bytes, only known after `Plan::measure`
+    pub size: Option<u64>,
+    pub action: FileAction,
+    /// Features that add lines to the file
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    #[serde(skip)]
+    asset: Asset,
+    #[serde(skip)]
+    patches: Vec<Patch>,
+    #[serde(skip)]
+    base: String,
+}
+
+/// Implementation of the Plan struct to build, print and apply the plans
+impl Plan {
+    /// Build the plan to create a project with a template
+    /// Nothing is written and nothing is downloaded
+    ///
+    /// # Arguments
+    /// * `template` - The template used to create the project
+    /// * `path` - The path where the new folder is created
+    /// * `name` - The name of the new folder
+    /// * `answers` - The answers of the user to the variables of the template
+    /// * `collision` - What to do with the existing files when the folder already exists
+    ///
+    /// # Example
+    /// ```
+    /// let plan = Plan::build(&template, ".", "thesis", &answers, Collision::Abort);
+    /// ```
+    pub fn build(template: &Template, path: &str, name: &str, answers: &Answers, collision: Collision) -> Plan {
+        let target = format!("{}/{}", path, name);
+        let root = Path::new(&target);
+
+        // The new folder, the folders of the template and the parents of its files
+        let mut directories = vec![PlannedDirectory { path: target.clone(), exists: root.is_dir() }];
+        let mut add_directory = |directory: &str| {
+            let path = format!("{}/{}", target, directory.trim_matches('/'));
+            if !directories.iter().any(|planned| planned.path == path) {
+                directories.push(PlannedDirectory { exists: Path::new(&path).is_dir(), path });
+            }
+        };
+
+        // The folders of the enabled features too
+        let features: Vec<_> = template.features.iter().filter(|feature| is_enabled(answers, &feature.name)).collect();
+        for folder in template.folders.iter().chain(features.iter().flat_map(|feature| &feature.folders)) {
+            add_directory(folder);
+        }
+
+        // Only the files that are always part of the template
+        // and the files enabled by the answers of the user
+        let enabled = template.files.iter().filter(|file| match &file.when {
+            Some(variable) => is_enabled(answers, variable),
+            None => true,
+        });
+
+        let mut files = Vec::new();
+        for file in enabled {
+            if let Some(parent) = Path::new(&file.asset.path).parent().and_then(|parent| parent.to_str()) {
+                if !parent.is_empty() {
+                    add_directory(parent);
+                }
+            }
+
+            // Keep the files of the existing folder when merging
+            let action = match collision == Collision::Merge && root.join(&file.asset.path).exists() {
+                true => FileAction::Skip,
+                false => FileAction::Create,
+            };
+
+            let source = match &file.asset.content {
+                Some(_) => "inline".to_string(),
+                None => file.asset.url(template.base_of(file)),
+            };
+
+            // Lines added by the enabled features
+            let patched: Vec<_> = features.iter()
+                .flat_map(|feature| feature.patches.iter().map(move |patch| (feature, patch)))
+                .filter(|(_, patch)| patch.file == file.asset.path)
+                .collect();
+
+            let mut names: Vec<String> = patched.iter().map(|(feature, _)| feature.name.clone()).collect();
+            names.dedup();
+
+            files.push(PlannedFile {
+                path: file.asset.path.clone(),
+                source,
+                size: file.asset.content.as_ref().map(|content| content.len() as u64),
+                action,
+                features: names,
+                asset: file.asset.clone(),
+                patches: patched.into_iter().map(|(_, patch)| patch.clone()).collect(),
+                base: template.base_of(file).to_string(),
+            });
+        }
+
+        // A patch is lost when its file is disabled or kept by the merge
+        for feature in &features {
+            for patch in &feature.patches {
+                match files.iter().find(|file| file.path == patch.file) {
+                    None => warning!("The feature {} changes {}, but the file is not part of the project", feature.name, patch.file),
+                    Some(file) if file.action == FileAction::Skip => warning!("The feature {} changes {}, but the existing file is kept", feature.name, patch.file),
+                    Some(_) => {},
+                }
+            }
+        }
+
+        let git = match is_enabled(answers, "git") {
+            true => vec![
+                format!("Initialize a git repository in {}", target),
+                match collision {
+                    Collision::Merge => "Add the files of the template to the repository".to_string(),
+                    _ => "Add all the files to the repository".to_string(),
+                },
+                "Commit the files to the repository".to_string(),
+            ],
+            false => Vec::new(),
+        };
+
+        Plan {
+            template: template.name.clone(),
+            target,
+            directories,
+            files,
+            git,
+            hooks: enabled_hooks(template, answers).iter().map(|hook| hook.describe()).collect(),
+            collision,
+            answers: answers.clone(),
+        }
+    }
+
+    /// Get the size of the files that are downloaded, without downloading them
+    ///
+    /// # Arguments
+    /// * `fetcher` - Where the files of the template come from
+    ///
+    /// # Example
+    /// ```
+    /// plan.measure(&HttpFetcher);
+    /// ```
+    pub fn measure(&mut self, fetcher: &dyn Fetcher) {
+        for file in self.files.iter_mut().filter(|file| file.size.is_none()) {
+            file.size = fetcher.size(&file.source);
+        }
+    }
+
+    /// Print the plan in the given format
+    ///
+    /// # Arguments
+    /// * `format` - The format of the output
+    ///
+    /// # Output
+    /// ```text
+    /// Plan to create math at ./thesis
+    /// Directories:
+    ///     ./thesis
+    ///     ./thesis/lib
+    /// Files:
+    ///     main.tex         2.1 KB  https://raw.githubusercontent.com/.../math/main.tex
+    ///     src/chap01.tex   0 B     inline
+    /// Git:
+    ///     Initialize a git repository in ./thesis
+    /// Hooks:
+    ///     none
+    /// ```
+    pub fn print(&self, format: OutputFormat) {
+        if format == OutputFormat::Json {
+            print_json(self);
+            return;
+        }
+
+        println!("{}", paint(Color::Blue, &format!("Plan to create {} at {}", self.template, self.target)));
+
+        println!("Directories:");
+        for directory in &self.directories {
+            match directory.exists {
+                true => println!("\t{} (exists)", directory.path),
+                false => println!("\t{}", directory.path),
+            }
+        }
+
+        println!("Files:");
+        let width = self.files.iter().map(|file| file.path.len()).max().unwrap_or(0);
+        for file in &self.files {
+            let size = file.size.map(format_size).unwrap_or_else(|| "?".to_string());
+            match file.action {
+                FileAction::Create if !file.features.is_empty() => {
+                    println!("\t{:width$}  {:>9}  {} (+ {})", file.path, size, file.source, file.features.join(", "), width = width);
+                },
+                FileAction::Create => println!("\t{:width$}  {:>9}  {}", file.path, size, file.source, width = width),
+                FileAction::Skip => println!("\t{:width$}  {:>9}  {}", file.path, "", paint(Color::Yellow, "skip, already exists"), width = width),
+            }
+        }
+
+        println!("Git:");
+        print_list(&self.git);
+
+        println!("Hooks:");
+        print_list(&self.hooks);
+    }
+
+    /// Apply the plan, creating the folders and the files of the project
+    /// The progress is given to the callback as events
+    ///
+    /// # Arguments
+    /// * `fetcher` - Where the files of the template come from
+    /// * `on_event` - The callback that receives the progress
+    ///
+    /// # Example
+    /// ```
+    /// let project = plan.apply(&HttpFetcher, &mut |event| println!("{:?}", event))?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if a folder can't be created,
+    /// if a file can't be downloaded or written, or if the git repository can't be initialized
+    pub fn apply(&self, fetcher: &dyn Fetcher, on_event: &mut dyn FnMut(&Event)) -> Result<Project, String> {
+        // The new folder is chosen by the user, the folders inside it come from the manifest
+        let sandbox = Sandbox::new(&self.target);
+        for directory in self.directories.iter().filter(|directory| !Path::new(&directory.path).is_dir()) {
+            on_event(&Event::CreateDirectory { path: directory.path.clone() });
+            match directory.path.strip_prefix(&format!("{}/", self.target)) {
+                Some(folder) => { sandbox.create_folder(folder)?; },
+                None => {
+                    trace!("mkdir {}", directory.path);
+                    std::fs::create_dir_all(&directory.path).map_err(|err| format!("Failed to create the folder {}: {}", directory.path, err))?;
+                },
+            }
+        }
+
+        let mut project = Project {
+            template: self.template.clone(),
+            path: self.target.clone(),
+            files: Vec::new(),
+            skipped: Vec::new(),
+            git_commit: None,
+        };
+
+        for file in &self.files {
+            let path = format!("{}/{}", self.target, file.path);
+
+            match file.action {
+                FileAction::Create => {
+                    if file.asset.content.is_none() {
+                        on_event(&Event::Download { url: file.source.clone() });
+                    }
+                    file.asset.install(&self.target, &file.base, fetcher, &self.answers, &file.patches)?;
+                    on_event(&Event::WriteFile { path });
+                    project.files.push(file.path.clone());
+                },
+                FileAction::Skip => {
+                    on_event(&Event::SkipFile { path });
+                    project.skipped.push(file.path.clone());
+                },
+            }
+        }
+
+        if !self.git.is_empty() {
+            on_event(&Event::InitGit { path: self.target.clone() });
+            let id = init_git(&self.target, &project.files, self.collision)?;
+            on_event(&Event::Commit { id: id.clone() });
+            project.git_commit = Some(id);
+        }
+
+        Ok(project)
+    }
+}
+
+/// Print the items of a list of the plan, or `none`
+fn print_list(items: &[String]) {
+    if items.is_empty() {
+        println!("\tnone");
+    }
+
+    for item in items {
+        println!("\t{}", item);
+    }
+}
+
+/// Format a size in bytes for humans
+///
+/// # Example
+/// ```
+/// assert_eq!(format_size(2150), "2.1 KB");
+/// ```
+pub fn format_size(size: u64) -> String {
+    match size {
+        size if size < 1024 => format!("{} B", size),
+        size if size < 1024 * 1024 => format!("{:.1} KB", size as f64 / 1024.0),
+        size => format!("{:.1} MB", size as f64 / (1024.0 * 1024.0)),
+    }
+}
@@ -0,0 +1,128 @@
+use std::path::Path;
+
+use crate::prompt;
+use crate::{status, warning};
+use crate::templates::Template;
+
+/// What to do when the folder of the new project already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Collision {
+    /// Stop before creating anything, the default
+    #[default]
+    Abort,
+    /// Use the folder if it is empty (`--force`)
+    Force,
+    /// Use the folder even if it has files, existing files are kept (`--merge`)
+    Merge,
+}
+
+/// Check that the folder of the new project can be used
+///
+/// # Arguments
+/// * `path` - The path where the new folder is created
+/// * `name` - The name of the new folder
+/// * `collision` - What to do if the folder already exists
+///
+/// # Example
+/// ```
+/// check_target(".", "thesis", Collision::Abort)?;
+/// ```
+///
+/// # Errors
+/// This function returns an error with the message to show to the user
+/// if the name is not valid or if the existing folder can't be used
+pub fn check_target(path: &str, name: &str, collision: Collision) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("The folder name can't be empty".to_string());
+    }
+
+    if name.contains('/') || name.contains('\\') {
+        return Err(format!("Invalid folder name '{}', it can't contain slashes", name));
+    }
+
+    if name == "." || name == ".." {
+        return Err(format!("Invalid folder name '{}'", name));
+    }
+
+    let target = Path::new(path).join(name);
+
+    if !target.exists() {
+        return Ok(());
+    }
+
+    if !target.is_dir() {
+        return Err(format!("{}/{} already exists and is not a folder", path, name));
+    }
+
+    let empty = std::fs::read_dir(&target)
+        .map_err(|err| format!("Failed to read {}/{}: {}", path, name, err))?
+        .next()
+        .is_none();
+
+    match collision {
+        Collision::Abort if empty => Err(format!("The folder {}/{} already exists, use --force to create the project inside it", path, name)),
+        Collision::Abort => Err(format!("The folder {}/{} already exists, use --merge to add the template to it", path, name)),
+        Collision::Force if !empty => Err(format!("The folder {}/{} is not empty, use --merge to add the template to it", path, name)),
+        Collision::Force | Collision::Merge => Ok(()),
+    }
+}
+
+/// Ask the name of the new folder until it can be used
+///
+/// # Arguments
+/// * `path` - The path where the new folder is created
+/// * `collision` - What to do if the folder already exists
+///
+/// # Example
+/// ```
+/// let name = ask_folder_name(".", Collision::Abort);
+/// ```
+pub fn ask_folder_name(path: &str, collision: Collision) -> String {
+    prompt::text("Enter the name of the new folder", None, |name| check_target(path, name, collision))
+}
+
+/// Return the files of the template that already exist in the folder of the new project
+/// Files that depend on a variable are included, since the answers are not known yet
+///
+/// # Arguments
+/// * `target` - The path to the folder of the new project
+/// * `template` - The template used to create the project
+///
+/// # Example
+/// ```
+/// let existing = existing_files("./thesis", &template);
+/// ```
+pub fn existing_files(target: &str, template: &Template) -> Vec<String> {
+    template.files.iter()
+        .map(|file| file.asset.path.clone())
+        .filter(|path| Path::new(target).join(path).exists())
+        .collect()
+}
+
+/// Show what happens with the existing folder of the new project
+///
+/// # Arguments
+/// * `target` - The path to the folder of the new project
+/// * `template` - The template used to create the project
+///
+/// # Output
+/// ```text
+/// Using the existing folder ./thesis
+/// The following files already exist and will be kept:
+///     main.tex
+///     lib/preamble.tex
+/// ```
+pub fn show_summary(target: &str, template: &Template) {
+    if !Path::new(target).is_dir() {
+        return;
+    }
+
+    status!("Using the existing folder {}", target);
+
+    let existing = existing_files(target, template);
+    if existing.is_empty() {
+        return;
+    }
+
+    warning!("The following files already exist and will be kept: {}", existing.join(", "));
+}
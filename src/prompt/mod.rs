@@ -0,0 +1,280 @@
+use std::io::{BufRead, Write};
+
+use crate::error;
+
+/// Ask a question until the answer is valid
+/// The answer is trimmed and given to `parse`, when `parse` returns an error
+/// the error is shown and the question is asked again
+///
+/// # Arguments
+/// * `message` - The question, with the default value in brackets
+/// * `parse` - Function that validates and converts the answer
+///
+/// # Example
+/// ```
+/// let age: u32 = ask("Age", |input| input.parse().map_err(|_| "Enter a number".to_string()));
+/// ```
+///
+/// # Panics
+/// This function will exit the program if stdin is closed before a valid answer
+pub fn ask<T>(message: &str, parse: impl Fn(&str) -> Result<T, String>) -> T {
+    let stdin = std::io::stdin();
+
+    // The questions go to stderr when stdout is used by the JSON results
+    let answer = match crate::output::is_json() {
+        true => ask_with(&mut stdin.lock(), &mut std::io::stderr(), message, parse),
+        false => ask_with(&mut stdin.lock(), &mut std::io::stdout(), message, parse),
+    };
+
+    match answer {
+        Some(value) => value,
+        None => {
+            eprintln!();
+            error!("No answer for: {}", message);
+            std::process::exit(1);
+        },
+    }
+}
+
+/// Ask a question using the given input and output until the answer is valid
+///
+/// # Arguments
+/// * `input` - Where the answers are read from
+/// * `output` - Where the questions and the errors are written to
+/// * `message` - The question, with the default value in brackets
+/// * `parse` - Function that validates and converts the answer
+///
+/// # Returns
+/// `None` if the input ends before a valid answer
+pub fn ask_with<T>(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    message: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Option<T> {
+    loop {
+        // Print the message without a new line at the end
+        // and flush it, so it is shown before the user input
+        write!(output, "{}: ", message).ok()?;
+        output.flush().ok()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+
+        match parse(line.trim()) {
+            Ok(value) => return Some(value),
+            Err(err) => {
+                writeln!(output, "{}", crate::output::paint(crate::output::Color::Red, &err)).ok()?;
+            },
+        }
+    }
+}
+
+/// Ask a yes or no question
+/// An empty answer takes the default value, any other answer
+/// that is not yes or no is rejected
+///
+/// # Arguments
+/// * `message` - The question
+/// * `default` - The answer used when the user just presses enter
+///
+/// # Example
+/// ```
+/// let git = confirm("Do you want to initialize a git repository?", true);
+/// ```
+///
+/// # Output
+/// ```text
+/// Do you want to initialize a git repository? [Y/n]:
+/// ```
+pub fn confirm(message: &str, default: bool) -> bool {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+
+    ask(&format!("{} {}", message, hint), |input| {
+        if input.is_empty() {
+            return Ok(default);
+        }
+        parse_confirm(input)
+    })
+}
+
+/// Parse a yes or no answer
+///
+/// # Arguments
+/// * `input` - The answer of the user
+///
+/// # Example
+/// ```
+/// assert_eq!(parse_confirm("Y"), Ok(true));
+/// ```
+///
+/// # Errors
+/// This function returns an error if the answer is not yes or no
+pub fn parse_confirm(input: &str) -> Result<bool, String> {
+    match input.to_lowercase().as_str() {
+        "y" | "yes" | "true" => Ok(true),
+        "n" | "no" | "false" => Ok(false),
+        _ => Err(format!("Invalid answer '{}', answer yes or no", input)),
+    }
+}
+
+/// Ask a text question
+/// An empty answer takes the default value, then the answer is validated
+///
+/// # Arguments
+/// * `message` - The question
+/// * `default` - The answer used when the user just presses enter
+/// * `validate` - Function that checks the answer
+///
+/// # Example
+/// ```
+/// let name = text("Enter the name of the new folder", None, |name| validate_folder_name(".", name));
+/// ```
+///
+/// # Output
+/// ```text
+/// Enter the name of the new folder:
+/// ```
+pub fn text(message: &str, default: Option<&str>, validate: impl Fn(&str) -> Result<(), String>) -> String {
+    let message = match default {
+        Some(default) if !default.is_empty() => format!("{} [{}]", message, default),
+        _ => message.to_string(),
+    };
+
+    ask(&message, |input| {
+        let value = if input.is_empty() { default.unwrap_or("") } else { input };
+        validate(value)?;
+        Ok(value.to_string())
+    })
+}
+
+/// Ask the user to choose one of the options
+/// The user can answer with the number or the text of the option
+///
+/// # Arguments
+/// * `message` - The question
+/// * `options` - The options to choose from
+/// * `default` - The index of the option used when the user just presses enter
+///
+/// # Example
+/// ```
+/// let style = select("Citation style", &styles, Some(0));
+/// ```
+///
+/// # Output
+/// ```text
+///   1) ieee
+///   2) apa
+/// Citation style [1]:
+/// ```
+pub fn select(message: &str, options: &[String], default: Option<usize>) -> usize {
+    print_options(options);
+
+    let message = match default {
+        Some(index) => format!("{} [{}]", message, index + 1),
+        None => message.to_string(),
+    };
+
+    ask(&message, |input| {
+        match (input.is_empty(), default) {
+            (true, Some(index)) => Ok(index),
+            _ => parse_option(input, options),
+        }
+    })
+}
+
+/// Ask the user to choose any number of the options
+/// The answer is a list of numbers or texts separated by commas,
+/// `none` chooses no option
+///
+/// # Arguments
+/// * `message` - The question
+/// * `options` - The options to choose from
+/// * `defaults` - The indexes of the options used when the user just presses enter
+///
+/// # Example
+/// ```
+/// let features = multi_select("Features", &features, &[0, 2]);
+/// ```
+///
+/// # Output
+/// ```text
+///   1) bibliography
+///   2) glossary
+///   3) appendix
+/// Features, separated by commas [1,3]:
+/// ```
+pub fn multi_select(message: &str, options: &[String], defaults: &[usize]) -> Vec<usize> {
+    print_options(options);
+
+    let hint = match defaults.is_empty() {
+        true => "none".to_string(),
+        false => defaults.iter().map(|index| (index + 1).to_string()).collect::<Vec<_>>().join(","),
+    };
+
+    ask(&format!("{}, separated by commas [{}]", message, hint), |input| {
+        if input.is_empty() {
+            return Ok(defaults.to_vec());
+        }
+        parse_options(input, options)
+    })
+}
+
+/// Parse the answer to a select question, the number or the text of an option
+///
+/// # Arguments
+/// * `input` - The answer of the user
+/// * `options` - The options to choose from
+///
+/// # Errors
+/// This function returns an error if the answer is not one of the options
+pub fn parse_option(input: &str, options: &[String]) -> Result<usize, String> {
+    let input = input.trim();
+
+    if let Ok(number) = input.parse::<usize>() {
+        if number >= 1 && number <= options.len() {
+            return Ok(number - 1);
+        }
+    }
+
+    options.iter()
+        .position(|option| option.eq_ignore_ascii_case(input))
+        .ok_or_else(|| format!("Invalid option '{}', choose one of: {}", input, options.join(", ")))
+}
+
+/// Parse the answer to a multi-select question
+///
+/// # Arguments
+/// * `input` - The answer of the user
+/// * `options` - The options to choose from
+///
+/// # Errors
+/// This function returns an error if any of the answers is not one of the options
+pub fn parse_options(input: &str, options: &[String]) -> Result<Vec<usize>, String> {
+    if input.trim().eq_ignore_ascii_case("none") {
+        return Ok(Vec::new());
+    }
+
+    let mut indexes = Vec::new();
+    for part in input.split(',').filter(|part| !part.trim().is_empty()) {
+        let index = parse_option(part, options)?;
+        if !indexes.contains(&index) {
+            indexes.push(index);
+        }
+    }
+
+    Ok(indexes)
+}
+
+/// Print the numbered options of a select question
+/// The options go with the questions, so they are shown even with `--quiet`
+fn print_options(options: &[String]) {
+    for (index, option) in options.iter().enumerate() {
+        match crate::output::is_json() {
+            true => eprintln!("  {}) {}", index + 1, option),
+            false => println!("  {}) {}", index + 1, option),
+        }
+    }
+}
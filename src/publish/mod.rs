@@ -0,0 +1,340 @@
+use std::path::{Path, PathBuf};
+use semver::Version;
+use serde::Serialize;
+
+use crate::authoring::{lint, Severity};
+use crate::network::client;
+use crate::serve::ARCHIVES;
+use crate::templates::{Template, MANIFEST};
+use crate::versions::{pack, sha256, unpack};
+use crate::{detail, trace};
+
+/// Part of the version increased by `lth template publish`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bump {
+    Major,
+    Minor,
+    Patch,
+    /// Publish the version of the manifest as it is
+    None,
+}
+
+/// Where `lth template publish` sends the templates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// A git repository with a folder per template and an `archives` folder, the layout read by `lth serve`
+    Git(String),
+    /// A server started with `lth serve --token TOKEN`
+    Server(String),
+}
+
+/// Struct with the archive of a published version of a template
+#[derive(Debug, Clone, Serialize)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    /// Name of the archive, like `thesis-1.2.0.tar.gz`
+    pub file: String,
+    /// SHA-256 checksum of the archive
+    pub sha256: String,
+    #[serde(skip)]
+    pub archive: Vec<u8>,
+}
+
+/// Implementation of the Bump enum to read and apply the bumps
+impl Bump {
+    /// Parse the value of `--bump`
+    ///
+    /// # Example
+    /// ```
+    /// assert_eq!(Bump::parse("minor"), Some(Bump::Minor));
+    /// ```
+    pub fn parse(value: &str) -> Option<Bump> {
+        match value {
+            "major" => Some(Bump::Major),
+            "minor" => Some(Bump::Minor),
+            "patch" => Some(Bump::Patch),
+            "none" => Some(Bump::None),
+            _ => None,
+        }
+    }
+
+    /// Return the next version, the lower parts are reset
+    ///
+    /// # Example
+    /// ```
+    /// assert_eq!(Bump::Minor.apply(&Version::new(1, 2, 3)), Version::new(1, 3, 0));
+    /// ```
+    pub fn apply(&self, version: &Version) -> Version {
+        match self {
+            Bump::Major => Version::new(version.major + 1, 0, 0),
+            Bump::Minor => Version::new(version.major, version.minor + 1, 0),
+            Bump::Patch => Version::new(version.major, version.minor, version.patch + 1),
+            Bump::None => version.clone(),
+        }
+    }
+}
+
+/// Implementation of the Target enum to read the targets
+impl Target {
+    /// Read a target given with `--to` or in the `publish` setting
+    /// The URLs ending in `.git`, the `git@` and `ssh://` URLs and the local paths are git repositories,
+    /// the other `http://` and `https://` URLs are servers started with `lth serve`
+    ///
+    /// # Example
+    /// ```
+    /// assert_eq!(Target::parse("http://lab-server:8080"), Target::Server("http://lab-server:8080".to_string()));
+    /// ```
+    pub fn parse(target: &str) -> Target {
+        let target = target.trim_end_matches('/');
+        let http = target.starts_with("http://") || target.starts_with("https://");
+
+        match http && !target.ends_with(".git") {
+            true => Target::Server(target.to_string()),
+            false => Target::Git(target.to_string()),
+        }
+    }
+}
+
+/// Replace the version of a manifest, keeping the rest of the file as it is
+/// The version goes after the name when the manifest has none
+///
+/// # Arguments
+/// * `manifest` - The content of the `template.toml` file
+/// * `version` - The new version
+///
+/// # Errors
+/// This function returns an error if the manifest has no version and no name
+pub fn set_version(manifest: &str, version: &str) -> Result<String, String> {
+    let mut lines: Vec<String> = manifest.lines().map(String::from).collect();
+    let line = format!("version = \"{}\"", version);
+
+    // Only the keys before the first table belong to the template itself
+    let end = lines.iter().position(|line| line.trim_start().starts_with('[')).unwrap_or(lines.len());
+    let key = |line: &str, name: &str| line.trim_start().strip_prefix(name).is_some_and(|rest| rest.trim_start().starts_with('='));
+
+    match lines[..end].iter().position(|existing| key(existing, "version")) {
+        Some(index) => lines[index] = line,
+        None => {
+            let name = lines[..end].iter().position(|existing| key(existing, "name")).ok_or("The manifest has no name")?;
+            lines.insert(name + 1, line);
+        },
+    }
+
+    let mut content = lines.join("\n");
+    if manifest.ends_with('\n') {
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+/// Publish a new version of a template
+/// The template is checked, its version is bumped in its manifest and its folder is packed in an archive;
+/// the manifest is restored if the archive can't be published
+///
+/// # Arguments
+/// * `dir` - The folder of the template
+/// * `bump` - The part of the version to increase
+/// * `target` - Where the archive goes, or `None` to only pack it, like a dry run
+/// * `token` - The token of the server, see `serve::Server::with_token`
+///
+/// # Example
+/// ```
+/// let package = publish(Path::new("thesis"), Bump::Minor, Some(&Target::parse("http://lab-server:8080")), Some("secret"))?;
+/// println!("Published {} ({})", package.file, package.sha256);
+/// ```
+///
+/// # Errors
+/// This function returns an error if the template has errors,
+/// or if the version is already published or can't be sent
+pub fn publish(dir: &Path, bump: Bump, target: Option<&Target>, token: Option<&str>) -> Result<Package, String> {
+    let errors: Vec<String> = lint(dir).into_iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .map(|issue| issue.message)
+        .collect();
+    if !errors.is_empty() {
+        return Err(format!("The template has {} error(s), check it with lth template lint: {}", errors.len(), errors.join("; ")));
+    }
+
+    let template = Template::load(dir)?;
+    let current = match (&template.version, bump) {
+        (Some(version), _) => Version::parse(version).map_err(|err| format!("The version {} is not valid: {}", version, err))?,
+        (None, Bump::None) => return Err(format!("The template {} has no version to publish", template.name)),
+        (None, _) => Version::new(0, 0, 0),
+    };
+    let version = bump.apply(&current).to_string();
+
+    // The archive has the manifest with the new version
+    let path = dir.join(MANIFEST);
+    let original = std::fs::read_to_string(&path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+    let write = |content: &str| std::fs::write(&path, content).map_err(|err| format!("Failed to write {}: {}", path.display(), err));
+    write(&set_version(&original, &version)?)?;
+
+    let result = pack(dir, &format!("{}-{}", template.name, version)).and_then(|archive| {
+        let package = Package {
+            file: format!("{}-{}.tar.gz", template.name, version),
+            sha256: sha256(&archive),
+            name: template.name.clone(),
+            version: version.clone(),
+            archive,
+        };
+
+        match target {
+            Some(Target::Git(repository)) => push_git(repository, &package)?,
+            Some(Target::Server(server)) => upload(server, &package, token)?,
+            None => {},
+        }
+        Ok(package)
+    });
+
+    // Only a published version stays in the manifest
+    if result.is_err() || target.is_none() {
+        write(&original)?;
+    }
+    result
+}
+
+/// Send an archive to a server started with `lth serve --token TOKEN`
+fn upload(server: &str, package: &Package, token: Option<&str>) -> Result<(), String> {
+    let url = format!("{}/{}/{}", server, ARCHIVES, package.file);
+    detail!("Uploading {}", url);
+
+    let mut request = client().put(&url)
+        .header("X-Checksum-Sha256", &package.sha256)
+        .body(package.archive.clone());
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request.send().map_err(|err| format!("Failed to upload {}: {}", url, err))?;
+    let status = response.status();
+    let message = response.text().unwrap_or_default();
+
+    match status.is_success() {
+        true => Ok(()),
+        false => Err(format!("The server refused {} ({}): {}", package.file, status, message.trim())),
+    }
+}
+
+/// Commit a version to a git registry, tag it and push the commit and the tag
+/// The folder of the template is replaced by the files of the archive, which is kept in the `archives` folder
+fn push_git(repository: &str, package: &Package) -> Result<(), String> {
+    let clone = std::env::temp_dir().join(format!("lth-publish-{}-{}", package.name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&clone);
+
+    let result = commit_version(repository, &clone, package);
+    let _ = std::fs::remove_dir_all(&clone);
+    result
+}
+
+/// Clone the registry, commit the version and push it, see `push_git`
+fn commit_version(repository: &str, clone: &Path, package: &Package) -> Result<(), String> {
+    let failed = |err: git2::Error| format!("Failed to publish to {}: {}", repository, err.message());
+
+    detail!("Cloning {}", repository);
+    let mut builder = git2::build::RepoBuilder::new();
+    let mut fetch = git2::FetchOptions::new();
+    fetch.remote_callbacks(callbacks());
+    let repo = builder.fetch_options(fetch).clone(repository, clone).map_err(failed)?;
+
+    let tag = format!("{}-v{}", package.name, package.version);
+    if repo.find_reference(&format!("refs/tags/{}", tag)).is_ok() {
+        return Err(format!("The version {} of {} is already published in {}", package.version, package.name, repository));
+    }
+
+    // The files of the template, as they are in the archive
+    let folder = clone.join(&package.name);
+    let unpacked = clone.join(format!(".{}", package.name));
+    let files = unpack(&package.archive, &unpacked)?;
+    let _ = std::fs::remove_dir_all(&folder);
+    std::fs::rename(&files, &folder).map_err(|err| format!("Failed to write {}: {}", folder.display(), err))?;
+    let _ = std::fs::remove_dir_all(&unpacked);
+
+    let archives = clone.join(ARCHIVES);
+    std::fs::create_dir_all(&archives).map_err(|err| format!("Failed to create the folder {}: {}", archives.display(), err))?;
+    write_archive(&archives, package)?;
+
+    // Commit every change, the removed files too
+    let mut index = repo.index().map_err(failed)?;
+    index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None).map_err(failed)?;
+    index.update_all(["*"].iter(), None).map_err(failed)?;
+    index.write().map_err(failed)?;
+    let tree = repo.find_tree(index.write_tree().map_err(failed)?).map_err(failed)?;
+
+    let signature = repo.signature().or_else(|_| git2::Signature::now("lth", "lth@localhost")).map_err(failed)?;
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let message = format!("Publish {} {}", package.name, package.version);
+    let id = repo.commit(Some("HEAD"), &signature, &signature, &message, &tree, &parents).map_err(failed)?;
+
+    let commit = repo.find_object(id, None).map_err(failed)?;
+    repo.tag(&tag, &commit, &signature, &message, false).map_err(failed)?;
+    trace!("commit {} tagged {}", id, tag);
+
+    // The branch of the clone and the new tag go to the registry
+    let branch = repo.head().map_err(failed)?.name().map(String::from).ok_or("The branch of the registry has no name")?;
+    let refspecs = [format!("{}:{}", branch, branch), format!("refs/tags/{}:refs/tags/{}", tag, tag)];
+
+    detail!("Pushing {} to {}", tag, repository);
+    let mut rejected = Vec::new();
+    {
+        let mut callbacks = callbacks();
+        callbacks.push_update_reference(|reference, status| {
+            if let Some(status) = status {
+                rejected.push(format!("{}: {}", reference, status));
+            }
+            Ok(())
+        });
+        let mut push = git2::PushOptions::new();
+        push.remote_callbacks(callbacks);
+        repo.find_remote("origin").and_then(|mut remote| remote.push(&refspecs, Some(&mut push))).map_err(failed)?;
+    }
+
+    match rejected.is_empty() {
+        true => Ok(()),
+        false => Err(format!("The registry {} refused the push: {}", repository, rejected.join(", "))),
+    }
+}
+
+/// Write an archive and its checksum in the format of `sha256sum`, like `thesis-1.2.0.tar.gz.sha256`
+///
+/// # Arguments
+/// * `archives` - The `archives` folder of a registry or of a server
+/// * `package` - The archive to write
+///
+/// # Errors
+/// This function returns an error if the files can't be written
+pub fn write_archive(archives: &Path, package: &Package) -> Result<PathBuf, String> {
+    let path = archives.join(&package.file);
+    std::fs::write(&path, &package.archive).map_err(|err| format!("Failed to write {}: {}", path.display(), err))?;
+    std::fs::write(archives.join(format!("{}.sha256", package.file)), format!("{}  {}\n", package.sha256, package.file))
+        .map_err(|err| format!("Failed to write the checksum of {}: {}", package.file, err))?;
+    Ok(path)
+}
+
+/// Return the callbacks that find the credentials of a git remote,
+/// from the SSH agent or from the credential helper of the git config
+/// libgit2 asks again after rejected credentials, so they are only given once
+fn callbacks<'a>() -> git2::RemoteCallbacks<'a> {
+    let mut callbacks = git2::RemoteCallbacks::new();
+    let mut attempts = 0;
+    callbacks.credentials(move |url, username, allowed| {
+        // Without a user in the URL, libgit2 asks for it before the key
+        if allowed == git2::CredentialType::USERNAME {
+            return git2::Cred::username(username.unwrap_or("git"));
+        }
+
+        attempts += 1;
+        if attempts > 1 {
+            return Err(git2::Error::from_str(&format!("The credentials for {} were rejected", url)));
+        }
+
+        if allowed.contains(git2::CredentialType::SSH_KEY) {
+            return git2::Cred::ssh_key_from_agent(username.unwrap_or("git"));
+        }
+
+        let config = git2::Config::open_default()?;
+        git2::Cred::credential_helper(&config, url, username)
+    });
+    callbacks
+}
@@ -0,0 +1,219 @@
+use std::path::Path;
+
+use crate::settings::template_dirs;
+use crate::templates::{builtins, layer, Template, MANIFEST};
+use crate::versions::{parse_spec, select};
+use crate::warning;
+
+/// Struct with the templates that can be used to create projects
+///
+/// # Example
+/// ```
+/// let registry = TemplateRegistry::builtin();
+/// for template in registry.templates() {
+///     println!("{}", template.name);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TemplateRegistry {
+    templates: Vec<Template>,
+}
+
+/// Implementation of the TemplateRegistry struct to find the templates
+impl TemplateRegistry {
+    /// Create an empty registry
+    pub fn new() -> TemplateRegistry {
+        TemplateRegistry::default()
+    }
+
+    /// Create a registry with the built-in templates
+    ///
+    /// # Panics
+    /// This function will panic if the manifest of a built-in template is not valid
+    pub fn builtin() -> TemplateRegistry {
+        TemplateRegistry { templates: builtins() }
+    }
+
+    /// Create a registry with the built-in templates and the templates of the user
+    /// The templates of the user replace the built-in templates with the same name,
+    /// see `settings::template_dirs`
+    ///
+    /// # Example
+    /// ```
+    /// let registry = TemplateRegistry::discover();
+    /// ```
+    pub fn discover() -> TemplateRegistry {
+        let mut registry = TemplateRegistry::builtin();
+
+        for dir in template_dirs() {
+            registry.add_dir(&dir);
+        }
+
+        registry
+    }
+
+    /// Add the local templates of a folder, one sub-folder with a manifest for each template
+    /// The templates that can't be loaded are skipped with a warning
+    ///
+    /// # Arguments
+    /// * `dir` - The folder with the templates
+    pub fn add_dir(&mut self, dir: &Path) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        // Sort the folders, so the order of the templates doesn't depend on the filesystem
+        let mut folders: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.join(MANIFEST).is_file())
+            .collect();
+        folders.sort();
+
+        for folder in folders {
+            match Template::load(&folder) {
+                Ok(template) => self.add(template),
+                Err(err) => warning!("Skipping the template at {}: {}", folder.display(), err),
+            }
+        }
+    }
+
+    /// Add a template to the registry
+    /// A template with the same name is replaced
+    ///
+    /// # Arguments
+    /// * `template` - The template to add
+    pub fn add(&mut self, template: Template) {
+        self.templates.retain(|existing| existing.name != template.name);
+        self.templates.push(template);
+    }
+
+    /// Add a template from its TOML manifest
+    ///
+    /// # Arguments
+    /// * `manifest` - The content of the manifest
+    ///
+    /// # Errors
+    /// This function returns an error if the manifest is not valid
+    pub fn add_manifest(&mut self, manifest: &str) -> Result<(), String> {
+        self.add(Template::parse(manifest)?);
+        Ok(())
+    }
+
+    /// Find a template by its name
+    ///
+    /// # Arguments
+    /// * `name` - The name of the template
+    ///
+    /// # Example
+    /// ```
+    /// let template = registry.get("math");
+    /// ```
+    pub fn get(&self, name: &str) -> Option<&Template> {
+        self.templates.iter().find(|template| template.name == name)
+    }
+
+    /// Find a template by its name, at the version given after `@`
+    /// Without a version, the template is returned as it is declared
+    ///
+    /// # Arguments
+    /// * `spec` - The name of the template, like `ieee` or `ieee@1.2`
+    ///
+    /// # Example
+    /// ```
+    /// let template = registry.get_version("ieee@1.2")?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the template doesn't exist, if no version matches
+    /// or if the files of the version can't be fetched, see `versions::select`
+    pub fn get_version(&self, spec: &str) -> Result<Template, String> {
+        let (name, requirement) = parse_spec(spec)?;
+        let template = self.get(name).ok_or_else(|| format!("The template {} doesn't exist", name))?;
+        select(template, requirement.as_ref())
+    }
+
+    /// Return the templates of the registry, as they are declared
+    /// Use `TemplateRegistry::resolved` to get them with their parents and layers
+    pub fn templates(&self) -> &[Template] {
+        &self.templates
+    }
+
+    /// Find a template by its name, with the files, variables and requirements
+    /// of the template it extends and of its layers
+    ///
+    /// # Arguments
+    /// * `spec` - The name of the template, with an optional version like `lab-thesis@2`
+    ///
+    /// # Example
+    /// ```
+    /// let template = registry.resolve("lab-thesis")?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the template, its parent or a layer doesn't exist,
+    /// if a version doesn't exist or if a template extends itself
+    pub fn resolve(&self, spec: &str) -> Result<Template, String> {
+        self.compose(&self.get_version(spec)?)
+    }
+
+    /// Merge a template with the template it extends and with its layers
+    /// The parent comes first, then the layers in order, then the template itself
+    ///
+    /// # Arguments
+    /// * `template` - The template, it doesn't need to be in the registry
+    ///
+    /// # Errors
+    /// This function returns an error if the parent or a layer doesn't exist,
+    /// or if a template extends itself
+    pub fn compose(&self, template: &Template) -> Result<Template, String> {
+        self.compose_chain(template, &mut Vec::new())
+    }
+
+    /// Merge a template with its parents, keeping the chain of names to find the cycles
+    fn compose_chain(&self, template: &Template, chain: &mut Vec<String>) -> Result<Template, String> {
+        if chain.contains(&template.name) {
+            chain.push(template.name.clone());
+            return Err(format!("The template {} extends itself: {}", template.name, chain.join(" -> ")));
+        }
+        chain.push(template.name.clone());
+
+        let mut base = match &template.extends {
+            Some(spec) => {
+                let (parent, requirement) = parse_spec(spec)?;
+                let parent = self.get(parent).ok_or_else(|| format!("The template {} extends the unknown template {}", template.name, parent))?;
+                Some(self.compose_chain(&select(parent, requirement.as_ref())?, chain)?)
+            },
+            None => None,
+        };
+
+        // Layers are templates of the registry or built-in layers
+        for spec in &template.layers {
+            let (name, requirement) = parse_spec(spec)?;
+            let found = match self.get(name) {
+                Some(found) => select(found, requirement.as_ref())?,
+                None => layer(name).ok_or_else(|| format!("The template {} uses the unknown layer {}", template.name, name))?,
+            };
+            let found = self.compose_chain(&found, chain)?;
+
+            base = Some(match base {
+                Some(base) => found.inherit(&base),
+                None => found,
+            });
+        }
+
+        chain.pop();
+        Ok(match base {
+            Some(base) => template.clone().inherit(&base),
+            None => template.clone(),
+        })
+    }
+
+    /// Return the templates of the registry with their parents and layers
+    /// The templates that can't be resolved are skipped with a warning
+    pub fn resolved(&self) -> Vec<Template> {
+        self.templates.iter()
+            .filter_map(|template| self.compose(template).map_err(|err| warning!("Skipping the template {}: {}", template.name, err)).ok())
+            .collect()
+    }
+}
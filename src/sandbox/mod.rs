@@ -0,0 +1,156 @@
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use crate::trace;
+
+/// Struct to write the files and folders of a project without leaving its folder
+/// The manifests of third-party templates are not trusted: their paths can't be absolute,
+/// can't have `..` components and can't go through a symlink that points outside of the project
+///
+/// # Example
+/// ```
+/// let sandbox = Sandbox::new("path/to/project");
+/// sandbox.create_file("lib/preamble.tex", b"\\usepackage{amsmath}")?;
+/// sandbox.create_file("../../.bashrc", b"")?; // Error
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    root: PathBuf,
+}
+
+/// Implementation of the Sandbox struct to check the paths and write inside the project
+impl Sandbox {
+    /// Create a sandbox for the folder of a project
+    ///
+    /// # Arguments
+    /// * `root` - The folder of the project, the files can't be written outside of it
+    pub fn new(root: impl AsRef<Path>) -> Sandbox {
+        Sandbox { root: root.as_ref().to_path_buf() }
+    }
+
+    /// Return the folder of the project
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Return the full path of a file of the project, after checking that it stays inside the project
+    /// The existing part of the path is resolved, so a symlink of the project can't point outside of it
+    ///
+    /// # Arguments
+    /// * `name` - The path of the file, relative to the project
+    ///
+    /// # Example
+    /// ```
+    /// let path = sandbox.resolve("lib/preamble.tex")?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the path is absolute, goes up with `..`
+    /// or goes through a symlink outside of the project
+    pub fn resolve(&self, name: &str) -> Result<PathBuf, String> {
+        check_relative(name)?;
+
+        let path = self.root.join(name);
+        let root = self.root.canonicalize().map_err(|err| format!("Failed to open the folder {}: {}", self.root.display(), err))?;
+
+        // The deepest part of the path that exists, without following a symlink at the end
+        let mut existing = path.as_path();
+        while existing.symlink_metadata().is_err() {
+            existing = match existing.parent() {
+                Some(parent) => parent,
+                None => return Ok(path),
+            };
+        }
+
+        // A broken symlink can't be resolved, but writing through it would create its target
+        let real = existing.canonicalize().map_err(|_| format!("{}: the path goes through a broken symlink", name))?;
+        if !real.starts_with(&root) {
+            return Err(format!("{}: the path goes outside of the folder through a symlink", name));
+        }
+
+        Ok(path)
+    }
+
+    /// Create a folder of the project, with its missing parents
+    ///
+    /// # Arguments
+    /// * `name` - The path of the folder, relative to the project
+    ///
+    /// # Errors
+    /// This function returns an error if the path is not safe or the folder can't be created
+    pub fn create_folder(&self, name: &str) -> Result<PathBuf, String> {
+        let path = self.resolve(name.trim_end_matches('/'))?;
+
+        trace!("mkdir {}", path.display());
+        std::fs::create_dir_all(&path).map_err(|err| format!("Failed to create the folder {}: {}", path.display(), err))?;
+
+        Ok(path)
+    }
+
+    /// Create a new file of the project and write the content exactly as given
+    /// The missing parent folders of the file are created too
+    ///
+    /// # Arguments
+    /// * `name` - The path of the new file, relative to the project
+    /// * `content` - The bytes to write in the new file
+    ///
+    /// # Example
+    /// ```
+    /// let path = sandbox.create_file("main.tex", b"content")?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the path is not safe,
+    /// or if the file can't be created, written or closed
+    pub fn create_file(&self, name: &str, content: &[u8]) -> Result<PathBuf, String> {
+        let path = self.resolve(name)?;
+
+        // Create the parent folders of the new file
+        if let Some(parent) = Path::new(name).parent().and_then(|parent| parent.to_str()).filter(|parent| !parent.is_empty()) {
+            self.create_folder(parent)?;
+        }
+
+        // Create the new file
+        trace!("write {} ({} bytes)", path.display(), content.len());
+        let mut file = std::fs::File::create(&path).map_err(|err| format!("Failed to create the file {}: {}", path.display(), err))?;
+
+        // Write the content to the new file
+        file.write_all(content).map_err(|err| format!("Failed to write the file {}: {}", path.display(), err))?;
+
+        // Close the file
+        file.flush().map_err(|err| format!("Failed to close the file {}: {}", path.display(), err))?;
+
+        Ok(path)
+    }
+}
+
+/// Check that a path of a manifest stays inside the new folder
+///
+/// # Arguments
+/// * `path` - The path to check, relative to the project
+///
+/// # Example
+/// ```
+/// assert!(check_relative("lib/preamble.tex").is_ok());
+/// assert!(check_relative("../../.bashrc").is_err());
+/// ```
+///
+/// # Errors
+/// This function returns an error with the path and the reason
+pub fn check_relative(path: &str) -> Result<(), String> {
+    if path.trim().is_empty() {
+        return Err("'': the path is empty".to_string());
+    }
+
+    // Windows paths are rejected on every system, like C:\ or \\server
+    let absolute = Path::new(path).components().any(|component| matches!(component, Component::RootDir | Component::Prefix(_)));
+    if absolute || path.starts_with('/') || path.starts_with('\\') || path.contains(':') {
+        return Err(format!("{}: the path must be relative", path));
+    }
+
+    if path.split(['/', '\\']).any(|component| component == "..") {
+        return Err(format!("{}: the path can't go outside of the folder", path));
+    }
+
+    Ok(())
+}
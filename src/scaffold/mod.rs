@@ -0,0 +1,218 @@
+use serde::Serialize;
+
+use crate::fetch::{Fetcher, SourceFetcher};
+use crate::plan::Plan;
+use crate::preflight::{check_target, Collision};
+use crate::templates::{Answers, Template, VariableKind};
+
+/// Progress of the creation of a project, given to the callback of the Scaffolder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A folder of the project is created
+    CreateDirectory { path: String },
+    /// A file of the template is downloaded
+    Download { url: String },
+    /// A file of the project is written
+    WriteFile { path: String },
+    /// An existing file is kept, when merging
+    SkipFile { path: String },
+    /// A git repository is initialized in the project
+    InitGit { path: String },
+    /// The files of the project were committed
+    Commit { id: String },
+}
+
+// Callback that receives the progress of the creation
+pub type EventCallback<'a> = Box<dyn FnMut(&Event) + 'a>;
+
+/// Struct with the project created by a Scaffolder
+#[derive(Debug, Clone, Serialize)]
+pub struct Project {
+    pub template: String,
+    pub path: String,
+    /// Files written to the new folder
+    pub files: Vec<String>,
+    /// Existing files that were kept, when merging
+    pub skipped: Vec<String>,
+    /// Id of the commit with the files, if a git repository was initialized
+    pub git_commit: Option<String>,
+}
+
+/// Builder to create a project from a template, without any prompt
+/// Variables without an answer take their default values
+///
+/// # Example
+/// ```
+/// let registry = TemplateRegistry::builtin();
+/// let project = Scaffolder::new(registry.resolve("math")?)
+///     .path("courses")
+///     .name("algebra-notes")
+///     .answer("git", "no")
+///     .on_event(|event| println!("{:?}", event))
+///     .create()?;
+/// ```
+pub struct Scaffolder<'a> {
+    template: Template,
+    path: String,
+    name: Option<String>,
+    answers: Answers,
+    collision: Collision,
+    fetcher: Box<dyn Fetcher + 'a>,
+    on_event: Option<EventCallback<'a>>,
+}
+
+/// Implementation of the Scaffolder struct to configure and create the projects
+impl<'a> Scaffolder<'a> {
+    /// Create a new Scaffolder for a template
+    /// The project is created in the current folder, in a folder named after the template
+    ///
+    /// # Arguments
+    /// * `template` - The template used to create the project
+    pub fn new(template: Template) -> Scaffolder<'a> {
+        Scaffolder {
+            template,
+            path: String::from("."),
+            name: None,
+            answers: Answers::new(),
+            collision: Collision::Abort,
+            fetcher: Box::new(SourceFetcher),
+            on_event: None,
+        }
+    }
+
+    /// Set the path where the new folder is created
+    pub fn path(mut self, path: impl Into<String>) -> Scaffolder<'a> {
+        self.path = path.into();
+        self
+    }
+
+    /// Set the name of the new folder
+    pub fn name(mut self, name: impl Into<String>) -> Scaffolder<'a> {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Answer a variable of the template, the answer is validated when the plan is built
+    ///
+    /// # Arguments
+    /// * `variable` - The name of the variable
+    /// * `value` - The answer, like the user would type it
+    pub fn answer(mut self, variable: impl Into<String>, value: impl Into<String>) -> Scaffolder<'a> {
+        self.answers.insert(variable.into(), value.into());
+        self
+    }
+
+    /// Answer several variables of the template
+    pub fn answers(mut self, answers: Answers) -> Scaffolder<'a> {
+        self.answers.extend(answers);
+        self
+    }
+
+    /// Set what to do if the new folder already exists
+    pub fn collision(mut self, collision: Collision) -> Scaffolder<'a> {
+        self.collision = collision;
+        self
+    }
+
+    /// Set where the files of the template come from
+    /// By default they are downloaded, or read from the disk for the local templates
+    ///
+    /// # Example
+    /// ```
+    /// let scaffolder = scaffolder.fetcher(LocalFetcher::new(&template.source, "../latex-templates/math"));
+    /// ```
+    pub fn fetcher(mut self, fetcher: impl Fetcher + 'a) -> Scaffolder<'a> {
+        self.fetcher = Box::new(fetcher);
+        self
+    }
+
+    /// Set the callback that receives the progress of the creation
+    ///
+    /// # Example
+    /// ```
+    /// let scaffolder = scaffolder.on_event(|event| match event {
+    ///     Event::WriteFile { path } => println!("Created {}", path),
+    ///     _ => {},
+    /// });
+    /// ```
+    pub fn on_event(mut self, callback: impl FnMut(&Event) + 'a) -> Scaffolder<'a> {
+        self.on_event = Some(Box::new(callback));
+        self
+    }
+
+    /// Return the name of the new folder
+    pub fn folder_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.template.name)
+    }
+
+    /// Validate the answers and complete them with the default values
+    /// The features are answered like confirm variables, with `yes` or `no`
+    ///
+    /// # Errors
+    /// This function returns an error if an answer is not valid,
+    /// if a required variable has no answer or if a variable doesn't exist
+    pub fn resolve_answers(&self) -> Result<Answers, String> {
+        let questions = self.template.questions();
+        if let Some(unknown) = self.answers.keys().find(|name| !questions.iter().any(|variable| &variable.name == *name)) {
+            return Err(format!("The template {} has no variable or feature called {}", self.template.name, unknown));
+        }
+
+        questions.iter()
+            .map(|variable| {
+                let input = match self.answers.get(&variable.name).map(String::as_str) {
+                    // An empty multi-select answer chooses no option, instead of the defaults
+                    Some("") if variable.kind == VariableKind::MultiSelect => "none",
+                    Some(input) => input,
+                    None => "",
+                };
+                Ok((variable.name.clone(), variable.resolve(input)?))
+            })
+            .collect()
+    }
+
+    /// Build the plan of the project without writing anything
+    /// The sizes of the files are only known after `Plan::measure`, see `Scaffolder::measured_plan`
+    ///
+    /// # Example
+    /// ```
+    /// let plan = scaffolder.plan()?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the folder can't be used or if an answer is not valid
+    pub fn plan(&self) -> Result<Plan, String> {
+        check_target(&self.path, self.folder_name(), self.collision)?;
+        let answers = self.resolve_answers()?;
+
+        Ok(Plan::build(&self.template, &self.path, self.folder_name(), &answers, self.collision))
+    }
+
+    /// Build the plan of the project with the sizes of its files
+    ///
+    /// # Errors
+    /// This function returns an error if the folder can't be used or if an answer is not valid
+    pub fn measured_plan(&self) -> Result<Plan, String> {
+        let mut plan = self.plan()?;
+        plan.measure(self.fetcher.as_ref());
+        Ok(plan)
+    }
+
+    /// Create the project
+    ///
+    /// # Example
+    /// ```
+    /// let project = scaffolder.create()?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the plan can't be built,
+    /// or if a file can't be downloaded or written
+    pub fn create(mut self) -> Result<Project, String> {
+        let plan = self.plan()?;
+
+        match self.on_event.as_mut() {
+            Some(callback) => plan.apply(self.fetcher.as_ref(), callback.as_mut()),
+            None => plan.apply(self.fetcher.as_ref(), &mut |_| {}),
+        }
+    }
+}
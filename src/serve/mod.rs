@@ -0,0 +1,322 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use semver::Version;
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::index::{Index, IndexEntry};
+use crate::publish::{write_archive, Package};
+use crate::sandbox::Sandbox;
+use crate::templates::{Template, MANIFEST};
+use crate::versions::{pack, sha256, unpack, Release};
+use crate::{detail, status, warning};
+
+// Folder of the served directory with the archives of the older versions
+pub const ARCHIVES: &str = "archives";
+
+// Biggest archive accepted by an upload, in bytes
+const MAX_UPLOAD: u64 = 64 * 1024 * 1024;
+
+/// Struct with a HTTP server that shares a folder of templates, like the user templates folder
+/// Each sub-folder with a `template.toml` manifest is a template
+///
+/// The server publishes:
+/// * `/index.toml` and `/index.json` - The index of the templates, see `index::Index`
+/// * `/templates/NAME/PATH` - The manifest and the files of each template
+/// * `/archives/NAME-VERSION.tar.gz` - The archives of the releases, kept in the `archives` folder,
+///   and of the current version of each template, packed when they are requested
+///
+/// With a token, `lth template publish` can upload new versions with `PUT /archives/NAME-VERSION.tar.gz`
+///
+/// # Example
+/// ```
+/// let server = Server::bind(Path::new("templates"), "0.0.0.0:8080")?;
+/// println!("Serving on {}", server.url());
+/// server.run();
+/// ```
+pub struct Server {
+    dir: PathBuf,
+    http: tiny_http::Server,
+    /// Token the uploads must send, the uploads are refused without it
+    token: Option<String>,
+}
+
+/// Implementation of the Server struct to answer the requests of the clients
+impl Server {
+    /// Start listening on an address, without answering the requests yet
+    ///
+    /// # Arguments
+    /// * `dir` - The folder with the templates
+    /// * `address` - The address and port, like `0.0.0.0:8080`, port 0 picks a free port
+    ///
+    /// # Errors
+    /// This function returns an error if the folder doesn't exist or the address can't be used
+    pub fn bind(dir: &Path, address: &str) -> Result<Server, String> {
+        let dir = dir.canonicalize().map_err(|err| format!("Failed to open the folder {}: {}", dir.display(), err))?;
+        let http = tiny_http::Server::http(address).map_err(|err| format!("Failed to listen on {}: {}", address, err))?;
+
+        Ok(Server { dir, http, token: None })
+    }
+
+    /// Accept the uploads of `lth template publish` sent with a token
+    ///
+    /// # Arguments
+    /// * `token` - The token sent by the clients as `Authorization: Bearer TOKEN`
+    ///
+    /// # Example
+    /// ```
+    /// let server = Server::bind(Path::new("templates"), "0.0.0.0:8080")?.with_token("secret");
+    /// ```
+    pub fn with_token(mut self, token: &str) -> Server {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Return the URL of the server, with the address it listens on
+    pub fn url(&self) -> String {
+        format!("http://{}", self.http.server_addr())
+    }
+
+    /// Answer the requests until the program ends
+    pub fn run(&self) {
+        for request in self.http.incoming_requests() {
+            self.answer(request);
+        }
+    }
+
+    /// Answer one request, every error is sent to the client as a status code
+    fn answer(&self, mut request: Request) {
+        let path = request.url().split('?').next().unwrap_or("/").to_string();
+
+        // The index points to the address used by the client, which can be a name on the LAN
+        let base = header(&request, "Host")
+            .map(|host| format!("http://{}", host))
+            .unwrap_or_else(|| self.url());
+
+        let result = match (request.method(), path.trim_start_matches('/')) {
+            (Method::Get | Method::Head, "" | "index.toml") => self.index(&base).to_toml()
+                .map(|index| (200, index.into_bytes(), "application/toml"))
+                .map_err(|err| (500, err)),
+            (Method::Get | Method::Head, "index.json") => serde_json::to_vec_pretty(&self.index(&base))
+                .map(|index| (200, index, "application/json"))
+                .map_err(|err| (500, err.to_string())),
+            (Method::Get | Method::Head, path) => self.file(path)
+                .map(|bytes| (200, bytes, "application/octet-stream"))
+                .map_err(|err| (404, err)),
+            (Method::Put, path) if path.starts_with("archives/") => {
+                let file = path.trim_start_matches("archives/").to_string();
+                self.upload(&file, &mut request).map(|message| (201, message.into_bytes(), "text/plain"))
+            },
+            _ => Err((405, "Method not allowed".to_string())),
+        };
+
+        let response = match result {
+            Ok((code, bytes, content_type)) => {
+                detail!("{} {} {}", request.method(), path, code);
+                let header = Header::from_bytes("Content-Type", content_type).expect("The content types are valid headers");
+                Response::from_data(bytes).with_header(header).with_status_code(code)
+            },
+            Err((code, err)) => {
+                detail!("{} {} {}: {}", request.method(), path, code, err);
+                Response::from_data(err.into_bytes()).with_status_code(code)
+            },
+        };
+
+        if let Err(err) = request.respond(response) {
+            warning!("Failed to answer a request: {}", err);
+        }
+    }
+
+    /// Return the content of a file of a template or of an archive
+    /// The paths can't leave the folder of the templates
+    fn file(&self, path: &str) -> Result<Vec<u8>, String> {
+        if let Some(path) = path.strip_prefix("templates/") {
+            let name = path.split('/').next().unwrap_or(path);
+            if !self.dir.join(name).join(MANIFEST).is_file() {
+                return Err(format!("There is no template {}", name));
+            }
+
+            let file = Sandbox::new(&self.dir).resolve(path)?;
+            return std::fs::read(&file).map_err(|err| format!("Failed to read {}: {}", path, err));
+        }
+
+        if let Some(file) = path.strip_prefix("archives/") {
+            let stored = Sandbox::new(&self.dir).resolve(&format!("{}/{}", ARCHIVES, file))?;
+            if stored.is_file() {
+                return std::fs::read(&stored).map_err(|err| format!("Failed to read {}: {}", path, err));
+            }
+
+            // The current version of a template is packed when it is requested
+            let root = file.strip_suffix(".tar.gz").ok_or("The archives are .tar.gz files")?;
+            let (folder, _) = self.templates().into_iter()
+                .find(|(_, template)| current_archive(template).as_deref() == Some(root))
+                .ok_or_else(|| format!("There is no archive {}", file))?;
+            return pack(&folder, root);
+        }
+
+        Err(format!("There is no file {}", path))
+    }
+
+    /// Store a new version of a template uploaded by `lth template publish`
+    /// The archive is kept in the `archives` folder and its files replace the folder of the template
+    ///
+    /// # Arguments
+    /// * `file` - The name of the archive, like `thesis-1.2.0.tar.gz`
+    /// * `request` - The request, with the token, the checksum and the archive
+    ///
+    /// # Errors
+    /// This function returns the status code and the message to send to the client
+    fn upload(&self, file: &str, request: &mut Request) -> Result<String, (u16, String)> {
+        let authorization = header(request, "Authorization");
+        let expected = header(request, "X-Checksum-Sha256");
+
+        // Only the clients with the token can change the templates
+        let token = self.token.as_deref().ok_or((403, "The server doesn't accept uploads, start it with --token".to_string()))?;
+        if authorization.as_deref() != Some(format!("Bearer {}", token).as_str()) {
+            return Err((401, "The token is not valid".to_string()));
+        }
+
+        let (name, version) = split_archive(file).ok_or_else(|| (400, format!("{} is not named NAME-VERSION.tar.gz", file)))?;
+        if name == ARCHIVES || name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err((400, format!("Invalid template name {}", name)));
+        }
+
+        let stored = self.dir.join(ARCHIVES).join(file);
+        if stored.exists() {
+            return Err((409, format!("The version {} of {} is already published", version, name)));
+        }
+
+        // The archive must be the one the client packed
+        let mut bytes = Vec::new();
+        request.as_reader().take(MAX_UPLOAD + 1).read_to_end(&mut bytes).map_err(|err| (400, format!("Failed to read the archive: {}", err)))?;
+        if bytes.len() as u64 > MAX_UPLOAD {
+            return Err((413, format!("The archive is bigger than {} bytes", MAX_UPLOAD)));
+        }
+        let checksum = sha256(&bytes);
+        if expected.is_some_and(|expected| !expected.eq_ignore_ascii_case(&checksum)) {
+            return Err((400, "The checksum of the archive doesn't match".to_string()));
+        }
+
+        // The manifest inside the archive must describe the same version
+        let partial = self.dir.join(format!(".{}.upload", name));
+        let _ = std::fs::remove_dir_all(&partial);
+        let result = unpack(&bytes, &partial).and_then(|root| {
+            let template = Template::load(&root)?;
+            match (template.name == name, template.version.as_deref() == Some(version)) {
+                (true, true) => self.replace(&root, &Package {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                    file: file.to_string(),
+                    sha256: checksum.clone(),
+                    archive: bytes.clone(),
+                }),
+                _ => Err(format!("The manifest of the archive is not the version {} of {}", version, name)),
+            }
+        });
+        let _ = std::fs::remove_dir_all(&partial);
+        result.map_err(|err| (400, err))?;
+
+        status!("Published the version {} of {}", version, name);
+        Ok(format!("Published the version {} of {}", version, name))
+    }
+
+    /// Replace the folder of a template with the files of a new version, and keep its archive
+    fn replace(&self, files: &Path, package: &Package) -> Result<(), String> {
+        let archives = self.dir.join(ARCHIVES);
+        std::fs::create_dir_all(&archives).map_err(|err| format!("Failed to create the folder {}: {}", archives.display(), err))?;
+        write_archive(&archives, package)?;
+
+        let folder = self.dir.join(&package.name);
+        let old = self.dir.join(format!(".{}.old", package.name));
+        let _ = std::fs::remove_dir_all(&old);
+        if folder.exists() {
+            std::fs::rename(&folder, &old).map_err(|err| format!("Failed to replace {}: {}", folder.display(), err))?;
+        }
+        std::fs::rename(files, &folder).map_err(|err| format!("Failed to write {}: {}", folder.display(), err))?;
+        let _ = std::fs::remove_dir_all(&old);
+        Ok(())
+    }
+
+    /// Return the templates of the folder, with their folders, sorted by folder
+    fn templates(&self) -> Vec<(PathBuf, Template)> {
+        let mut folders: Vec<PathBuf> = std::fs::read_dir(&self.dir)
+            .map(|entries| entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect())
+            .unwrap_or_default();
+        folders.retain(|folder| folder.join(MANIFEST).is_file());
+        folders.sort();
+
+        folders.into_iter()
+            .filter_map(|folder| match Template::load(&folder) {
+                Ok(template) => Some((folder, template)),
+                Err(err) => {
+                    warning!("Skipping the template at {}: {}", folder.display(), err);
+                    None
+                },
+            })
+            .collect()
+    }
+
+    /// Build the index of the templates, with the URLs of the server
+    ///
+    /// # Arguments
+    /// * `base` - The URL of the server, as the client sees it
+    pub fn index(&self, base: &str) -> Index {
+        let archives: Vec<String> = std::fs::read_dir(self.dir.join(ARCHIVES))
+            .map(|entries| entries.filter_map(|entry| entry.ok()?.file_name().into_string().ok()).collect())
+            .unwrap_or_default();
+
+        let templates = self.templates().into_iter().map(|(folder, template)| {
+            let folder = folder.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+
+            // The stored archives are the releases, like thesis-1.0.0.tar.gz
+            let mut releases: Vec<Release> = archives.iter()
+                .filter_map(|file| {
+                    let version = file.strip_suffix(".tar.gz")?.strip_prefix(&template.name)?.strip_prefix('-')?;
+                    Version::parse(version).ok()?;
+                    // The checksum written next to the archive, in the format of sha256sum
+                    let sha256 = std::fs::read_to_string(self.dir.join(ARCHIVES).join(format!("{}.sha256", file))).ok()
+                        .and_then(|content| content.split_whitespace().next().map(String::from));
+
+                    Some(Release {
+                        version: version.to_string(),
+                        tag: None,
+                        archive: Some(format!("{}/{}/{}", base, ARCHIVES, file)),
+                        source: None,
+                        path: None,
+                        sha256,
+                    })
+                })
+                .filter(|release| Some(&release.version) != template.version.as_ref())
+                .collect();
+            releases.sort_by(|a, b| Version::parse(&b.version).ok().cmp(&Version::parse(&a.version).ok()));
+
+            IndexEntry {
+                source: Some(format!("{}/templates/{}", base, folder)),
+                releases,
+                ..IndexEntry::new(&template)
+            }
+        }).collect();
+
+        Index { name: None, templates }
+    }
+}
+
+/// Return the value of a header of a request, without spaces around it
+fn header(request: &Request, name: &'static str) -> Option<String> {
+    request.headers().iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.as_str().trim().to_string())
+}
+
+/// Split the name of an archive like `thesis-1.2.0.tar.gz` into the name and the version of the template
+/// The names can have dashes, so the version starts at the first dash followed by a valid version
+fn split_archive(file: &str) -> Option<(&str, &str)> {
+    let root = file.strip_suffix(".tar.gz")?;
+    root.match_indices('-')
+        .map(|(index, _)| (&root[..index], &root[index + 1..]))
+        .find(|(name, version)| !name.is_empty() && Version::parse(version).is_ok())
+}
+
+/// Return the name of the archive of the current version of a template, without `.tar.gz`
+fn current_archive(template: &Template) -> Option<String> {
+    template.version.as_ref().map(|version| format!("{}-{}", template.name, version))
+}
@@ -0,0 +1,222 @@
+use std::path::PathBuf;
+use serde::Deserialize;
+
+/// Struct to manage the user settings stored in the configuration file
+///
+/// # Example
+/// ```toml
+/// [network]
+/// proxy = "http://proxy.university.edu:3128"
+/// no_proxy = ["localhost", ".university.edu"]
+/// ca_bundles = ["/etc/ssl/certs/university-root.pem"]
+/// timeout = 30
+///
+/// [registry]
+/// indexes = ["https://example.com/templates/index.toml"]
+/// servers = ["http://lab-server:8080"]
+/// publish = "git@github.com:lab/latex-templates.git"
+///
+/// [bibliography]
+/// database = "/home/me/references.bib"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub network: NetworkSettings,
+    pub registry: RegistrySettings,
+    pub bibliography: BibliographySettings,
+}
+
+/// Struct with the settings of `lth bib`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BibliographySettings {
+    /// Local BibTeX database searched for the DOIs and the arXiv ids before the web, see `bib::LocalResolver`
+    pub database: Option<PathBuf>,
+}
+
+/// Struct with the sources of templates searched by `lth search`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RegistrySettings {
+    /// URLs of the indexes published by the sources, see `index::Index`
+    pub indexes: Vec<String>,
+    /// Base URLs of the servers started with `lth serve`
+    pub servers: Vec<String>,
+    /// Git repository or server where `lth template publish` sends the templates, see `publish::Target`
+    pub publish: Option<String>,
+}
+
+/// Implementation of the RegistrySettings struct to find the indexes
+impl RegistrySettings {
+    /// Return the URLs of every index, the servers publish theirs in `/index.toml`
+    ///
+    /// # Example
+    /// ```
+    /// let urls = settings.registry.index_urls();
+    /// ```
+    pub fn index_urls(&self) -> Vec<String> {
+        self.indexes.iter().cloned()
+            .chain(self.servers.iter().map(|server| format!("{}/index.toml", server.trim_end_matches('/'))))
+            .collect()
+    }
+}
+
+/// Struct to manage the settings used by the HTTP client that downloads the templates
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    /// Proxy used for every request, overrides `HTTPS_PROXY`, `HTTP_PROXY` and `ALL_PROXY`
+    pub proxy: Option<String>,
+    /// Username for an authenticated proxy
+    pub proxy_username: Option<String>,
+    /// Password for an authenticated proxy
+    pub proxy_password: Option<String>,
+    /// Hosts that must be reached without the proxy, added to the `NO_PROXY` entries
+    pub no_proxy: Vec<String>,
+    /// Extra PEM files with root certificates to trust
+    pub ca_bundles: Vec<PathBuf>,
+    /// Timeout of a whole request, in seconds
+    pub timeout: Option<u64>,
+    /// Timeout of the connection phase, in seconds
+    pub connect_timeout: Option<u64>,
+    /// User agent sent with every request
+    pub user_agent: Option<String>,
+}
+
+/// Implementation of the Settings struct to load the user settings
+impl Settings {
+    /// Load the settings from the configuration file
+    /// If the file doesn't exist, the default settings are returned
+    ///
+    /// # Example
+    /// ```
+    /// let settings = Settings::load()?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the file can't be read
+    /// or if the file is not a valid TOML configuration
+    pub fn load() -> Result<Settings, String> {
+        let path = match config_file() {
+            Some(path) => path,
+            None => return Ok(Settings::default()),
+        };
+
+        // A missing configuration file is not an error
+        if !path.is_file() {
+            return Ok(Settings::default());
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+
+        toml::from_str(&content).map_err(|err| format!("Invalid configuration in {}: {}", path.display(), err))
+    }
+}
+
+/// Return the directory where the configuration of the program is stored
+///
+/// The directory is `$XDG_CONFIG_HOME/lth` or `$HOME/.config/lth`
+///
+/// # Example
+/// ```
+/// let dir = config_dir();
+/// ```
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir).join("lth"));
+    }
+
+    std::env::var_os("HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(|home| PathBuf::from(home).join(".config").join("lth"))
+}
+
+/// Return the path to the configuration file
+///
+/// The `LTH_CONFIG` environment variable can be used to point to another file
+///
+/// # Example
+/// ```
+/// let path = config_file();
+/// ```
+pub fn config_file() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("LTH_CONFIG").filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// Return the path to the file with the sources trusted to run hooks
+///
+/// The file is `trust.toml`, next to the configuration file
+///
+/// # Example
+/// ```
+/// let path = trust_file();
+/// ```
+pub fn trust_file() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("trust.toml"))
+}
+
+/// Return the directory where the data of the program is stored
+///
+/// The directory is `$XDG_DATA_HOME/lth` or `$HOME/.local/share/lth`
+///
+/// # Example
+/// ```
+/// let dir = data_dir();
+/// ```
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir).join("lth"));
+    }
+
+    std::env::var_os("HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(|home| PathBuf::from(home).join(".local").join("share").join("lth"))
+}
+
+/// Return the directory where the downloaded files of the program are kept
+///
+/// The directory is `$XDG_CACHE_HOME/lth` or `$HOME/.cache/lth`
+///
+/// # Example
+/// ```
+/// let dir = cache_dir();
+/// ```
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_CACHE_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir).join("lth"));
+    }
+
+    std::env::var_os("HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(|home| PathBuf::from(home).join(".cache").join("lth"))
+}
+
+/// Return the directories with the templates of the user
+///
+/// The templates are in `~/.config/lth/templates` and `~/.local/share/lth/templates`,
+/// one folder with a `template.toml` manifest for each template
+pub fn template_dirs() -> Vec<PathBuf> {
+    [config_dir(), data_dir()]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join("templates"))
+        .collect()
+}
+
+/// Return the directories with the citation styles of the user
+///
+/// The styles are in `~/.config/lth/styles` and `~/.local/share/lth/styles`,
+/// one `.csl` file for each style
+pub fn style_dirs() -> Vec<PathBuf> {
+    [config_dir(), data_dir()]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join("styles"))
+        .collect()
+}
@@ -0,0 +1,238 @@
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+
+use crate::sandbox::Sandbox;
+use crate::settings::style_dirs;
+use crate::{trace, STYLES};
+
+// Other names of the bundled styles, the names used by the Zotero style repository
+const ALIASES: &[(&str, &str)] = &[
+    ("apa7", "apa"),
+    ("chicago-author-date", "chicago"),
+];
+
+// Files with the metadata of the pandoc templates, relative to the project
+const METADATA_FILES: &[&str] = &["metadata.yaml", "metadata.yml"];
+
+// Folder of the project where the styles are installed
+const STYLE_FOLDER: &str = "lib";
+
+/// Struct with a CSL citation style, used by pandoc to format the citations and the references
+///
+/// # Example
+/// ```
+/// let style = find_style(&styles()?, "apa")?;
+/// println!("{} ({})", style.title, style.name);
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct Style {
+    /// Name of the style, the name of its file without `.csl`
+    pub name: String,
+    /// Title of the style, from the `<title>` of its `<info>`
+    pub title: String,
+    /// File of a local style, `None` for the bundled styles
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Content of the CSL file
+    #[serde(skip)]
+    pub content: String,
+}
+
+/// Implementation of the Style struct to read the CSL files
+impl Style {
+    /// Create a style from the content of a CSL file
+    ///
+    /// # Arguments
+    /// * `name` - The name of the style
+    /// * `content` - The XML of the CSL file
+    /// * `path` - The file of a local style
+    ///
+    /// # Example
+    /// ```
+    /// let style = Style::new("ieee", include_str!("builtin/ieee.csl"), None);
+    /// ```
+    pub fn new(name: &str, content: &str, path: Option<PathBuf>) -> Style {
+        let title = content.split_once("<title>")
+            .and_then(|(_, rest)| rest.split_once("</title>"))
+            .map(|(title, _)| title.trim().replace("&amp;", "&"))
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| name.to_string());
+
+        Style { name: name.to_string(), title, path, content: content.to_string() }
+    }
+
+    /// Return the path of the style in a project, like `lib/apa.csl`
+    pub fn file(&self) -> String {
+        format!("{}/{}.csl", STYLE_FOLDER, self.name)
+    }
+}
+
+/// Return the styles bundled with the program, see the STYLES const map
+pub fn builtin_styles() -> Vec<Style> {
+    STYLES.iter().map(|(name, content)| Style::new(name, content, None)).collect()
+}
+
+/// Return the bundled styles and the styles of the user,
+/// kept in `~/.config/lth/styles` and `~/.local/share/lth/styles`
+///
+/// # Example
+/// ```
+/// let styles = styles()?;
+/// ```
+///
+/// # Errors
+/// This function returns an error if a style of the user can't be read
+pub fn styles() -> Result<Vec<Style>, String> {
+    styles_in(&style_dirs())
+}
+
+/// Return the bundled styles and the `.csl` files of the given folders
+/// A local style with the name of a bundled one replaces it, the folders that don't exist are skipped
+///
+/// # Arguments
+/// * `dirs` - The folders with the styles of the user, the first ones win
+///
+/// # Example
+/// ```
+/// let styles = styles_in(&[PathBuf::from("/home/me/styles")])?;
+/// ```
+///
+/// # Errors
+/// This function returns an error if a folder or a style can't be read
+pub fn styles_in(dirs: &[PathBuf]) -> Result<Vec<Style>, String> {
+    let mut styles = builtin_styles();
+    let mut local: Vec<Style> = Vec::new();
+
+    for dir in dirs.iter().filter(|dir| dir.is_dir()) {
+        let entries = std::fs::read_dir(dir).map_err(|err| format!("Failed to read the folder {}: {}", dir.display(), err))?;
+
+        let mut files: Vec<PathBuf> = entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|extension| extension == "csl"))
+            .collect();
+        files.sort();
+
+        for path in files {
+            let name = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+            if local.iter().any(|style| style.name == name) {
+                continue;
+            }
+
+            let content = std::fs::read_to_string(&path).map_err(|err| format!("Failed to read the style {}: {}", path.display(), err))?;
+            trace!("style {} from {}", name, path.display());
+            local.push(Style::new(&name, &content, Some(path)));
+        }
+    }
+
+    // The local styles replace the bundled ones with the same name, the rest go at the end
+    for style in local {
+        match styles.iter_mut().find(|builtin| builtin.name == style.name) {
+            Some(builtin) => *builtin = style,
+            None => styles.push(style),
+        }
+    }
+
+    Ok(styles)
+}
+
+/// Find a style by its name or by one of its aliases, without caring about the case
+///
+/// # Arguments
+/// * `styles` - The available styles, see `styles`
+/// * `name` - The name given by the user, like `apa` or `APA7`
+///
+/// # Example
+/// ```
+/// let style = find_style(&styles()?, "chicago-author-date")?;
+/// assert_eq!(style.name, "chicago");
+/// ```
+///
+/// # Errors
+/// This function returns an error with the available styles if no style has this name
+pub fn find_style<'a>(styles: &'a [Style], name: &str) -> Result<&'a Style, String> {
+    let name = name.trim().trim_end_matches(".csl").to_lowercase();
+    let name = ALIASES.iter().find(|(alias, _)| *alias == name).map(|(_, style)| style.to_string()).unwrap_or(name);
+
+    styles.iter().find(|style| style.name.to_lowercase() == name).ok_or_else(|| {
+        let available: Vec<&str> = styles.iter().map(|style| style.name.as_str()).collect();
+        format!("There is no style called {} (available: {})", name, available.join(", "))
+    })
+}
+
+/// Return the metadata of a pandoc project with its `csl` field set to the given file
+/// The existing top-level `csl` field is replaced, otherwise the field is added
+/// at the end of the YAML block, before its closing `---` or `...`
+///
+/// # Arguments
+/// * `metadata` - The content of `metadata.yaml`
+/// * `file` - The CSL file, relative to the project
+///
+/// # Example
+/// ```
+/// assert_eq!(set_csl("title: Paper\ncsl: lib/bibliography.csl\n", "lib/apa.csl"), "title: Paper\ncsl: lib/apa.csl\n");
+/// ```
+pub fn set_csl(metadata: &str, file: &str) -> String {
+    let field = format!("csl: {}", file);
+    let mut lines: Vec<String> = metadata.lines().map(String::from).collect();
+
+    // Only the top-level field counts, an indented csl belongs to another field
+    if let Some(line) = lines.iter_mut().find(|line| line.starts_with("csl:")) {
+        *line = field;
+        return lines.join("\n") + "\n";
+    }
+
+    // A YAML block opened with --- ends at the next --- or ...
+    let start = lines.iter().position(|line| !line.trim().is_empty());
+    let end = start
+        .filter(|start| lines[*start].trim_end() == "---")
+        .and_then(|start| lines.iter().skip(start + 1).position(|line| matches!(line.trim_end(), "---" | "...")).map(|end| start + 1 + end));
+
+    match end {
+        Some(end) => lines.insert(end, field),
+        None => {
+            while lines.last().is_some_and(|line| line.trim().is_empty()) {
+                lines.pop();
+            }
+            lines.push(field);
+        },
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Install a style in a pandoc project: its CSL file is written to `lib`
+/// and the `csl` field of `metadata.yaml` points to it
+/// The previous CSL file is kept, the project may use it somewhere else
+///
+/// # Arguments
+/// * `project` - The folder of the project
+/// * `style` - The style to install
+///
+/// # Returns
+/// The files written, relative to the project: the CSL file, then the metadata
+///
+/// # Example
+/// ```
+/// let files = set_style(Path::new("paper"), find_style(&styles()?, "apa")?)?;
+/// assert_eq!(files, ["lib/apa.csl", "metadata.yaml"]);
+/// ```
+///
+/// # Errors
+/// This function returns an error if the project has no `metadata.yaml`
+/// or if the files can't be read or written
+pub fn set_style(project: &Path, style: &Style) -> Result<Vec<String>, String> {
+    let metadata = METADATA_FILES.iter().find(|file| project.join(file).is_file()).ok_or_else(|| {
+        format!("There is no metadata.yaml in {}, the citation styles are used by the pandoc templates", project.display())
+    })?;
+
+    let path = project.join(metadata);
+    let content = std::fs::read_to_string(&path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+
+    // The project can't be trusted more than a template, the files stay inside of it
+    let sandbox = Sandbox::new(project);
+    let file = style.file();
+    sandbox.create_file(&file, style.content.as_bytes())?;
+    sandbox.create_file(metadata, set_csl(&content, &file).as_bytes())?;
+
+    Ok(vec![file, metadata.to_string()])
+}
@@ -1,405 +1,813 @@
-use crate::git::*;
-use crate::utils::*;
-
-// Use a const array to store the git answers
-const GIT_ANSWERS: [&str; 6] = [
-    "Y",
-    "y",
-    "Yes",
-    "YES",
-    "yes",
-    "",
-];
-
-
-/// Create a new latex report, template focused on math
-/// Based on @gillescastel & @SirCharlieMars templates
-///
-/// # Arguments
-/// * `path` - A string slice that holds the path to the new folder
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::assets::Asset;
+use crate::hooks::Hook;
+use crate::prompt::{self, parse_confirm, parse_option, parse_options};
+use crate::versions::Release;
+use crate::error;
+
+// Answers of the user to the variables of a template, by variable name
+pub type Answers = BTreeMap<String, String>;
+
+// File name of the manifest of the local templates
+pub const MANIFEST: &str = "template.toml";
+
+/// Struct to describe a template, loaded from its TOML manifest
+/// A template can extend another template and add layers, see `TemplateRegistry::resolve`
 ///
 /// # Example
+/// ```toml
+/// name = "math"
+/// description = "Latex report, template focused on math"
+/// extends = "article"         # Inherit the files, variables and requirements of a template
+/// layers = ["git-setup"]      # Add the files of reusable layers
+/// version = "1.0.0"           # Semantic version, older versions are listed in [[releases]]
+/// authors = ["@Johanx22x"]
+/// credits = ["@gillescastel", "@SirCharlieMars"]
+/// license = "MIT"
+/// tags = ["report", "math"]
+/// languages = ["en"]          # ISO 639-1 codes of the languages of the documents
+/// document = "report"         # Type of document, like paper, report, thesis or slides
+/// source = "https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math"
+/// backend = "latex"
+/// folders = ["lib", "images", "src"]
+/// preview = ["main.tex"]
+///
+/// [requirements]
+/// executables = ["pdflatex", "make"]
+/// packages = ["amsmath.sty", "tikz.sty"]
+///
+/// [[files]]
+/// path = "main.tex"
+///
+/// [[files]]
+/// path = "README.md"
+/// when = "readme"
+///
+/// [[variables]]
+/// name = "readme"
+/// prompt = "Do you want to create a README.md file?"
+/// kind = "confirm"
+/// default = "yes"
 /// ```
-/// let path = "path/to/a/directory";
-/// math_template(path);
+#[derive(Debug, Clone, Deserialize)]
+pub struct Template {
+    pub name: String,
+    pub description: String,
+    pub version: Option<String>,
+    /// Git repository with the tags of the releases
+    pub repository: Option<String>,
+    /// Older versions of the template, see `versions::Release`
+    #[serde(default)]
+    pub releases: Vec<Release>,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    /// People and projects the template is based on
+    #[serde(default)]
+    pub credits: Vec<String>,
+    pub license: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Languages of the documents, as ISO 639-1 codes like `en` or `es`
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Type of document, like paper, report, thesis or slides
+    pub document: Option<String>,
+    /// URL of the files of the template, the folder of the manifest for the local templates
+    #[serde(default)]
+    pub source: String,
+    /// Where the manifest was read or downloaded from, the source trusted to run the hooks, see `Template::origin`
+    /// A manifest can't choose it, its `source` may point anywhere
+    #[serde(skip)]
+    pub origin: Option<String>,
+    /// Tool used to build the documents of the template, inherited when it is not set, see `Template::backend`
+    pub backend: Option<Backend>,
+    /// Name of the template this one is based on
+    pub extends: Option<String>,
+    /// Names of the layers added to the template, built-in layers or other templates
+    #[serde(default)]
+    pub layers: Vec<String>,
+    #[serde(default)]
+    pub folders: Vec<String>,
+    /// Key files shown in the preview of the template
+    #[serde(default)]
+    pub preview: Vec<String>,
+    /// Executables, TeX packages and fonts needed to build the documents
+    #[serde(default)]
+    pub requirements: Requirements,
+    #[serde(default)]
+    pub files: Vec<TemplateFile>,
+    #[serde(default)]
+    pub variables: Vec<Variable>,
+    /// Optional parts of the template, asked like confirm variables
+    #[serde(default)]
+    pub features: Vec<Feature>,
+    /// Commands run in the new project after it is created, see `hooks::Hook`
+    #[serde(default)]
+    pub hooks: Vec<Hook>,
+}
+
+/// Tool used to build the documents of a template
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// LaTeX sources built with pdflatex
+    #[default]
+    Latex,
+    /// Markdown sources converted with pandoc
+    Pandoc,
+}
+
+/// Implementation of the Backend enum to show the backends
+impl Backend {
+    /// Return the name of the backend, as it is written in the manifest
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Latex => "latex",
+            Backend::Pandoc => "pandoc",
+        }
+    }
+}
+
+/// Struct with the requirements declared by a template
+/// Each requirement is a name with an optional minimum version, like `pandoc >= 2.11`,
+/// or a table to mark it as optional
+///
+/// # Example
+/// ```toml
+/// [requirements]
+/// executables = ["pandoc >= 2.11", "make"]
+/// packages = ["apa7.cls"]           # Package versions are dates, like "apa7.cls >= 2020/01/01"
+/// fonts = [{ name = "TeX Gyre Termes", optional = true }]
 /// ```
-pub fn math(path: &str) {
-    // Create the new folder
-    let new_path = create_dir_using_stdin(path);
-
-    // Create main.tex file
-    // Download the main.tex file from the repo 
-    // https://raw.githubusercontent.com/TeXample/templates/master/math/main.tex
-    let main = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math/main.tex");
-    create_file(&new_path, "main.tex", main.as_str());
-
-    // Create lib folder 
-    create_folder(&new_path, "lib");
-
-    // Create .text files inside lib folder 
-
-    // Download the preamble.tex file 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math/preamble.tex
-    let preamble = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math/preamble.tex");
-    create_file(&new_path, "lib/preamble.tex", preamble.as_str());
-
-    // Download the macros.tex file 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math/macros.tex 
-    let macros = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math/macros.tex");
-    create_file(&new_path, "lib/macros.tex", macros.as_str());
-
-    // Download the letterfonts.tex file 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math/letterfonts.tex 
-    let letterfonts = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math/letterfonts.tex");
-    create_file(&new_path, "lib/letterfonts.tex", letterfonts.as_str());
-
-    // Create the images folder
-    create_folder(&new_path, "images");
-    // Get an image about math from the internet and save it to the images folder 
-    // download_file("https://upload.wikimedia.org/wikipedia/commons/thumb/2/2a/Logarithmic_spiral.svg/1200px-Logarithmic_spiral.svg.png", &format!("{}/images", new_path));
-
-    // Create the src folder
-    create_folder(&new_path, "src");
-    create_file(&format!("{}/src", new_path), "chap01.tex", "");
-
-    // Ask the user if he wants to initialize a git repository
-    let git = read_stdin("Do you want to initialize a git repository? (Y/n)".to_string()).trim().to_string();
-
-    // Ask the user if he wants to create a README.md file 
-    let readme = read_stdin("Do you want to create a README.md file? (Y/n)".to_string()).trim().to_string();
-
-    // Check if the user wants to create a README.md file 
-    if GIT_ANSWERS.contains(&readme.as_str()) {
-        // Create a README.md file 
-        // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math/README.md 
-        let readme = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math/README.md");
-        create_file(&new_path, "README.md", readme.as_str());
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Requirements {
+    pub executables: Vec<Requirement>,
+    /// TeX packages and classes, by file name
+    pub packages: Vec<Requirement>,
+    /// Font families, or font files like `lmroman10-regular.otf`
+    pub fonts: Vec<Requirement>,
+}
+
+/// Struct to describe a requirement of a template
+/// A missing optional requirement is only a warning
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "RequirementSpec")]
+pub struct Requirement {
+    pub name: String,
+    /// Minimum version
+    pub version: Option<String>,
+    pub optional: bool,
+}
+
+/// Forms of a requirement in the manifest
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RequirementSpec {
+    Short(String),
+    Full {
+        name: String,
+        version: Option<String>,
+        #[serde(default)]
+        optional: bool,
+    },
+}
+
+/// Build the requirements from their forms in the manifest
+impl From<RequirementSpec> for Requirement {
+    fn from(spec: RequirementSpec) -> Requirement {
+        match spec {
+            RequirementSpec::Short(text) => match text.split_once(">=") {
+                Some((name, version)) => Requirement {
+                    name: name.trim().to_string(),
+                    version: Some(version.trim().to_string()),
+                    optional: false,
+                },
+                None => Requirement { name: text.trim().to_string(), version: None, optional: false },
+            },
+            RequirementSpec::Full { name, version, optional } => Requirement { name, version, optional },
+        }
     }
+}
 
-    // Check if the user wants to initialize a git repository
-    if GIT_ANSWERS.contains(&git.as_str()) {
-        // Create a .gitignore file 
-        // Download the .gitignore file from the repo
-        // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math/.gitignore 
-        let gitignore = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math/.gitignore");
-        create_file(&new_path, ".gitignore", gitignore.as_str());
+/// Implementation of the Requirement struct to create and show the requirements
+impl Requirement {
+    /// Create a required requirement without a minimum version
+    ///
+    /// # Arguments
+    /// * `name` - The name of the executable, package or font
+    pub fn new(name: &str) -> Requirement {
+        Requirement { name: name.to_string(), version: None, optional: false }
+    }
+}
 
-        // Initialize a new git repository
-        init_git(&new_path);
+/// Show the requirements as they are written in the manifest
+impl std::fmt::Display for Requirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{} >= {}", self.name, version),
+            None => write!(f, "{}", self.name),
+        }
     }
+}
 
-    println!("\x1b[34mCreated the new folder at {}\x1b[0m", new_path);
-
-    // Use tree -C to print the new folder structure with colors
-    let output = std::process::Command::new("tree")
-        .arg("-C")
-        .arg(&new_path)
-        .output()
-        .expect("Failed to execute command");
-    
-    // Print the new folder structure
-    println!("{}", String::from_utf8_lossy(&output.stdout));
+/// Struct to describe a file of a template
+/// `when` is the name of the confirm variable that enables the file
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemplateFile {
+    #[serde(flatten)]
+    pub asset: Asset,
+    pub when: Option<String>,
+    /// Source of the template that declared the file, for the inherited files
+    #[serde(skip)]
+    pub base: Option<String>,
 }
 
-/// Create a new latex report, IEEE template 
-///
-/// # Arguments
-/// * `path` - A string slice that holds the path to the new folder
+/// Kind of value of a variable
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VariableKind {
+    #[default]
+    Text,
+    Confirm,
+    Select,
+    MultiSelect,
+}
+
+/// Implementation of the VariableKind enum to show the kinds
+impl VariableKind {
+    /// Return the name of the kind, as it is written in the manifest
+    pub fn name(&self) -> &'static str {
+        match self {
+            VariableKind::Text => "text",
+            VariableKind::Confirm => "confirm",
+            VariableKind::Select => "select",
+            VariableKind::MultiSelect => "multi-select",
+        }
+    }
+}
+
+/// Struct to describe a value asked to the user before creating the project
+/// The confirm variable called `git` initializes a git repository
 ///
 /// # Example
+/// ```toml
+/// [[variables]]
+/// name = "style"
+/// prompt = "Citation style"
+/// kind = "select"            # text, confirm, select or multi-select
+/// options = ["ieee", "apa"]
+/// default = "ieee"           # Multi-select defaults are separated by commas
 /// ```
-/// let path = "path/to/a/directory";
-/// ieee_template(path);
-/// ```
-pub fn ieee(path: &str) {
-    // Create the new folder 
-    let new_path = create_dir_using_stdin(path);
-
-    // Create the metadata.yaml file
-    // Download the metadata.yaml file from the repo 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/metadata.yaml
-    let main = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/metadata.yaml");
-    create_file(&new_path, "metadata.yaml", main.as_str());
-
-    // Create lib folder 
-    create_folder(&new_path, "lib");
-
-    // Download the necessary files from the repo 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/lib/bibliography.csl
-    let bibliography = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/lib/bibliography.csl");
-    create_file(&new_path, "lib/bibliography.csl", bibliography.as_str());
-
-    // Download the necessary files from the repo 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/lib/bibliography.bib
-    let bibliography = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/lib/bibliography.bib");
-    create_file(&new_path, "lib/bibliography.bib", bibliography.as_str());
-
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/lib/template.tex 
-    let template = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/lib/template.tex");
-    create_file(&new_path, "lib/template.tex", template.as_str());
-
-    // Create the images folder
-    create_folder(&new_path, "images");
-    // Get an image from the repo and save it to the images folder 
-    get_image("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/images/markdown.png", &format!("{}/images", new_path), "markdown.png");
-
-    // Create the src folder
-    create_folder(&new_path, "src");
-
-    // Download the 01.md file 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/src/01.md
-    let first = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/src/01.md");
-    create_file(&new_path, "src/01.md", first.as_str());
-
-    // Download the Makefile
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/Makefile
-    let makefile = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/Makefile");
-    create_file(&new_path, "Makefile", makefile.as_str());
-
-    // Make a new directory called build 
-    create_folder(&new_path, "build");
-
-    // Ask the user if he wants to initialize a git repository
-    let git = read_stdin("Do you want to initialize a git repository? (Y/n)".to_string()).trim().to_string();
-
-    // Ask the user if he wants to create a README.md file 
-    let readme = read_stdin("Do you want to create a README.md file? (Y/n)".to_string()).trim().to_string();
-
-    // Check if the user wants to create a README.md file 
-    if GIT_ANSWERS.contains(&readme.as_str()) {
-        // Create a README.md file 
-        // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/README.md 
-        let readme = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/README.md");
-        create_file(&new_path, "README.md", readme.as_str());
-    }
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Variable {
+    pub name: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub kind: VariableKind,
+    pub default: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// Options of the select and multi-select variables
+    #[serde(default)]
+    pub options: Vec<String>,
+}
 
-    // Check if the user wants to initialize a git repository
-    if GIT_ANSWERS.contains(&git.as_str()) {
-        // Create a .gitignore file 
-        // Download the .gitignore file from the repo
-        // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/.gitignore
-        let gitignore = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieee/.gitignore");
-        create_file(&new_path, ".gitignore", gitignore.as_str());
+/// Implementation of the Variable struct to validate the answers of the user
+impl Variable {
+    /// Validate the input of the user and return the value of the variable
+    /// An empty input takes the default value, confirm values are stored as `yes` or `no`,
+    /// select values as the chosen option and multi-select values as the options separated by commas
+    ///
+    /// # Arguments
+    /// * `input` - The text typed by the user
+    ///
+    /// # Example
+    /// ```
+    /// let value = variable.resolve("Y")?; // "yes"
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error with the message to show to the user
+    /// if the input is not valid
+    pub fn resolve(&self, input: &str) -> Result<String, String> {
+        let input = input.trim();
+        let value = if input.is_empty() {
+            self.default.clone().unwrap_or_default()
+        } else {
+            input.to_string()
+        };
+
+        match self.kind {
+            VariableKind::Text => {
+                if self.required && value.is_empty() {
+                    return Err(format!("A value for {} is required", self.name));
+                }
+                Ok(value)
+            },
+            VariableKind::Confirm => match parse_confirm(&value)? {
+                true => Ok("yes".to_string()),
+                false => Ok("no".to_string()),
+            },
+            VariableKind::Select => {
+                let index = parse_option(&value, &self.options)?;
+                Ok(self.options[index].clone())
+            },
+            VariableKind::MultiSelect => {
+                let indexes = parse_options(&value, &self.options)?;
+                if self.required && indexes.is_empty() {
+                    return Err(format!("Choose at least one option for {}", self.name));
+                }
+                Ok(indexes.iter().map(|index| self.options[*index].as_str()).collect::<Vec<_>>().join(","))
+            },
+        }
+    }
 
-        // Initialize a new git repository
-        init_git(&new_path);
+    /// Ask the variable using stdin until the answer is valid
+    /// The options of select and multi-select variables are listed before the question
+    ///
+    /// # Example
+    /// ```
+    /// let value = variable.ask();
+    /// ```
+    ///
+    /// # Output
+    /// ```text
+    /// Do you want to create a README.md file? [Y/n]:
+    /// ```
+    pub fn ask(&self) -> String {
+        let default = self.default.as_deref();
+
+        match self.kind {
+            VariableKind::Text => prompt::text(&self.prompt, default, |value| {
+                if self.required && value.is_empty() {
+                    return Err(format!("A value for {} is required", self.name));
+                }
+                Ok(())
+            }),
+            VariableKind::Confirm => {
+                let default = default.is_none_or(|default| parse_confirm(default).unwrap_or(true));
+                match prompt::confirm(&self.prompt, default) {
+                    true => "yes".to_string(),
+                    false => "no".to_string(),
+                }
+            },
+            VariableKind::Select => {
+                let default = default.and_then(|default| parse_option(default, &self.options).ok());
+                self.options[prompt::select(&self.prompt, &self.options, default)].clone()
+            },
+            VariableKind::MultiSelect => loop {
+                let defaults = default.and_then(|default| parse_options(default, &self.options).ok()).unwrap_or_default();
+                let indexes = prompt::multi_select(&self.prompt, &self.options, &defaults);
+
+                if self.required && indexes.is_empty() {
+                    error!("Choose at least one option for {}", self.name);
+                    continue;
+                }
+
+                break indexes.iter().map(|index| self.options[*index].as_str()).collect::<Vec<_>>().join(",");
+            },
+        }
     }
 
-    println!("\x1b[34mCreated the new folder at {}\x1b[0m", new_path);
-
-    // Use tree -C to print the new folder structure with colors
-    let output = std::process::Command::new("tree")
-        .arg("-C")
-        .arg(&new_path)
-        .output()
-        .expect("Failed to execute command");
-    
-    // Print the new folder structure
-    println!("{}", String::from_utf8_lossy(&output.stdout));
+    /// Return the message used to ask the variable, with its default value in brackets
+    ///
+    /// # Example
+    /// ```
+    /// let message = variable.message(); // "Do you want to create a README.md file? [Y/n]"
+    /// ```
+    pub fn message(&self) -> String {
+        match (self.kind, self.default.as_deref()) {
+            (VariableKind::Confirm, Some(default)) if parse_confirm(default) == Ok(false) => format!("{} [y/N]", self.prompt),
+            (VariableKind::Confirm, _) => format!("{} [Y/n]", self.prompt),
+            (VariableKind::MultiSelect, None) => format!("{}, separated by commas [none]", self.prompt),
+            (VariableKind::MultiSelect, Some(default)) => format!("{}, separated by commas [{}]", self.prompt, default),
+            (_, Some(default)) if !default.is_empty() => format!("{} [{}]", self.prompt, default),
+            (_, _) => self.prompt.clone(),
+        }
+    }
 }
 
-/// Create a new latex project, APA template 
-///
-/// # Arguments
-/// * `path` - A string slice that holds the path to the new folder 
+/// Struct to describe an optional part of a template, like a bibliography or code listings
+/// Features are asked like confirm variables, or given with `--with` and `--without`
+/// Files that belong to the feature use `when = "<feature>"`, and its patches add lines
+/// to the other files, like the preamble or the Makefile
 ///
 /// # Example
+/// ```toml
+/// [[features]]
+/// name = "bibliography"
+/// description = "a bibliography with biblatex"
+/// default = false
+/// folders = ["lib"]
+///
+/// [[features.patches]]
+/// file = "lib/preamble.tex"
+/// text = "\\usepackage{biblatex}"   # Appended to the end of the file
+///
+/// [[features.patches]]
+/// file = "main.tex"
+/// text = "\\printbibliography"
+/// before = "\\end{document}"        # Or after, the first line with the text
 /// ```
-/// let path = "path/to/a/directory";
-/// apa_template(path);
-/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Feature {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub default: bool,
+    /// Folders created only with the feature
+    #[serde(default)]
+    pub folders: Vec<String>,
+    #[serde(default)]
+    pub patches: Vec<Patch>,
+}
+
+/// Struct to describe lines added to a file of the template by a feature
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Patch {
+    /// Path of the file inside the new project
+    pub file: String,
+    pub text: String,
+    /// Insert the text before the first line that contains this text
+    pub before: Option<String>,
+    /// Insert the text after the first line that contains this text
+    pub after: Option<String>,
+}
+
+/// Implementation of the Feature struct to ask the features like variables
+impl Feature {
+    /// Return the confirm variable used to ask the feature
+    ///
+    /// # Example
+    /// ```
+    /// let message = feature.variable().message(); // "Include a bibliography with biblatex? [y/N]"
+    /// ```
+    pub fn variable(&self) -> Variable {
+        Variable {
+            name: self.name.clone(),
+            prompt: format!("Include {}?", self.description),
+            kind: VariableKind::Confirm,
+            default: Some(if self.default { "yes" } else { "no" }.to_string()),
+            required: false,
+            options: Vec::new(),
+        }
+    }
+}
+
+/// Implementation of the Patch struct to add the lines to the files
+impl Patch {
+    /// Add the text to the content of a file
+    /// The text is appended if the line given with `before` or `after` is not found
+    ///
+    /// # Arguments
+    /// * `content` - The content of the file, with LF line endings
+    ///
+    /// # Example
+    /// ```
+    /// let content = patch.apply("\\begin{document}\n\\end{document}\n");
+    /// ```
+    pub fn apply(&self, content: &str) -> String {
+        let text = match self.text.ends_with('\n') {
+            true => self.text.clone(),
+            false => format!("{}\n", self.text),
+        };
+
+        let marker = self.before.as_deref().map(|marker| (marker, true))
+            .or_else(|| self.after.as_deref().map(|marker| (marker, false)));
+
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            if let Some((_, before)) = marker.filter(|(marker, _)| line.contains(marker)) {
+                let position = if before { offset } else { offset + line.len() };
+                // A last line without LF needs one before the inserted text
+                let separator = if !before && !line.ends_with('\n') { "\n" } else { "" };
+                return format!("{}{}{}{}", &content[..position], separator, text, &content[position..]);
+            }
+            offset += line.len();
+        }
+
+        match content.is_empty() || content.ends_with('\n') {
+            true => format!("{}{}", content, text),
+            false => format!("{}\n{}", content, text),
+        }
+    }
+}
+
+/// Check if a confirm variable was answered with yes
 ///
-/// # Panics
-/// This function panics if the path is not valid
-pub fn apa7tec(path: &str) {
-    // Create the new folder 
-    let new_path = create_dir_using_stdin(path);
-
-    // Create the metadata.yaml file
-    // Download the metadata.yaml file from the repo 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/metadata.yaml
-    let main = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/metadata.yaml");
-    create_file(&new_path, "metadata.yaml", main.as_str());
-
-    // Create lib folder 
-    create_folder(&new_path, "lib");
-
-    // Download the necessary files from the repo 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/lib/bibliography.bib
-    let bibliography = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/lib/bibliography.bib");
-    create_file(&new_path, "lib/bibliography.bib", bibliography.as_str());
-
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/lib/template.tex 
-    let template = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/lib/apa7tec.cls");
-    create_file(&new_path, "lib/apa7tec.cls", template.as_str());
-
-    // Create the src folder
-    create_folder(&new_path, "src");
-
-    // Download the 01.md file 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/src/01.md 
-    let first = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/src/01.md");
-    create_file(&new_path, "src/01.md", first.as_str());
-
-    // Download the Makefile 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/Makefile 
-    let makefile = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/Makefile"); 
-    create_file(&new_path, "Makefile", makefile.as_str()); 
-
-    // Create images folder 
-    create_folder(&new_path, "images");
-
-    // Download the logo 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/images/logo.png
-    get_image("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/images/logo.png", &format!("{}/images", new_path), "logo.png");
-
-    // Make a new directory called build 
-    create_folder(&new_path, "build"); 
-
-    // Ask the user if he wants to initialize a git repository
-    let git = read_stdin("Do you want to initialize a git repository? (Y/n)".to_string()).trim().to_string(); 
-
-    // Ask the user if he wants to create a README.md file 
-    let readme = read_stdin("Do you want to create a README.md file? (Y/n)".to_string()).trim().to_string(); 
-
-    // Check if the user wants to create a README.md file 
-    if GIT_ANSWERS.contains(&readme.as_str()) {
-        // Create a README.md file 
-        // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/README.md 
-        let readme = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/README.md"); 
-        create_file(&new_path, "README.md", readme.as_str()); 
+/// # Arguments
+/// * `answers` - The answers of the user
+/// * `name` - The name of the variable
+pub fn is_enabled(answers: &Answers, name: &str) -> bool {
+    answers.get(name).map(|value| value == "yes").unwrap_or(false)
+}
+
+/// Implementation of the Template struct to load and create the templates
+impl Template {
+    /// Parse a template from its TOML manifest
+    ///
+    /// # Arguments
+    /// * `manifest` - The content of the manifest
+    ///
+    /// # Example
+    /// ```
+    /// let template = Template::parse(include_str!("builtin/math.toml"))?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the manifest is not valid
+    pub fn parse(manifest: &str) -> Result<Template, String> {
+        toml::from_str(manifest).map_err(|err| format!("Invalid template manifest: {}", err))
+    }
+
+    /// Load a local template from its folder
+    /// The files of the template are next to its `template.toml` manifest,
+    /// unless the manifest has a source
+    ///
+    /// # Arguments
+    /// * `dir` - The folder of the template
+    ///
+    /// # Example
+    /// ```
+    /// let template = Template::load(Path::new("~/.config/lth/templates/thesis"))?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the manifest can't be read or is not valid
+    pub fn load(dir: &Path) -> Result<Template, String> {
+        let path = dir.join(MANIFEST);
+        let manifest = std::fs::read_to_string(&path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        let mut template = Template::parse(&manifest).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+        let dir = dir.canonicalize().map_err(|err| format!("Failed to read {}: {}", dir.display(), err))?;
+        template.origin = Some(format!("file://{}", dir.display()));
+        if template.source.is_empty() {
+            template.source = format!("file://{}", dir.display());
+        }
+
+        Ok(template)
+    }
+
+    /// Return the source of a file of the template
+    /// Inherited files come from the source of the template that declared them
+    ///
+    /// # Example
+    /// ```
+    /// let url = file.asset.url(template.base_of(file));
+    /// ```
+    pub fn base_of<'a>(&'a self, file: &'a TemplateFile) -> &'a str {
+        file.base.as_deref().unwrap_or(&self.source)
+    }
+
+    /// Merge the template over its parent
+    /// The files, variables and requirements of the template replace the ones of the parent
+    /// with the same path or name, in the position of the parent; the new ones are added after them
+    ///
+    /// # Arguments
+    /// * `parent` - The resolved template extended by this one, or one of its layers
+    ///
+    /// # Example
+    /// ```
+    /// let template = child.inherit(&builtin("ieee").unwrap());
+    /// ```
+    pub fn inherit(mut self, parent: &Template) -> Template {
+        // The inherited files keep the source of the parent
+        let files = parent.files.iter().map(|file| TemplateFile { base: Some(parent.base_of(file).to_string()), ..file.clone() }).collect();
+        self.files = merge(files, self.files, |file| file.asset.path.clone());
+        self.variables = merge(parent.variables.clone(), self.variables, |variable| variable.name.clone());
+        self.features = merge(parent.features.clone(), self.features, |feature| feature.name.clone());
+        self.hooks = parent.hooks.iter()
+            .map(|hook| Hook { base: Some(parent.hook_source(hook).to_string()), ..hook.clone() })
+            .chain(self.hooks)
+            .collect();
+        self.folders = merge(parent.folders.clone(), self.folders, String::clone);
+        self.tags = merge(parent.tags.clone(), self.tags, String::clone);
+        self.languages = merge(parent.languages.clone(), self.languages, String::clone);
+        self.document = self.document.or_else(|| parent.document.clone());
+
+        let requirements = &mut self.requirements;
+        requirements.executables = merge(parent.requirements.executables.clone(), std::mem::take(&mut requirements.executables), |requirement| requirement.name.clone());
+        requirements.packages = merge(parent.requirements.packages.clone(), std::mem::take(&mut requirements.packages), |requirement| requirement.name.clone());
+        requirements.fonts = merge(parent.requirements.fonts.clone(), std::mem::take(&mut requirements.fonts), |requirement| requirement.name.clone());
+
+        if self.preview.is_empty() {
+            self.preview = parent.preview.clone();
+        }
+
+        // A backend set by the template replaces the one of the parent, even LaTeX
+        self.backend = self.backend.or(parent.backend);
+
+        self
+    }
+
+    /// Return the tool used to build the documents, LaTeX when neither the template nor its parents set it
+    pub fn backend(&self) -> Backend {
+        self.backend.unwrap_or_default()
+    }
+
+    /// Return where the template was read or downloaded from, its source when it is not known
+    pub fn origin(&self) -> &str {
+        self.origin.as_deref().unwrap_or(&self.source)
+    }
+
+    /// Return the origin of the template that declares a hook, to check if the user trusts it
+    pub fn hook_source<'a>(&'a self, hook: &'a Hook) -> &'a str {
+        hook.base.as_deref().unwrap_or(self.origin())
+    }
+
+    /// Return the folder of a local template, if its files are read from the disk
+    pub fn local_root(&self) -> Option<PathBuf> {
+        self.source.strip_prefix("file://").map(PathBuf::from)
+    }
+
+    /// Return the questions of the template: its variables, then its features as confirm variables
+    pub fn questions(&self) -> Vec<Variable> {
+        self.variables.iter().cloned()
+            .chain(self.features.iter().map(Feature::variable))
+            .collect()
+    }
+
+    /// Find a feature of the template by its name
+    pub fn feature(&self, name: &str) -> Option<&Feature> {
+        self.features.iter().find(|feature| feature.name == name)
+    }
+
+    /// Ask the variables and the features of the template using stdin
+    /// Invalid answers are rejected and asked again
+    ///
+    /// # Arguments
+    /// * `given` - The answers that are already known, like the features given with `--with`
+    ///
+    /// # Example
+    /// ```
+    /// let answers = template.ask(&Answers::new());
+    /// ```
+    pub fn ask(&self, given: &Answers) -> Answers {
+        let mut answers: Answers = self.questions().iter()
+            .filter(|variable| !given.contains_key(&variable.name))
+            .map(|variable| (variable.name.clone(), variable.ask()))
+            .collect();
+
+        answers.extend(given.clone());
+        answers
+    }
+
+    /// Answer the variables and the features of the template with their default values
+    /// Used when the variables can't be asked, like in a non interactive dry run
+    ///
+    /// # Example
+    /// ```
+    /// let answers = template.defaults();
+    /// ```
+    pub fn defaults(&self) -> Answers {
+        self.questions().iter()
+            .map(|variable| (variable.name.clone(), variable.resolve("").unwrap_or_default()))
+            .collect()
     }
 
-    // Check if the user wants to initialize a git repository 
-    if GIT_ANSWERS.contains(&git.as_str()) {
-        // Create a .gitignore file 
-        // Download the .gitignore file from the repo 
-        // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/.gitignore 
-        let gitignore = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/apa7tec/.gitignore"); 
-        create_file(&new_path, ".gitignore", gitignore.as_str()); 
+    /// Draw the directory layout of the template, like the `tree` command does
+    /// Files that depend on a variable are followed by the name of the variable
+    ///
+    /// # Example
+    /// ```
+    /// for line in template.layout() {
+    ///     println!("{}", line);
+    /// }
+    /// ```
+    ///
+    /// # Output
+    /// ```text
+    /// ├── lib
+    /// │   └── preamble.tex
+    /// ├── main.tex
+    /// └── README.md (readme)
+    /// ```
+    pub fn layout(&self) -> Vec<String> {
+        let mut root = Node::default();
+
+        for folder in &self.folders {
+            root.insert(folder, None);
+        }
+
+        for file in &self.files {
+            root.insert(&file.asset.path, file.when.as_deref());
+        }
+
+        let mut lines = Vec::new();
+        root.draw("", &mut lines);
+        lines
+    }
+}
+
+/// Node of the directory layout of a template
+#[derive(Default)]
+struct Node {
+    children: BTreeMap<String, Node>,
+    when: Option<String>,
+}
 
-        // Initialize a new git repository 
-        init_git(&new_path); 
+/// Implementation of the Node struct to build and draw the layout of a template
+impl Node {
+    /// Insert a path in the layout, creating its parent folders
+    fn insert(&mut self, path: &str, when: Option<&str>) {
+        let mut node = self;
+        for component in path.split('/').filter(|component| !component.is_empty()) {
+            node = node.children.entry(component.to_string()).or_default();
+        }
+        node.when = when.map(String::from);
     }
 
-    println!("\x1b[34mCreated the new folder at {}\x1b[0m", new_path); 
+    /// Draw the children of the node, each line starts with the given prefix
+    fn draw(&self, prefix: &str, lines: &mut Vec<String>) {
+        let count = self.children.len();
 
-    // Use tree -C to print the new folder structure with colors 
-    let output = std::process::Command::new("tree")
-        .arg("-C")
-        .arg(&new_path)
-        .output()
-        .expect("Failed to execute command");
+        for (index, (name, child)) in self.children.iter().enumerate() {
+            let last = index + 1 == count;
+            let branch = if last { "└── " } else { "├── " };
 
-    // Print the new folder structure 
-    println!("{}", String::from_utf8_lossy(&output.stdout)); 
+            match &child.when {
+                Some(when) => lines.push(format!("{}{}{} ({})", prefix, branch, name, when)),
+                None => lines.push(format!("{}{}{}", prefix, branch, name)),
+            }
+
+            let indent = if last { "    " } else { "│   " };
+            child.draw(&format!("{}{}", prefix, indent), lines);
+        }
+    }
 }
 
-/// Create a new latex project, IEEE template for TEC 
+/// Load a built-in template from the TEMPLATES const map
 ///
 /// # Arguments
-/// * `path` - A string slice that holds the path to the new folder 
+/// * `name` - The name of the template
 ///
 /// # Example
 /// ```
-/// let path = "path/to/a/directory";
-/// ieee_tec(path);
+/// let template = builtin("math");
 /// ```
 ///
-/// # Note
-/// This template is based on the IEEEtran.cls class file 
-/// https://www.ctan.org/pkg/ieeetran 
-pub fn ieee_tec(path: &str) {
-    // Create the new folder 
-    let new_path = create_dir_using_stdin(path);
-
-    // Download the main.tex file 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/main.tex
-    let main = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/main.tex");
-    create_file(&new_path, "main.tex", main.as_str());
-
-    // Create the lib folder 
-    create_folder(&new_path, "lib"); 
-
-    // Create the src folder 
-    create_folder(&new_path, "src");
-
-    // Download the IEEEtran.cls file 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/lib/IEEEtran.cls 
-    let template = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/lib/IEEEtran.cls");
-    create_file(&new_path, "lib/IEEEtran.cls", template.as_str());
-
-    // Download the preamble.tex file 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/lib/preamble.tex 
-    let preamble = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/lib/preamble.tex");
-    create_file(&new_path, "lib/preamble.tex", preamble.as_str());
-
-    // Download the header.tex file 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/lib/header.tex 
-    let header = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/lib/header.tex");
-    create_file(&new_path, "lib/header.tex", header.as_str());
-
-    // Download the 01.md file 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/src/01.md 
-    let first = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/src/01.tex");
-    create_file(&new_path, "src/01.tex", first.as_str());
-
-    // Download the abstract.tex file 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/src/abstract.tex 
-    let abstract_ = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/src/abstract.tex");
-    create_file(&new_path, "src/abstract.tex", abstract_.as_str());
-
-    // Download the title.tex file 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/src/title.tex 
-    let title = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/src/title.tex");
-    create_file(&new_path, "src/title.tex", title.as_str());
-
-    // Create figs folder 
-    create_folder(&new_path, "figs"); 
-
-    // Download the logo 
-    // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/figs/logo.png
-    get_image("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/figs/logo.png", &format!("{}/figs", new_path), "logo.png");
-
-    // Create the build folder
-    create_folder(&new_path, "build");
-
-    // Ask the user if he wants to initialize a git repository
-    let git = read_stdin("Do you want to initialize a git repository? (Y/n)".to_string()).trim().to_string();
-
-    // Ask the user if he wants to create a README.md file 
-    let readme = read_stdin("Do you want to create a README.md file? (Y/n)".to_string()).trim().to_string();
-
-    // Check if the user wants to create a README.md file 
-    if GIT_ANSWERS.contains(&readme.as_str()) {
-        // Create a README.md file 
-        // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/README.md 
-        let readme = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/README.md");
-        create_file(&new_path, "README.md", readme.as_str());
-    }
-
-    // Check if the user wants to initialize a git repository
-    if GIT_ANSWERS.contains(&git.as_str()) {
-        // Create a .gitignore file 
-        // Download the .gitignore file from the repo
-        // https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/.gitignore
-        let gitignore = get_file("https://raw.githubusercontent.com/Johanx22x/latex-templates/main/ieeetec/.gitignore");
-        create_file(&new_path, ".gitignore", gitignore.as_str());
+/// # Panics
+/// This function will panic if the manifest of the template is not valid
+pub fn builtin(name: &str) -> Option<Template> {
+    crate::TEMPLATES.iter()
+        .find(|(template, _)| *template == name)
+        .map(|(_, manifest)| Template::parse(manifest).expect("Invalid built-in template"))
+}
 
+/// Load a built-in layer from the LAYERS const map
+/// Layers are small templates with the files shared by several templates
+/// Their files are inline, so they have no source; their origin is `builtin:layer/<name>`,
+/// the name given to `lth trust` for their hooks
+///
+/// # Arguments
+/// * `name` - The name of the layer
+///
+/// # Example
+/// ```
+/// let layer = layer("pandoc-pipeline");
+/// ```
+///
+/// # Panics
+/// This function will panic if the manifest of the layer is not valid
+pub fn layer(name: &str) -> Option<Template> {
+    crate::LAYERS.iter()
+        .find(|(layer, _)| *layer == name)
+        .map(|(_, manifest)| Template {
+            origin: Some(format!("builtin:layer/{}", name)),
+            ..Template::parse(manifest).expect("Invalid built-in layer")
+        })
+}
 
-        // Initialize a new git repository
-        init_git(&new_path);
-    }
+/// Merge two lists by key, the items of `own` replace the items of `inherited` with the same key
+fn merge<T, K: PartialEq>(inherited: Vec<T>, mut own: Vec<T>, key: impl Fn(&T) -> K) -> Vec<T> {
+    let mut merged: Vec<T> = inherited.into_iter()
+        .map(|item| match own.iter().position(|candidate| key(candidate) == key(&item)) {
+            Some(index) => own.remove(index),
+            None => item,
+        })
+        .collect();
+
+    merged.append(&mut own);
+    merged
+}
 
-    println!("\x1b[34mCreated the new folder at {}\x1b[0m", new_path);
-
-    // Use tree -C to print the new folder structure with colors
-    let output = std::process::Command::new("tree")
-        .arg("-C")
-        .arg(&new_path)
-        .output()
-        .expect("Failed to execute command");
-    
-    // Print the new folder structure
-    println!("{}", String::from_utf8_lossy(&output.stdout));
+/// Load all the built-in templates from the TEMPLATES const map
+///
+/// # Example
+/// ```
+/// for template in builtins() {
+///     println!("{}", template.name);
+/// }
+/// ```
+///
+/// # Panics
+/// This function will panic if the manifest of a template is not valid
+pub fn builtins() -> Vec<Template> {
+    crate::TEMPLATES.iter()
+        .map(|(_, manifest)| Template::parse(manifest).expect("Invalid built-in template"))
+        .collect()
 }
@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::trust_file;
+use crate::trace;
+
+/// Struct with the trust decisions of the user, one for each template source
+/// The hooks of a template only run if the source that declares them is trusted
+///
+/// # Example
+/// ```toml
+/// [sources]
+/// "https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math" = true
+/// "https://example.com/templates/unknown" = false
+/// ```
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct TrustStore {
+    #[serde(default)]
+    sources: BTreeMap<String, bool>,
+    /// File where the decisions are saved
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+/// Implementation of the TrustStore struct to load, change and save the trust decisions
+impl TrustStore {
+    /// Load the trust decisions from `~/.config/lth/trust.toml`
+    /// If the file doesn't exist, no source is trusted
+    ///
+    /// # Example
+    /// ```
+    /// let store = TrustStore::load()?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if the file can't be read or is not valid
+    pub fn load() -> Result<TrustStore, String> {
+        match trust_file() {
+            Some(path) => TrustStore::load_from(&path),
+            None => Ok(TrustStore::default()),
+        }
+    }
+
+    /// Load the trust decisions from a given file
+    ///
+    /// # Arguments
+    /// * `path` - The file with the decisions, it is created when the decisions are saved
+    ///
+    /// # Errors
+    /// This function returns an error if the file can't be read or is not valid
+    pub fn load_from(path: &Path) -> Result<TrustStore, String> {
+        let mut store = match path.is_file() {
+            true => {
+                let content = std::fs::read_to_string(path)
+                    .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+                toml::from_str(&content).map_err(|err| format!("Invalid trust file {}: {}", path.display(), err))?
+            },
+            false => TrustStore::default(),
+        };
+
+        store.path = Some(path.to_path_buf());
+        Ok(store)
+    }
+
+    /// Return the decision of the user for a source, `None` if the user was never asked
+    ///
+    /// # Example
+    /// ```
+    /// if store.decision(&template.source) == Some(true) { ... }
+    /// ```
+    pub fn decision(&self, source: &str) -> Option<bool> {
+        self.sources.get(source).copied()
+    }
+
+    /// Check if the user trusts a source
+    pub fn is_trusted(&self, source: &str) -> bool {
+        self.decision(source) == Some(true)
+    }
+
+    /// Record the decision of the user for a source
+    ///
+    /// # Arguments
+    /// * `source` - The source of the templates
+    /// * `trusted` - Whether the hooks of the source can run
+    pub fn set(&mut self, source: &str, trusted: bool) {
+        self.sources.insert(source.to_string(), trusted);
+    }
+
+    /// Forget the decision of the user for a source, so the user is asked again
+    ///
+    /// # Returns
+    /// `true` if there was a decision for the source
+    pub fn forget(&mut self, source: &str) -> bool {
+        self.sources.remove(source).is_some()
+    }
+
+    /// Return the sources with a decision, sorted, with the decision
+    pub fn sources(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.sources.iter().map(|(source, trusted)| (source.as_str(), *trusted))
+    }
+
+    /// Save the decisions in the file they were loaded from
+    ///
+    /// # Example
+    /// ```
+    /// store.set(&template.source, true);
+    /// store.save()?;
+    /// ```
+    ///
+    /// # Errors
+    /// This function returns an error if there is no configuration folder
+    /// or if the file can't be written
+    pub fn save(&self) -> Result<(), String> {
+        let path = self.path.as_ref().ok_or("There is no configuration folder to save the trusted sources")?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| format!("Failed to create the folder {}: {}", parent.display(), err))?;
+        }
+
+        let content = toml::to_string(self).map_err(|err| format!("Failed to save the trusted sources: {}", err))?;
+        trace!("write {}", path.display());
+        std::fs::write(path, content).map_err(|err| format!("Failed to write {}: {}", path.display(), err))
+    }
+}
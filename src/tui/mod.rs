@@ -0,0 +1,479 @@
+use std::collections::HashMap;
+use std::io::Stdout;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::execute;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+
+use crate::fuzzy::best_score;
+use crate::templates::{Answers, Template, Variable};
+use crate::preflight::{check_target, Collision};
+use crate::fetch::{Fetcher, SourceFetcher};
+
+// Maximum number of lines shown for each key file in the preview
+const PREVIEW_LINES: usize = 40;
+
+// Key file of a template, with its content or the error that happened loading it
+type PreviewFile = (String, Result<String, String>);
+
+/// Struct with the result of the template picker
+pub struct Selection {
+    pub template: Template,
+    pub name: String,
+    pub answers: Answers,
+}
+
+/// Screens of the terminal UI
+enum Screen {
+    Picker,
+    Form(Form),
+}
+
+/// Struct to manage the state of the template picker
+struct Picker {
+    templates: Vec<Template>,
+    query: String,
+    matches: Vec<usize>,
+    state: ListState,
+    // Key files of each template, loaded when the user asks for them
+    previews: HashMap<String, Vec<PreviewFile>>,
+}
+
+/// Struct to manage the state of the variables form
+struct Form {
+    template: usize,
+    // Name of the new folder, when it was given before opening the picker
+    folder: Option<String>,
+    fields: Vec<Variable>,
+    values: Vec<String>,
+    input: String,
+    error: Option<String>,
+}
+
+/// Restore the terminal when it goes out of scope, even after a panic
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(std::io::stdout(), LeaveAlternateScreen);
+    }
+}
+
+/// Show the terminal UI to choose a template and answer its variables
+///
+/// # Arguments
+/// * `templates` - The templates the user can choose from
+/// * `path` - The path where the new folder is created
+/// * `name` - The name of the new folder, if it was already given
+/// * `given` - The answers that are already known, like the features given with `--with`
+/// * `collision` - What to do if the folder already exists
+///
+/// # Example
+/// ```
+/// if let Some(selection) = pick(builtins(), path, None, &Answers::new(), Collision::Abort)? {
+///     Plan::build(&selection.template, path, &selection.name, &selection.answers, Collision::Abort).apply();
+/// }
+/// ```
+///
+/// # Errors
+/// This function returns an error if the terminal can't be used
+///
+/// # Returns
+/// `None` if the user closes the picker without choosing a template
+pub fn pick(templates: Vec<Template>, path: &str, name: Option<&str>, given: &Answers, collision: Collision) -> Result<Option<Selection>, String> {
+    enable_raw_mode().map_err(|err| format!("Failed to start the terminal UI: {}", err))?;
+    let _guard = TerminalGuard;
+
+    execute!(std::io::stdout(), EnterAlternateScreen).map_err(|err| format!("Failed to start the terminal UI: {}", err))?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))
+        .map_err(|err| format!("Failed to start the terminal UI: {}", err))?;
+
+    let mut picker = Picker::new(templates);
+    let mut screen = Screen::Picker;
+
+    loop {
+        draw(&mut terminal, &mut picker, &screen)?;
+
+        let key = match event::read().map_err(|err| format!("Failed to read the terminal input: {}", err))? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+
+        // Ctrl+C always closes the picker
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Ok(None);
+        }
+
+        screen = match screen {
+            Screen::Picker => match picker.handle(key) {
+                PickerAction::None => Screen::Picker,
+                PickerAction::Quit => return Ok(None),
+                PickerAction::Select(index) => Screen::Form(Form::new(index, &picker.templates[index], name, given)),
+            },
+            Screen::Form(mut form) => match form.handle(key, path, collision) {
+                FormAction::None => Screen::Form(form),
+                FormAction::Back => Screen::Picker,
+                FormAction::Done => {
+                    let template = picker.templates[form.template].clone();
+                    return Ok(Some(form.into_selection(template)));
+                },
+            },
+        };
+    }
+}
+
+/// Actions produced by the keys of the picker
+enum PickerAction {
+    None,
+    Quit,
+    Select(usize),
+}
+
+/// Actions produced by the keys of the form
+enum FormAction {
+    None,
+    Back,
+    Done,
+}
+
+/// Implementation of the Picker struct to search and preview the templates
+impl Picker {
+    /// Create a new picker with all the templates matching
+    fn new(templates: Vec<Template>) -> Picker {
+        let mut picker = Picker {
+            templates,
+            query: String::new(),
+            matches: Vec::new(),
+            state: ListState::default(),
+            previews: HashMap::new(),
+        };
+        picker.search();
+        picker
+    }
+
+    /// Return the index of the selected template, if any
+    fn selected(&self) -> Option<usize> {
+        self.state.selected().and_then(|index| self.matches.get(index).copied())
+    }
+
+    /// Filter and sort the templates using the query
+    fn search(&mut self) {
+        let mut scored: Vec<(usize, i64)> = self.templates.iter()
+            .enumerate()
+            .filter_map(|(index, template)| {
+                let tags = template.tags.join(" ");
+                best_score(&self.query, &[(&template.name, 3), (&tags, 2), (&template.description, 1)])
+                    .map(|score| (index, score))
+            })
+            .collect();
+
+        // Sort by score, keeping the original order of the templates on ties
+        scored.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        self.matches = scored.into_iter().map(|(index, _)| index).collect();
+        self.state.select(if self.matches.is_empty() { None } else { Some(0) });
+    }
+
+    /// Load the key files of the selected template
+    fn load_preview(&mut self) {
+        let template = match self.selected() {
+            Some(index) => &self.templates[index],
+            None => return,
+        };
+
+        if self.previews.contains_key(&template.name) {
+            return;
+        }
+
+        let files = template.preview.iter()
+            .map(|path| {
+                let content = match template.files.iter().find(|file| &file.asset.path == path) {
+                    Some(file) => match &file.asset.content {
+                        Some(content) => Ok(content.clone()),
+                        None => SourceFetcher.fetch(&file.asset.url(template.base_of(file)))
+                            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned()),
+                    },
+                    None => Err(format!("{} is not a file of the template", path)),
+                };
+                (path.clone(), content)
+            })
+            .collect();
+
+        self.previews.insert(template.name.clone(), files);
+    }
+
+    /// Handle a key pressed by the user
+    fn handle(&mut self, key: KeyEvent) -> PickerAction {
+        match key.code {
+            KeyCode::Esc => return PickerAction::Quit,
+            KeyCode::Enter => {
+                if let Some(index) = self.selected() {
+                    return PickerAction::Select(index);
+                }
+            },
+            KeyCode::Up => {
+                let index = self.state.selected().unwrap_or(0);
+                self.state.select(Some(index.saturating_sub(1)));
+            },
+            KeyCode::Down => {
+                let index = self.state.selected().map_or(0, |index| index + 1);
+                self.state.select(Some(index.min(self.matches.len().saturating_sub(1))));
+            },
+            KeyCode::Tab => self.load_preview(),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.search();
+            },
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.search();
+            },
+            _ => {},
+        }
+
+        PickerAction::None
+    }
+}
+
+/// Implementation of the Form struct to ask the variables of a template
+impl Form {
+    /// Create a new form with the folder name and the variables of the template
+    /// The folder name and the answers already known are not asked
+    fn new(index: usize, template: &Template, folder: Option<&str>, answers: &Answers) -> Form {
+        let field = Variable {
+            name: "folder".to_string(),
+            prompt: "Enter the name of the new folder".to_string(),
+            kind: Default::default(),
+            default: None,
+            required: true,
+            options: Vec::new(),
+        };
+
+        let given = folder.map(String::from);
+        let mut fields = if given.is_some() { Vec::new() } else { vec![field] };
+        fields.extend(template.questions().into_iter().filter(|variable| !answers.contains_key(&variable.name)));
+
+        Form {
+            template: index,
+            folder: given,
+            fields,
+            values: Vec::new(),
+            input: String::new(),
+            error: None,
+        }
+    }
+
+    /// Handle a key pressed by the user
+    /// The first field is the name of the new folder, created inside `path`
+    fn handle(&mut self, key: KeyEvent, path: &str, collision: Collision) -> FormAction {
+        match key.code {
+            KeyCode::Esc => {
+                // Go back to the previous field, or to the picker
+                match self.values.pop() {
+                    Some(value) => self.input = value,
+                    None => return FormAction::Back,
+                }
+                self.error = None;
+            },
+            KeyCode::Enter => {
+                // A template without variables and a given folder has nothing to ask
+                if self.values.len() == self.fields.len() {
+                    return FormAction::Done;
+                }
+
+                let field = &self.fields[self.values.len()];
+                let value = field.resolve(&self.input).and_then(|value| {
+                    if self.folder.is_none() && self.values.is_empty() {
+                        check_target(path, &value, collision)?;
+                    }
+                    Ok(value)
+                });
+
+                match value {
+                    Ok(value) => {
+                        self.values.push(value);
+                        self.input.clear();
+                        self.error = None;
+                        if self.values.len() == self.fields.len() {
+                            return FormAction::Done;
+                        }
+                    },
+                    Err(err) => self.error = Some(err),
+                }
+            },
+            KeyCode::Backspace => {
+                self.input.pop();
+            },
+            KeyCode::Char(c) => self.input.push(c),
+            _ => {},
+        }
+
+        FormAction::None
+    }
+
+    /// Build the selection with the answers of the form
+    fn into_selection(self, template: Template) -> Selection {
+        let mut values = self.values.into_iter();
+        let (name, skip) = match self.folder {
+            Some(folder) => (folder, 0),
+            None => (values.next().unwrap_or_default(), 1),
+        };
+        let answers = self.fields.iter().skip(skip).map(|field| field.name.clone()).zip(values).collect();
+
+        Selection { template, name, answers }
+    }
+}
+
+/// Draw the current screen
+fn draw(terminal: &mut Terminal<CrosstermBackend<Stdout>>, picker: &mut Picker, screen: &Screen) -> Result<(), String> {
+    terminal.draw(|frame| match screen {
+        Screen::Picker => draw_picker(frame, picker),
+        Screen::Form(form) => draw_form(frame, &picker.templates[form.template], form),
+    }).map_err(|err| format!("Failed to draw the terminal UI: {}", err))?;
+
+    Ok(())
+}
+
+/// Draw the search box, the list of templates and the details of the selected one
+fn draw_picker(frame: &mut Frame, picker: &mut Picker) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(5), Constraint::Length(1)])
+        .split(frame.size());
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(rows[1]);
+
+    // Search box
+    let search = Paragraph::new(Line::from(vec![
+        Span::styled("> ", Style::default().fg(Color::Blue)),
+        Span::raw(picker.query.as_str()),
+    ])).block(Block::default().borders(Borders::ALL).title(" Search "));
+    frame.render_widget(search, rows[0]);
+
+    // List of templates
+    let items: Vec<ListItem> = picker.matches.iter()
+        .map(|index| {
+            let template = &picker.templates[*index];
+            ListItem::new(vec![
+                Line::from(Span::styled(template.name.clone(), Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD))),
+                Line::from(Span::styled(template.description.clone(), Style::default().fg(Color::Gray))),
+            ])
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Templates "))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(list, columns[0], &mut picker.state);
+
+    // Details of the selected template
+    draw_details(frame, picker, columns[1]);
+
+    let help = Paragraph::new(" ↑/↓ move  Enter select  Tab preview key files  Esc quit")
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(help, rows[2]);
+}
+
+/// Draw the description, tags, layout and key files of the selected template
+fn draw_details(frame: &mut Frame, picker: &Picker, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(" Details ");
+
+    let template = match picker.selected() {
+        Some(index) => &picker.templates[index],
+        None => {
+            frame.render_widget(Paragraph::new("No templates match the search").block(block), area);
+            return;
+        },
+    };
+
+    let title = Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD);
+
+    let mut lines = vec![
+        Line::from(Span::styled(template.name.clone(), title)),
+        Line::from(template.description.clone()),
+        Line::from(""),
+        Line::from(vec![Span::styled("Tags: ", title), Span::raw(template.tags.join(", "))]),
+        Line::from(""),
+        Line::from(Span::styled("Layout", title)),
+    ];
+    lines.extend(template.layout().into_iter().map(Line::from));
+
+    match picker.previews.get(&template.name) {
+        Some(files) => {
+            for (path, content) in files {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(path.clone(), title)));
+                match content {
+                    Ok(content) => lines.extend(content.lines().take(PREVIEW_LINES).map(|line| Line::from(line.to_string()))),
+                    Err(err) => lines.push(Line::from(Span::styled(err.clone(), Style::default().fg(Color::Red)))),
+                }
+            }
+        },
+        None if !template.preview.is_empty() => {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                format!("Press Tab to preview {}", template.preview.join(", ")),
+                Style::default().fg(Color::DarkGray),
+            )));
+        },
+        None => {},
+    }
+
+    frame.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: false }), area);
+}
+
+/// Draw the answered variables and the input of the current one
+fn draw_form(frame: &mut Frame, template: &Template, form: &Form) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(1)])
+        .split(frame.size());
+
+    let mut lines = Vec::new();
+
+    for (field, value) in form.fields.iter().zip(&form.values) {
+        lines.push(Line::from(vec![
+            Span::styled("✔ ", Style::default().fg(Color::Green)),
+            Span::raw(format!("{}: ", field.message())),
+            Span::styled(value.clone(), Style::default().fg(Color::Blue)),
+        ]));
+    }
+
+    match form.fields.get(form.values.len()) {
+        Some(field) => {
+            lines.push(Line::from(vec![
+                Span::styled("> ", Style::default().fg(Color::Blue)),
+                Span::raw(format!("{}: ", field.message())),
+                Span::raw(form.input.clone()),
+                Span::styled("█", Style::default().fg(Color::Gray)),
+            ]));
+
+            // The options of select and multi-select fields, chosen by their number or their text
+            for (index, option) in field.options.iter().enumerate() {
+                lines.push(Line::from(Span::styled(format!("    {}) {}", index + 1, option), Style::default().fg(Color::Gray))));
+            }
+        },
+        None => lines.push(Line::from("Press Enter to create the project")),
+    }
+
+    if let Some(err) = &form.error {
+        lines.push(Line::from(Span::styled(err.clone(), Style::default().fg(Color::Red))));
+    }
+
+    let block = Block::default().borders(Borders::ALL).title(format!(" New {} project ", template.name));
+    frame.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: false }), rows[0]);
+
+    let help = Paragraph::new(" Enter confirm  Esc back")
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(help, rows[1]);
+}
@@ -0,0 +1,367 @@
+use std::path::{Path, PathBuf};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::fetch::{Fetcher, SourceFetcher};
+use crate::sandbox::check_relative;
+use crate::settings::cache_dir;
+use crate::templates::{Template, MANIFEST};
+use crate::{detail, trace};
+
+/// Struct to describe a released version of a template
+/// The files of a release come from a git tag of the repository of the template,
+/// from a `.tar.gz` archive or from another source URL
+///
+/// # Example
+/// ```toml
+/// repository = "https://github.com/Johanx22x/latex-templates"
+///
+/// [[releases]]
+/// version = "1.2.0"
+/// tag = "ieee-v1.2.0"          # Git tag of the repository
+/// path = "ieee"                # Folder of the template inside the tag or the archive
+///
+/// [[releases]]
+/// version = "1.1.0"
+/// archive = "https://example.com/ieee-1.1.0.tar.gz"
+/// sha256 = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+///
+/// [[releases]]
+/// version = "1.0.0"
+/// source = "https://raw.githubusercontent.com/Johanx22x/latex-templates/v1.0.0/ieee"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Release {
+    pub version: String,
+    pub tag: Option<String>,
+    pub archive: Option<String>,
+    pub source: Option<String>,
+    pub path: Option<String>,
+    /// SHA-256 checksum of the archive, checked when it is downloaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// Implementation of the Release struct to check the releases
+impl Release {
+    /// Check that the release has a valid version and exactly one place for its files
+    ///
+    /// # Errors
+    /// This function returns an error with the message to show to the template author
+    pub fn validate(&self) -> Result<(), String> {
+        Version::parse(&self.version).map_err(|err| format!("The release {} has an invalid version: {}", self.version, err))?;
+
+        match [&self.tag, &self.archive, &self.source].iter().filter(|place| place.is_some()).count() {
+            1 => Ok(()),
+            0 => Err(format!("The release {} needs a tag, an archive or a source", self.version)),
+            _ => Err(format!("The release {} can only have one of tag, archive and source", self.version)),
+        }?;
+
+        match (&self.sha256, &self.archive) {
+            (Some(_), None) => Err(format!("The release {} has a checksum, but only archives are checked", self.version)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Split a template name like `ieee@1.2` into the name and the version requirement
+/// A bare version pins its components, so `1.2` matches `1.2.x`;
+/// requirements like `^1.2` or `>=1.0, <2.0` are used as they are
+///
+/// # Arguments
+/// * `spec` - The name of the template, with an optional version after `@`
+///
+/// # Example
+/// ```
+/// let (name, requirement) = parse_spec("ieee@1.2")?;
+/// assert_eq!(name, "ieee");
+/// ```
+///
+/// # Errors
+/// This function returns an error if the version requirement is not valid
+pub fn parse_spec(spec: &str) -> Result<(&str, Option<VersionReq>), String> {
+    let (name, version) = match spec.split_once('@') {
+        Some((name, version)) => (name, version.trim()),
+        None => return Ok((spec, None)),
+    };
+
+    let requirement = match version.starts_with(|c: char| c.is_ascii_digit()) {
+        true => format!("={}", version),
+        false => version.to_string(),
+    };
+
+    VersionReq::parse(&requirement)
+        .map(|requirement| (name, Some(requirement)))
+        .map_err(|err| format!("Invalid version {} for the template {}: {}", version, name, err))
+}
+
+/// Return the versions of a template, the newest first
+/// The version of the manifest and the versions of its releases are included,
+/// the versions that are not valid semantic versions are skipped
+///
+/// # Example
+/// ```
+/// let versions = available(&template); // [1.2.0, 1.1.0, 1.0.0]
+/// ```
+pub fn available(template: &Template) -> Vec<Version> {
+    let mut versions: Vec<Version> = template.version.iter()
+        .chain(template.releases.iter().map(|release| &release.version))
+        .filter_map(|version| Version::parse(version).ok())
+        .collect();
+
+    versions.sort_by(|a, b| b.cmp(a));
+    versions.dedup();
+    versions
+}
+
+/// Return the template at the newest version matching a requirement
+/// The version of the manifest uses its source, the releases are checked out in the cache
+///
+/// # Arguments
+/// * `template` - The template, as it is declared
+/// * `requirement` - The version requirement, or `None` for the version of the manifest
+///
+/// # Example
+/// ```
+/// let (name, requirement) = parse_spec("ieee@1.2")?;
+/// let template = select(registry.get(name).unwrap(), requirement.as_ref())?;
+/// ```
+///
+/// # Errors
+/// This function returns an error if no version matches,
+/// or if the files of the release can't be fetched
+pub fn select(template: &Template, requirement: Option<&VersionReq>) -> Result<Template, String> {
+    let requirement = match requirement {
+        Some(requirement) => requirement,
+        None => return Ok(template.clone()),
+    };
+
+    let versions = available(template);
+    let version = versions.iter().find(|version| requirement.matches(version)).ok_or_else(|| {
+        let versions: Vec<String> = versions.iter().map(Version::to_string).collect();
+        format!("The template {} has no version matching {} (available: {})", template.name, requirement,
+            if versions.is_empty() { "none".to_string() } else { versions.join(", ") })
+    })?;
+
+    // The version of the manifest is the one of its source
+    if template.version.as_deref().and_then(|current| Version::parse(current).ok()).as_ref() == Some(version) {
+        return Ok(template.clone());
+    }
+
+    let release = template.releases.iter()
+        .find(|release| Version::parse(&release.version).ok().as_ref() == Some(version))
+        .expect("The available versions come from the releases");
+
+    let cache = cache_dir().ok_or("There is no cache folder to keep the releases of the templates")?;
+    checkout(template, release, &cache.join("releases"))
+}
+
+/// Return the template of a release, fetching its files in a cache folder
+/// When the release has its own manifest, it replaces the manifest of the template
+///
+/// # Arguments
+/// * `template` - The template, as it is declared
+/// * `release` - The release to check out
+/// * `cache` - The folder where the tags and the archives are kept
+///
+/// # Example
+/// ```
+/// let template = checkout(&template, &template.releases[0], &cache)?;
+/// ```
+///
+/// # Errors
+/// This function returns an error if the release is not valid,
+/// or if the tag or the archive can't be fetched
+pub fn checkout(template: &Template, release: &Release, cache: &Path) -> Result<Template, String> {
+    release.validate()?;
+
+    // The name and the path come from the manifest, they can't leave the cache
+    check_relative(&template.name).map_err(|err| format!("Invalid template name {}", err))?;
+    if let Some(path) = &release.path {
+        check_relative(path).map_err(|err| format!("Invalid path of the release {}: {}", release.version, err))?;
+    }
+
+    let folder = match (&release.tag, &release.archive, &release.source) {
+        (_, _, Some(source)) => {
+            let mut template = template.clone();
+            template.source = source.trim_end_matches('/').to_string();
+            template.version = Some(release.version.clone());
+            return Ok(template);
+        },
+        (Some(tag), _, _) => {
+            let repository = template.repository.as_deref()
+                .ok_or_else(|| format!("The release {} of {} has a tag, but the template has no repository", release.version, template.name))?;
+            fetch_tag(repository, tag, &cache.join(&template.name).join(&release.version))?
+        },
+        (_, Some(archive), _) => fetch_archive(archive, release.sha256.as_deref(), &cache.join(&template.name).join(&release.version))?,
+        (None, None, None) => unreachable!("The release was validated"),
+    };
+
+    let folder = match &release.path {
+        Some(path) => folder.join(path),
+        None => folder,
+    };
+
+    // The manifest of the release, if it has one, describes the files of that version
+    let mut release_template = match folder.join(MANIFEST).is_file() {
+        true => Template::load(&folder)?,
+        false => template.clone(),
+    };
+
+    // The files are read from the cache, the template still comes from its origin,
+    // so the user trusts it once for all its versions
+    let folder = folder.canonicalize().map_err(|err| format!("The release {} of {} has no folder {}: {}", release.version, template.name, folder.display(), err))?;
+    release_template.source = format!("file://{}", folder.display());
+    release_template.origin = Some(template.origin().to_string());
+    release_template.version = Some(release.version.clone());
+    Ok(release_template)
+}
+
+/// Clone a git repository at a tag, only once
+fn fetch_tag(repository: &str, tag: &str, folder: &Path) -> Result<PathBuf, String> {
+    if folder.is_dir() {
+        trace!("cached {}", folder.display());
+        return Ok(folder.to_path_buf());
+    }
+
+    detail!("Cloning {} at {}", repository, tag);
+    let partial = partial_folder(folder);
+    let _ = std::fs::remove_dir_all(&partial);
+    if let Some(parent) = folder.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| format!("Failed to create the folder {}: {}", parent.display(), err))?;
+    }
+
+    let repo = git2::Repository::clone(repository, &partial).map_err(|err| format!("Failed to clone {}: {}", repository, err))?;
+    let commit = repo.revparse_single(&format!("refs/tags/{}", tag))
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|err| format!("The repository {} has no tag {}: {}", repository, tag, err))?;
+
+    repo.checkout_tree(commit.as_object(), Some(git2::build::CheckoutBuilder::new().force()))
+        .and_then(|_| repo.set_head_detached(commit.id()))
+        .map_err(|err| format!("Failed to check out {} of {}: {}", tag, repository, err))?;
+
+    std::fs::rename(&partial, folder).map_err(|err| format!("Failed to keep the release in {}: {}", folder.display(), err))?;
+    Ok(folder.to_path_buf())
+}
+
+/// Pack the folder of a template in a `.tar.gz` archive, the format read by the `archive` releases
+/// The files are inside a `name-version` folder, the `.git` folder is skipped
+///
+/// # Arguments
+/// * `folder` - The folder of the template, with its manifest
+/// * `root` - The name of the folder inside the archive, like `thesis-1.2.0`
+///
+/// # Example
+/// ```
+/// let bytes = pack(Path::new("templates/thesis"), "thesis-1.2.0")?;
+/// ```
+///
+/// # Errors
+/// This function returns an error if a file can't be read
+pub fn pack(folder: &Path, root: &str) -> Result<Vec<u8>, String> {
+    let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    builder.follow_symlinks(false);
+
+    let mut pending = vec![folder.to_path_buf()];
+    let mut files = Vec::new();
+    while let Some(dir) = pending.pop() {
+        let entries = std::fs::read_dir(&dir).map_err(|err| format!("Failed to read {}: {}", dir.display(), err))?;
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            match path.is_dir() && !path.is_symlink() {
+                true if path.file_name().is_some_and(|name| name == ".git") => {},
+                true => pending.push(path),
+                false => files.push(path),
+            }
+        }
+    }
+
+    // Sorted, so the same folder always gives the same archive
+    files.sort();
+    for path in files {
+        let relative = path.strip_prefix(folder).map_err(|_| format!("{} is outside of {}", path.display(), folder.display()))?;
+        builder.append_path_with_name(&path, Path::new(root).join(relative))
+            .map_err(|err| format!("Failed to pack {}: {}", path.display(), err))?;
+    }
+
+    builder.into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(|err| format!("Failed to pack {}: {}", folder.display(), err))
+}
+
+/// Return the folder where a release is written before it is complete,
+/// so an interrupted download is never used from the cache
+fn partial_folder(folder: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.partial", folder.display()))
+}
+
+/// Return the SHA-256 checksum of some bytes, in lowercase hexadecimal
+///
+/// # Example
+/// ```
+/// let checksum = sha256(&bytes);
+/// ```
+pub fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Unpack a `.tar.gz` archive in a new folder, written as a partial folder until it is complete
+/// Entries with absolute paths or `..` are not unpacked
+///
+/// # Arguments
+/// * `bytes` - The archive
+/// * `folder` - The folder to create, it must not exist
+///
+/// # Returns
+/// The folder of the files, the single folder at the root of the archive if it has one
+///
+/// # Errors
+/// This function returns an error if the archive is not valid or the folder can't be written
+pub fn unpack(bytes: &[u8], folder: &Path) -> Result<PathBuf, String> {
+    let partial = partial_folder(folder);
+    let _ = std::fs::remove_dir_all(&partial);
+    std::fs::create_dir_all(&partial).map_err(|err| format!("Failed to create the folder {}: {}", partial.display(), err))?;
+
+    tar::Archive::new(flate2::read::GzDecoder::new(bytes))
+        .unpack(&partial)
+        .map_err(|err| format!("Failed to unpack the archive: {}", err))?;
+
+    std::fs::rename(&partial, folder).map_err(|err| format!("Failed to write the folder {}: {}", folder.display(), err))?;
+    archive_root(folder)
+}
+
+/// Download a `.tar.gz` archive and unpack it, only once
+/// The checksum, when it is known, is checked before the archive is unpacked
+fn fetch_archive(url: &str, checksum: Option<&str>, folder: &Path) -> Result<PathBuf, String> {
+    if folder.is_dir() {
+        trace!("cached {}", folder.display());
+        return archive_root(folder);
+    }
+
+    detail!("Downloading {}", url);
+    let bytes = SourceFetcher.fetch(url)?;
+
+    if let Some(checksum) = checksum {
+        let actual = sha256(&bytes);
+        if !actual.eq_ignore_ascii_case(checksum.trim()) {
+            return Err(format!("The checksum of {} doesn't match: expected {}, got {}", url, checksum.trim(), actual));
+        }
+    }
+
+    unpack(&bytes, folder).map_err(|err| format!("{}: {}", url, err))
+}
+
+/// Return the folder of the files of an unpacked archive,
+/// an archive with a single folder at its root is used from that folder
+fn archive_root(folder: &Path) -> Result<PathBuf, String> {
+    let entries: Vec<PathBuf> = std::fs::read_dir(folder)
+        .map_err(|err| format!("Failed to read {}: {}", folder.display(), err))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+
+    Ok(match entries.as_slice() {
+        [root] if root.is_dir() && !folder.join(MANIFEST).is_file() => root.clone(),
+        _ => folder.to_path_buf(),
+    })
+}
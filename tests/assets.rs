@@ -0,0 +1,25 @@
+//! Tell the text files of a template from the binary ones, and normalize their line endings
+
+use lth::assets::{detect_encoding, normalize_line_endings, Encoding};
+
+#[test]
+fn encodings_come_from_the_names_then_the_content() {
+    // The known names and extensions win over the content
+    assert_eq!(detect_encoding("main.tex", b"\\documentclass{article}"), Encoding::Text);
+    assert_eq!(detect_encoding("lib/Makefile", b"all:\n\tpandoc\n"), Encoding::Text);
+    assert_eq!(detect_encoding("images/logo.PNG", b"plain text"), Encoding::Binary);
+
+    // Unknown files are checked by their content
+    assert_eq!(detect_encoding("notes.unknown", b"caf\xe9 au lait\r\n"), Encoding::Text);
+    assert_eq!(detect_encoding("data.unknown", b"abc\0def"), Encoding::Binary);
+    assert_eq!(detect_encoding("data.unknown", b"\x01\x02\x03\x04abc"), Encoding::Binary);
+    assert_eq!(detect_encoding("empty", b""), Encoding::Text);
+}
+
+#[test]
+fn line_endings_become_lf() {
+    assert_eq!(normalize_line_endings(b"a\r\nb\rc\n"), b"a\nb\nc\n");
+    assert_eq!(normalize_line_endings(b"\r\n\r\r\n"), b"\n\n\n");
+    assert_eq!(normalize_line_endings(b"caf\xe9\r\n"), b"caf\xe9\n");
+    assert_eq!(normalize_line_endings(b""), b"");
+}
@@ -0,0 +1,122 @@
+//! Write templates with the skeleton, check them with the linter and render them from the disk
+
+use std::path::Path;
+
+use lth::authoring::{import, lint, render, skeleton, Severity};
+use lth::registry::TemplateRegistry;
+use lth::{Scaffolder, Template};
+
+#[test]
+fn skeleton_is_a_valid_template() {
+    let directory = tempfile::tempdir().unwrap();
+    let dir = skeleton(directory.path().to_str().unwrap(), "thesis").unwrap();
+
+    assert_eq!(lint(&dir), Vec::new());
+    assert!(skeleton(directory.path().to_str().unwrap(), "thesis").is_err());
+}
+
+#[test]
+fn lint_reports_the_broken_manifests() {
+    let directory = tempfile::tempdir().unwrap();
+    let dir = skeleton(directory.path().to_str().unwrap(), "thesis").unwrap();
+
+    let manifest = std::fs::read_to_string(dir.join("template.toml")).unwrap()
+        .replace("path = \"Makefile\"", "path = \"../Makefile\"")
+        .replace("when = \"readme\"", "when = \"readmee\"")
+        + "\n[[files]]\npath = \"chapters/intro.tex\"\n";
+    std::fs::write(dir.join("template.toml"), manifest).unwrap();
+    std::fs::write(dir.join("notes.txt"), "").unwrap();
+
+    let issues = lint(&dir);
+    let has = |severity: Severity, text: &str| issues.iter().any(|issue| issue.severity == severity && issue.message.contains(text));
+
+    assert!(has(Severity::Error, "../Makefile: the path can't go outside of the folder"));
+    assert!(has(Severity::Error, "unknown variable readmee"));
+    assert!(has(Severity::Error, "The file chapters/intro.tex is missing"));
+    assert!(has(Severity::Warning, "The variable readme is not used by any file"));
+    assert!(has(Severity::Warning, "notes.txt is not a file of the template"));
+    assert!(has(Severity::Warning, "Makefile is not a file of the template"));
+}
+
+#[test]
+fn local_templates_render_and_override_the_builtins() {
+    let directory = tempfile::tempdir().unwrap();
+    let dir = skeleton(directory.path().to_str().unwrap(), "math").unwrap();
+
+    let template = Template::load(&dir).unwrap();
+    assert_eq!(template.local_root(), Some(dir.canonicalize().unwrap()));
+
+    let project = render(&template).unwrap();
+    assert_eq!(project.files, ["main.tex", "Makefile", "README.md"]);
+    assert_eq!(project.git_commit, None);
+    assert!(std::fs::read_to_string(Path::new(&project.path).join("main.tex")).unwrap().contains("\\title{math}"));
+    std::fs::remove_dir_all(Path::new(&project.path).parent().unwrap()).unwrap();
+
+    let mut registry = TemplateRegistry::builtin();
+    registry.add_dir(directory.path());
+    assert_eq!(registry.templates().len(), 4);
+    assert_eq!(registry.get("math").unwrap().description, "Describe the template in one line");
+}
+
+#[test]
+fn import_skips_the_artifacts_and_detects_the_variables() {
+    let directory = tempfile::tempdir().unwrap();
+    let project = directory.path().join("paper");
+    for folder in ["src", "build", ".git"] {
+        std::fs::create_dir_all(project.join(folder)).unwrap();
+    }
+    std::fs::write(project.join("metadata.yaml"), "---\ntitle: \"Old title\"\nauthor: Ada\nbibliography: refs.bib\n---\n").unwrap();
+    std::fs::write(project.join("src/01.md"), "# Introduction\n").unwrap();
+    std::fs::write(project.join("main.tex"), "\\date{\\today}\n").unwrap();
+    for artifact in ["main.aux", "main.log", "main.pdf", "build/main.pdf", ".git/HEAD"] {
+        std::fs::write(project.join(artifact), "").unwrap();
+    }
+
+    let templates = directory.path().join("templates");
+    let dir = import(&project, templates.to_str().unwrap(), "paper").unwrap();
+    assert_eq!(lint(&dir), Vec::new());
+
+    let template = Template::load(&dir).unwrap();
+    let files: Vec<&str> = template.files.iter().map(|file| file.asset.path.as_str()).collect();
+    let variables: Vec<&str> = template.variables.iter().map(|variable| variable.name.as_str()).collect();
+    assert_eq!(files, ["main.tex", "metadata.yaml", "src/01.md"]);
+    assert_eq!(variables, ["date", "title", "author", "git"]);
+    assert_eq!(template.folders, ["build"]);
+    assert_eq!(template.backend().name(), "pandoc");
+
+    let output = directory.path().join("output");
+    std::fs::create_dir(&output).unwrap();
+    Scaffolder::new(template)
+        .path(output.to_str().unwrap())
+        .answer("title", "New title")
+        .answer("git", "no")
+        .create()
+        .unwrap();
+
+    let metadata = std::fs::read_to_string(output.join("paper/metadata.yaml")).unwrap();
+    assert_eq!(metadata, "---\ntitle: \"New title\"\nauthor: Ada\nbibliography: refs.bib\n---\n");
+    assert_eq!(std::fs::read_to_string(output.join("paper/main.tex")).unwrap(), "\\date{\\today}\n");
+}
+
+#[test]
+fn import_skips_the_comments_and_the_symlinks() {
+    let directory = tempfile::tempdir().unwrap();
+    let project = directory.path().join("paper");
+    std::fs::create_dir_all(project.join("lib")).unwrap();
+    std::fs::write(project.join("main.tex"), "% \\title{Draft}\n\\title{Real title} % \\author{Nobody}\n").unwrap();
+
+    // A link to a parent folder must not be followed forever
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&project, project.join("lib/loop")).unwrap();
+
+    let templates = directory.path().join("templates");
+    let dir = import(&project, templates.to_str().unwrap(), "paper").unwrap();
+
+    let template = Template::load(&dir).unwrap();
+    let files: Vec<&str> = template.files.iter().map(|file| file.asset.path.as_str()).collect();
+    let variables: Vec<(&str, Option<&str>)> = template.variables.iter().map(|variable| (variable.name.as_str(), variable.default.as_deref())).collect();
+    assert_eq!(files, ["main.tex"]);
+    assert_eq!(variables[0], ("title", Some("Real title")));
+    assert!(variables.iter().all(|(name, _)| *name != "author"));
+    assert_eq!(std::fs::read_to_string(dir.join("main.tex")).unwrap(), "% \\title{Draft}\n\\title{{{title}}} % \\author{Nobody}\n");
+}
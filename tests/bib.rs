@@ -0,0 +1,97 @@
+//! Add references to the bibliography of a project and check its citations
+
+use lth::bib::{check, entries_of, parse, Bibliography, Identifier, LocalResolver, Resolver, WebResolver};
+use lth::MemoryFetcher;
+
+const BIBLIOGRAPHY: &str = r#"% Add the references of the document here, cite them with \cite{key} or [@key]
+@book{knuth1984,
+  author    = {Donald E. Knuth},
+  title     = {The {\TeX}book},
+  publisher = {Addison-Wesley},
+  year      = {1984},
+}
+"#;
+
+const CROSSREF: &str = r#" @article{Dijkstra_1968, title={Go To Statement Considered Harmful}, volume={11},
+ DOI={10.1145/362929.362947}, journal={Communications of the ACM}, author={Dijkstra, Edsger W.},
+ year={1968}, month=mar, pages={147--148} }"#;
+
+#[test]
+fn entries_are_parsed_and_identified() {
+    let entries = parse(&format!("{}\n@string{{acm = \"ACM\"}}\n@misc{{x, title = \"A \" # {{B}}, year = 2020}}", BIBLIOGRAPHY)).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].field("title"), Some("The {\\TeX}book"));
+    assert_eq!(entries[0].normalized_key(), "knuth1984texbook");
+    assert_eq!(entries[1].field("title"), Some("A B"));
+    assert_eq!(parse("@book{knuth,\n  title = {Unclosed").unwrap_err(), "Line 1: the field title of knuth has no value");
+
+    assert_eq!(Identifier::parse("https://doi.org/10.1145/362929.362947"), Some(Identifier::Doi("10.1145/362929.362947".to_string())));
+    assert_eq!(Identifier::parse("arXiv:1706.03762v5"), Some(Identifier::Arxiv("1706.03762v5".to_string())));
+    assert_eq!(Identifier::parse("hep-th/9901001"), Some(Identifier::Arxiv("hep-th/9901001".to_string())));
+    assert_eq!(Identifier::parse("smith2020"), None);
+}
+
+#[test]
+fn references_are_resolved_and_deduplicated() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("lib/bibliography.bib");
+    std::fs::create_dir(directory.path().join("lib")).unwrap();
+    std::fs::write(&path, BIBLIOGRAPHY).unwrap();
+
+    // The local database answers first, the web resolver is a stand-in with the answer of Crossref
+    let database = directory.path().join("references.bib");
+    std::fs::write(&database, "@article{attention, author = {Vaswani, Ashish}, title = {Attention Is All You Need}, year = {2017}, eprint = {1706.03762}}").unwrap();
+    let mut fetcher = MemoryFetcher::new();
+    fetcher.insert("https://api.crossref.org/works/10.1145/362929.362947/transform/application/x-bibtex", CROSSREF);
+    let local = LocalResolver::new(&database);
+    let web = WebResolver::new(&fetcher);
+    let resolvers: [&dyn Resolver; 2] = [&local, &web];
+
+    let mut bibliography = Bibliography::load(&path).unwrap();
+    for source in ["doi:10.1145/362929.362947", "arXiv:1706.03762v2"] {
+        for entry in entries_of(source, &resolvers).unwrap() {
+            bibliography.add(entry).unwrap();
+        }
+    }
+    assert!(entries_of("10.1000/unknown", &[&local]).unwrap_err().starts_with("No resolver knows the DOI 10.1000/unknown"));
+
+    // The same work is detected by its DOI, its arXiv id or its title and year
+    let duplicate = entries_of("@misc{other, doi = {https://doi.org/10.1145/362929.362947}}", &[]).unwrap();
+    assert_eq!(bibliography.add(duplicate[0].clone()).unwrap_err(), "other is already in the bibliography as dijkstra1968go");
+    let duplicate = entries_of("@book{tex, title = {The {\\TeX}book}, year = {1984}}", &[]).unwrap();
+    assert!(bibliography.add(duplicate[0].clone()).is_err());
+
+    // A different work with the same key gets a letter
+    let homonyms = entries_of("@misc{x, author = {Knuth, D.}, title = {TeXbook companion}, year = {1984}} @misc{y, author = {Knuth, D.}, title = {TeXbook exercises}, year = {1984}}", &[]).unwrap();
+    assert_eq!(bibliography.add(homonyms[0].clone()).unwrap(), "knuth1984texbook");
+    assert_eq!(bibliography.add(homonyms[1].clone()).unwrap(), "knuth1984texbooka");
+    bibliography.save().unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(content.starts_with(BIBLIOGRAPHY));
+    assert!(content.contains("@article{dijkstra1968go,\n  title   = {Go To Statement Considered Harmful},\n"));
+    assert!(content.contains("@article{vaswani2017attention,"));
+    assert_eq!(parse(&content).unwrap().len(), 5);
+}
+
+#[test]
+fn citations_are_checked_against_the_bibliography() {
+    let directory = tempfile::tempdir().unwrap();
+    let project = directory.path();
+    std::fs::create_dir_all(project.join("src")).unwrap();
+    std::fs::create_dir_all(project.join("build")).unwrap();
+    std::fs::write(project.join("main.tex"), "% \\cite{commented}\n\\citep[see][p.~2]{knuth1984, lamport1994}\n").unwrap();
+    std::fs::write(project.join("src/01.md"), "See @fig:plot and [@knuth1984; @smith2020, p. 3], or write to me@example.com.\n").unwrap();
+    std::fs::write(project.join("build/main.tex"), "\\cite{built}\n").unwrap();
+    std::fs::write(project.join("refs.bib"), format!("{}\n@misc{{unused, title = {{Never cited}}}}\n", BIBLIOGRAPHY)).unwrap();
+
+    let bibliography = Bibliography::load(&project.join("refs.bib")).unwrap();
+    let report = check(project, &bibliography);
+    let missing: Vec<(&str, &str, usize)> = report.missing.iter().map(|citation| (citation.key.as_str(), citation.file.as_str(), citation.line)).collect();
+    assert_eq!(missing, [("lamport1994", "main.tex", 2), ("smith2020", "src/01.md", 1)]);
+    assert_eq!(report.unused, ["unused"]);
+
+    // \nocite{*} cites every entry
+    std::fs::write(project.join("main.tex"), "\\nocite{*}\n").unwrap();
+    assert!(check(project, &bibliography).unused.is_empty());
+}
@@ -0,0 +1,123 @@
+//! Check the folder of a new project before creating it,
+//! and create projects in existing folders with --force and --merge
+
+mod common;
+
+use lth::git::init_git;
+use lth::preflight::{check_target, Collision};
+use lth::{LocalFetcher, MemoryFetcher, Scaffolder, TemplateRegistry};
+
+use common::fixtures;
+
+#[test]
+fn the_existing_folders_need_force_or_merge() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().to_str().unwrap();
+    std::fs::create_dir(directory.path().join("empty")).unwrap();
+    std::fs::create_dir(directory.path().join("full")).unwrap();
+    std::fs::write(directory.path().join("full/notes.txt"), "mine").unwrap();
+
+    assert!(check_target(path, "new", Collision::Abort).is_ok());
+    assert!(check_target(path, "empty", Collision::Abort).unwrap_err().contains("--force"));
+    assert!(check_target(path, "empty", Collision::Force).is_ok());
+    assert!(check_target(path, "full", Collision::Force).unwrap_err().contains("--merge"));
+    assert!(check_target(path, "full", Collision::Merge).is_ok());
+    assert!(check_target(path, "a/b", Collision::Abort).is_err());
+}
+
+#[test]
+fn rejects_an_existing_folder_by_default() {
+    let template = TemplateRegistry::builtin().resolve("math").unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    std::fs::create_dir(directory.path().join("math")).unwrap();
+
+    let result = Scaffolder::new(template)
+        .path(directory.path().to_str().unwrap())
+        .fetcher(MemoryFetcher::new())
+        .create();
+
+    assert!(result.unwrap_err().contains("--force"));
+}
+
+#[test]
+fn merge_keeps_the_existing_files() {
+    let template = TemplateRegistry::builtin().resolve("math").unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    let root = directory.path().join("notes");
+    std::fs::create_dir(&root).unwrap();
+    std::fs::write(root.join("main.tex"), "mine").unwrap();
+
+    let fetcher = LocalFetcher::new(template.source.clone(), fixtures().join("math"));
+    let project = Scaffolder::new(template)
+        .path(directory.path().to_str().unwrap())
+        .name("notes")
+        .answer("git", "no")
+        .collision(Collision::Merge)
+        .fetcher(fetcher)
+        .create()
+        .unwrap();
+
+    assert_eq!(project.skipped, vec!["main.tex".to_string()]);
+    assert_eq!(std::fs::read_to_string(root.join("main.tex")).unwrap(), "mine");
+}
+
+#[test]
+fn merge_only_commits_the_template_files() {
+    let template = TemplateRegistry::builtin().resolve("math").unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    let root = directory.path().join("notes");
+    std::fs::create_dir(&root).unwrap();
+
+    // A repository with a commit, a modified file and a file that isn't tracked
+    std::fs::write(root.join("notes.txt"), "first").unwrap();
+    init_git(root.to_str().unwrap(), &[], Collision::Abort).unwrap();
+    std::fs::write(root.join("notes.txt"), "second").unwrap();
+    std::fs::write(root.join("draft.txt"), "wip").unwrap();
+
+    let fetcher = LocalFetcher::new(template.source.clone(), fixtures().join("math"));
+    let project = Scaffolder::new(template)
+        .path(directory.path().to_str().unwrap())
+        .name("notes")
+        .answer("git", "yes")
+        .collision(Collision::Merge)
+        .fetcher(fetcher)
+        .create()
+        .unwrap();
+
+    let repo = git2::Repository::open(&root).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(Some(head.id().to_string()), project.git_commit);
+    assert_eq!(head.message(), Some("Add the template files"));
+    assert!(head.tree().unwrap().get_path(std::path::Path::new("main.tex")).is_ok());
+
+    // The work of the user stays out of the commit
+    assert_eq!(repo.status_file(std::path::Path::new("notes.txt")).unwrap(), git2::Status::WT_MODIFIED);
+    assert_eq!(repo.status_file(std::path::Path::new("draft.txt")).unwrap(), git2::Status::WT_NEW);
+}
+
+#[test]
+fn merge_into_a_folder_without_git_only_commits_the_template_files() {
+    let template = TemplateRegistry::builtin().resolve("math").unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    let root = directory.path().join("notes");
+    std::fs::create_dir(&root).unwrap();
+    std::fs::write(root.join("notes.txt"), "mine").unwrap();
+
+    let fetcher = LocalFetcher::new(template.source.clone(), fixtures().join("math"));
+    let project = Scaffolder::new(template)
+        .path(directory.path().to_str().unwrap())
+        .name("notes")
+        .answer("git", "yes")
+        .collision(Collision::Merge)
+        .fetcher(fetcher)
+        .create()
+        .unwrap();
+
+    let repo = git2::Repository::open(&root).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(Some(head.id().to_string()), project.git_commit);
+    assert_eq!(head.message(), Some("Initial commit"));
+    assert!(head.tree().unwrap().get_path(std::path::Path::new("main.tex")).is_ok());
+    assert!(head.tree().unwrap().get_path(std::path::Path::new("notes.txt")).is_err());
+    assert_eq!(repo.status_file(std::path::Path::new("notes.txt")).unwrap(), git2::Status::WT_NEW);
+}
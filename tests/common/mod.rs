@@ -0,0 +1,155 @@
+//! Helpers shared by the integration tests: a local fixture server, the snapshots of the created trees
+//! and the template published by the publish and serve tests
+
+// Each test crate only uses some of the helpers
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+/// Folder with the files of the built-in templates, served by the fixture server
+pub fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/templates")
+}
+
+/// Start a HTTP server on a random local port that serves the fixtures
+/// The server runs until the test process ends
+///
+/// # Returns
+/// The base URL of the server, like `http://127.0.0.1:41234`
+pub fn serve_fixtures() -> String {
+    isolate_network();
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to start the fixture server");
+    let address = listener.local_addr().expect("Failed to read the fixture server address");
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = handle(stream);
+        }
+    });
+
+    format!("http://{}", address)
+}
+
+/// Make the HTTP client of the tests independent of the configuration and the proxies of the machine
+/// The tests run in parallel threads, so the environment is only changed by the first call
+pub fn isolate_network() {
+    static ISOLATE: Once = Once::new();
+
+    ISOLATE.call_once(|| {
+        std::env::set_var("LTH_CONFIG", fixtures().join("missing-config.toml"));
+        std::env::set_var("NO_PROXY", "127.0.0.1");
+    });
+}
+
+/// Answer one request of the fixture server, GET and HEAD are supported
+fn handle(mut stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+
+    // Skip the headers, the body of GET and HEAD requests is empty
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("/").trim_start_matches('/');
+
+    let file = fixtures().join(path);
+    let body = match path.split('/').any(|component| component == "..") {
+        true => None,
+        false => std::fs::read(&file).ok(),
+    };
+
+    match body {
+        Some(body) => {
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len())?;
+            if method != "HEAD" {
+                stream.write_all(&body)?;
+            }
+        },
+        None => write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?,
+    }
+
+    stream.flush()
+}
+
+/// Describe the tree of a folder, one line per entry, sorted by path
+/// Folders end with a slash, files have their size and `+x` when they are executable
+/// The `.git` folder is skipped
+pub fn describe_tree(root: &Path) -> String {
+    let mut lines = Vec::new();
+    walk(root, root, &mut lines);
+    lines.sort();
+    lines.join("\n") + "\n"
+}
+
+/// Add the entries of a folder to the description of the tree
+fn walk(root: &Path, directory: &Path, lines: &mut Vec<String>) {
+    for entry in std::fs::read_dir(directory).expect("Failed to read the created folder").flatten() {
+        let path = entry.path();
+        let relative = path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/");
+
+        if relative == ".git" {
+            continue;
+        }
+
+        if path.is_dir() {
+            lines.push(format!("{}/", relative));
+            walk(root, &path, lines);
+            continue;
+        }
+
+        let metadata = entry.metadata().expect("Failed to read the created file");
+        let executable = is_executable(&metadata);
+        lines.push(format!("{} {}{}", relative, metadata.len(), if executable { " +x" } else { "" }));
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// Compare a tree with its snapshot in tests/snapshots
+/// Run the tests with `UPDATE_SNAPSHOTS=1` to write the snapshots again
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots").join(format!("{}.txt", name));
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, actual).expect("Failed to write the snapshot");
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("Missing snapshot {}, run the tests with UPDATE_SNAPSHOTS=1", path.display()));
+
+    assert_eq!(expected, actual, "The tree of {} doesn't match its snapshot", name);
+}
+
+/// Return the manifest of the thesis template, published by the publish and serve tests
+pub fn thesis_manifest(version: &str) -> String {
+    format!("name = \"thesis\"\ndescription = \"Thesis of the lab\"\nversion = \"{}\"\ntags = [\"thesis\"]\nlanguages = [\"en\"]\n\n[[files]]\npath = \"main.tex\"\n", version)
+}
+
+/// Write the thesis template in a folder, with a main.tex file that says its version
+pub fn write_template(folder: &Path, version: &str) {
+    std::fs::create_dir_all(folder).unwrap();
+    std::fs::write(folder.join("template.toml"), thesis_manifest(version)).unwrap();
+    std::fs::write(folder.join("main.tex"), format!("% {}\n", version)).unwrap();
+}
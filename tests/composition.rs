@@ -0,0 +1,97 @@
+//! Compose templates with `extends` and layers
+
+use lth::{MemoryFetcher, Scaffolder, Template, TemplateRegistry};
+
+#[test]
+fn extended_templates_inherit_and_override() {
+    let mut registry = TemplateRegistry::builtin();
+    registry.add_manifest(r#"
+        name = "notes"
+        description = "Math notes in markdown"
+        source = "https://example.com/notes"
+        extends = "math"
+        layers = ["pandoc-pipeline", "git-setup"]
+
+        [[files]]
+        path = "main.tex"
+        content = "\\input{lib/preamble}"
+    "#).unwrap();
+
+    let template = registry.resolve("notes").unwrap();
+    let file = |path: &str| template.files.iter().find(|file| file.asset.path == path).unwrap();
+
+    // Own files replace the inherited ones, inherited files keep the source of their template
+    assert_eq!(file("main.tex").asset.content.as_deref(), Some("\\input{lib/preamble}"));
+    assert_eq!(file("lib/preamble.tex").asset.url(template.base_of(file("lib/preamble.tex"))), "https://raw.githubusercontent.com/Johanx22x/latex-templates/main/math/preamble.tex");
+    assert!(file(".gitignore").asset.content.as_deref().unwrap().contains("build/"));
+    assert_eq!(template.backend().name(), "pandoc");
+    assert!(template.folders.contains(&"build".to_string()));
+    assert!(template.requirements.executables.iter().any(|requirement| requirement.name == "pandoc"));
+
+    let variables: Vec<&str> = template.variables.iter().map(|variable| variable.name.as_str()).collect();
+    assert_eq!(variables, ["git", "readme", "title", "author"]);
+
+    // The layers render their placeholders with the answers
+    let directory = tempfile::tempdir().unwrap();
+    let mut fetcher = MemoryFetcher::new();
+    for file in template.files.iter().filter(|file| file.asset.content.is_none()) {
+        fetcher.insert(file.asset.url(template.base_of(file)), "");
+    }
+
+    Scaffolder::new(template)
+        .path(directory.path().to_str().unwrap())
+        .answer("title", "Algebra")
+        .answer("git", "no")
+        .fetcher(fetcher)
+        .create()
+        .unwrap();
+
+    let metadata = std::fs::read_to_string(directory.path().join("notes/metadata.yaml")).unwrap();
+    assert_eq!(metadata, "---\ntitle: \"Algebra\"\nauthor: \"Author\"\n---\n");
+}
+
+#[test]
+fn builtins_share_the_layers() {
+    let registry = TemplateRegistry::builtin();
+    let ieee = registry.resolve("ieee").unwrap();
+    let apa7tec = registry.resolve("apa7tec").unwrap();
+    let math = registry.resolve("math").unwrap();
+
+    // The files of the layers are replaced by the ones of the templates, read from their sources
+    let from_source = |template: &Template, path: &str| {
+        let file = template.files.iter().find(|file| file.asset.path == path).unwrap();
+        file.asset.content.is_none() && template.base_of(file) == template.source
+    };
+
+    for template in [&ieee, &apa7tec, &math] {
+        assert!(template.variables.iter().any(|variable| variable.name == "git"), "{} doesn't use git-setup", template.name);
+        assert!(from_source(template, ".gitignore"));
+    }
+    for template in [&ieee, &apa7tec] {
+        assert_eq!(template.backend().name(), "pandoc");
+        assert!(template.requirements.executables.iter().any(|requirement| requirement.name == "make"));
+        for path in ["metadata.yaml", "lib/bibliography.bib", "Makefile"] {
+            assert!(from_source(template, path), "{} doesn't read {} from its source", template.name, path);
+        }
+    }
+    assert_eq!(math.backend().name(), "latex");
+
+    // A template can switch a pandoc parent back to LaTeX
+    let mut registry = registry;
+    registry.add_manifest("name = \"slides\"\ndescription = \"\"\nextends = \"ieee\"\nbackend = \"latex\"").unwrap();
+    assert_eq!(registry.resolve("slides").unwrap().backend().name(), "latex");
+}
+
+#[test]
+fn broken_compositions_are_reported() {
+    let mut registry = TemplateRegistry::builtin();
+    registry.add_manifest("name = \"a\"\ndescription = \"\"\nextends = \"b\"").unwrap();
+    registry.add_manifest("name = \"b\"\ndescription = \"\"\nextends = \"a\"").unwrap();
+    registry.add_manifest("name = \"c\"\ndescription = \"\"\nlayers = [\"nope\"]").unwrap();
+    registry.add_manifest("name = \"d\"\ndescription = \"\"\nextends = \"nope\"").unwrap();
+
+    assert_eq!(registry.resolve("a").unwrap_err(), "The template a extends itself: a -> b -> a");
+    assert_eq!(registry.resolve("c").unwrap_err(), "The template c uses the unknown layer nope");
+    assert_eq!(registry.resolve("d").unwrap_err(), "The template d extends the unknown template nope");
+    assert_eq!(registry.resolved().len(), 4);
+}
@@ -0,0 +1,24 @@
+//! Read the versions reported by the tools and compare them with the requirements
+
+use lth::doctor::{extract_version, is_at_least};
+
+#[test]
+fn versions_are_extracted_from_the_version_lines() {
+    assert_eq!(extract_version("pandoc 3.1.2").as_deref(), Some("3.1.2"));
+    assert_eq!(extract_version("pdfTeX 3.141592653-2.6-1.40.25 (TeX Live 2023)").as_deref(), Some("3.141592653"));
+    assert_eq!(extract_version("git version 2.43.0").as_deref(), Some("2.43.0"));
+    assert_eq!(extract_version("GNU Make 4.4.1").as_deref(), Some("4.4.1"));
+    assert_eq!(extract_version("pandoc-crossref v0.3.17.0 git commit").as_deref(), Some("0.3.17.0"));
+    assert_eq!(extract_version("latexmk, John Collins, version 4"), None);
+}
+
+#[test]
+fn versions_are_compared_by_their_numbers() {
+    assert!(is_at_least("2.19.2", "2.11"));
+    assert!(is_at_least("2.11", "2.11"));
+    assert!(is_at_least("3.0", "2.19.2"));
+    assert!(!is_at_least("2.9", "2.11"));
+    assert!(is_at_least("1.8b", "1.8"));
+    assert!(is_at_least("2020/01/01", "2020/01/01"));
+    assert!(!is_at_least("2019/12/31", "2020/01/01"));
+}
@@ -0,0 +1,63 @@
+//! Create projects without any server, using the local and in-memory fetchers
+
+mod common;
+
+use lth::{LocalFetcher, MemoryFetcher, Scaffolder, TemplateRegistry};
+
+use common::{assert_snapshot, describe_tree, fixtures};
+
+#[test]
+fn local_fetcher_creates_the_same_tree() {
+    let template = TemplateRegistry::builtin().resolve("ieee").unwrap();
+    let directory = tempfile::tempdir().unwrap();
+
+    let fetcher = LocalFetcher::new(template.source.clone(), fixtures().join("ieee"));
+    Scaffolder::new(template)
+        .path(directory.path().to_str().unwrap())
+        .name("project")
+        .fetcher(fetcher)
+        .create()
+        .unwrap();
+
+    assert_snapshot("ieee", &describe_tree(&directory.path().join("project")));
+}
+
+#[test]
+fn local_fetcher_stays_inside_its_folder() {
+    let fetcher = LocalFetcher::new("https://example.com/math", fixtures().join("math"));
+
+    assert!(fetcher.path_of("https://example.com/math/main.tex").is_ok());
+    assert!(fetcher.path_of("https://example.com/math/../ieee/main.tex").is_err());
+    assert!(fetcher.path_of("https://example.org/math/main.tex").is_err());
+}
+
+#[test]
+fn memory_fetcher_reports_the_missing_files() {
+    let template = TemplateRegistry::builtin().resolve("math").unwrap();
+    let directory = tempfile::tempdir().unwrap();
+
+    let mut fetcher = MemoryFetcher::new();
+    fetcher.insert(format!("{}/main.tex", template.source), "\\documentclass{report}\n");
+
+    let result = Scaffolder::new(template)
+        .path(directory.path().to_str().unwrap())
+        .answer("git", "no")
+        .fetcher(fetcher)
+        .create();
+
+    let err = result.unwrap_err();
+    assert!(err.contains("preamble.tex"), "{}", err);
+}
+
+#[test]
+fn rejects_unknown_variables_and_invalid_answers() {
+    let template = TemplateRegistry::builtin().resolve("math").unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().to_str().unwrap();
+
+    let unknown = Scaffolder::new(template.clone()).path(path).answer("colour", "blue").plan();
+    assert!(unknown.unwrap_err().contains("colour"));
+
+    let invalid = Scaffolder::new(template).path(path).answer("git", "maybe").plan();
+    assert!(invalid.is_err());
+}
@@ -0,0 +1,29 @@
+//! Rank the templates of the picker with a fuzzy match
+
+use lth::fuzzy::{best_score, score};
+
+#[test]
+fn queries_match_in_order() {
+    assert_eq!(score("", "math"), Some(0));
+    assert!(score("ieee", "ieeetec").is_some());
+    assert!(score("IEE tec", "ieeetec").is_some());
+    assert!(score("xyz", "math").is_none());
+    assert!(score("tam", "math").is_none());
+    assert!(score("mathx", "math").is_none());
+}
+
+#[test]
+fn closer_matches_score_higher() {
+    // Consecutive characters beat scattered ones
+    assert!(score("ieee", "ieee").unwrap() > score("ieee", "i-e-e-e").unwrap());
+    // The start of a word beats the middle of one
+    assert!(score("tec", "ieee tec").unwrap() > score("tec", "ieeetec").unwrap());
+    // Shorter texts are closer to the query
+    assert!(score("math", "math").unwrap() > score("math", "mathematics notes for the course").unwrap());
+
+    // The weights let the name rank higher than the description
+    let name = best_score("apa", &[("apa7tec", 3), ("Custom template", 1)]).unwrap();
+    let description = best_score("apa", &[("ieee", 3), ("Template with apa citations", 1)]).unwrap();
+    assert!(name > description);
+    assert_eq!(best_score("xyz", &[("math", 3), ("Latex report", 1)]), None);
+}
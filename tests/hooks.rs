@@ -0,0 +1,101 @@
+//! Run the hooks of a template in the new project
+
+use lth::hooks::{enabled_hooks, hook_sources, run_hooks};
+use lth::plan::Plan;
+use lth::preflight::Collision;
+use lth::templates::Answers;
+use lth::Template;
+
+const MANIFEST: &str = r#"
+    name = "hooked"
+    description = "A template with hooks"
+
+    [[files]]
+    path = "main.tex"
+    content = ""
+
+    [[variables]]
+    name = "notes"
+    prompt = "Do you want notes?"
+    kind = "confirm"
+    default = "no"
+
+    [[hooks]]
+    run = "echo \"$LTH_TEMPLATE\" > hooked.txt"
+    description = "Write the name of the template"
+
+    [[hooks]]
+    run = "touch notes.txt"
+    when = "notes"
+"#;
+
+#[test]
+fn hooks_run_in_the_project_when_enabled() {
+    let template = Template::parse(MANIFEST).unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().to_str().unwrap();
+
+    let answers = template.defaults();
+    let hooks = enabled_hooks(&template, &answers);
+    assert_eq!(hooks.len(), 1);
+
+    let plan = Plan::build(&template, path, "hooked", &answers, Collision::Abort);
+    assert_eq!(plan.hooks, ["Write the name of the template (echo \"$LTH_TEMPLATE\" > hooked.txt)"]);
+
+    run_hooks(&hooks, path, &template).unwrap();
+    assert_eq!(std::fs::read_to_string(directory.path().join("hooked.txt")).unwrap(), "hooked\n");
+    assert!(!directory.path().join("notes.txt").exists());
+
+    let mut answers = Answers::new();
+    answers.insert("notes".to_string(), "yes".to_string());
+    assert_eq!(enabled_hooks(&template, &answers).len(), 2);
+}
+
+#[test]
+fn broken_hooks_are_reported() {
+    let template = Template::parse(r#"
+        name = "broken"
+        description = ""
+
+        [[hooks]]
+        run = "exit 3"
+
+        [[hooks]]
+        run = "touch never.txt"
+
+        [[hooks]]
+        run = "make"
+        action = "build"
+    "#).unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().to_str().unwrap();
+
+    // The hooks stop at the first failure
+    let err = run_hooks(&enabled_hooks(&template, &Answers::new()), path, &template).unwrap_err();
+    assert!(err.starts_with("The hook exit 3 failed"), "{}", err);
+    assert!(!directory.path().join("never.txt").exists());
+
+    assert_eq!(template.hooks[2].validate().unwrap_err(), "A hook can't have both run and action");
+}
+
+#[test]
+fn hooks_are_trusted_by_the_place_of_the_manifest() {
+    let directory = tempfile::tempdir().unwrap();
+    std::fs::write(directory.path().join("template.toml"), format!("source = \"https://example.com/thesis\"\n{}", MANIFEST)).unwrap();
+
+    // The manifest can point its files anywhere, but not choose the source the user trusts
+    let template = Template::load(directory.path()).unwrap();
+    let origin = format!("file://{}", directory.path().canonicalize().unwrap().display());
+    assert_eq!(template.source, "https://example.com/thesis");
+    assert_eq!(hook_sources(&enabled_hooks(&template, &Answers::new()), &template), [origin.as_str()]);
+}
+
+#[test]
+fn hooks_of_the_builtin_layers_are_trusted_by_their_name() {
+    let mut layer = lth::templates::layer("git-setup").unwrap();
+    layer.hooks = Template::parse(MANIFEST).unwrap().hooks;
+    assert_eq!(layer.origin(), "builtin:layer/git-setup");
+
+    let template = Template::parse("name = \"paper\"\ndescription = \"\"\nsource = \"https://example.com/paper\"").unwrap().inherit(&layer);
+    assert_eq!(hook_sources(&enabled_hooks(&template, &Answers::new()), &template), ["builtin:layer/git-setup"]);
+}
@@ -0,0 +1,56 @@
+//! Choose the proxy of each request and read the CA bundles of the HTTP client
+
+use std::path::Path;
+
+use lth::network::{build_client, host_matches, read_ca_bundle, ProxyRoutes};
+use lth::settings::NetworkSettings;
+use reqwest::Url;
+
+/// Folder with the certificates of the tests
+fn certificates() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/network")
+}
+
+#[test]
+fn no_proxy_entries_match_the_hosts() {
+    assert!(host_matches("example.com", "example.com"));
+    assert!(host_matches("files.example.com", ".example.com"));
+    assert!(host_matches("files.example.com", "example.com"));
+    assert!(host_matches("Files.Example.COM", "example.com"));
+    assert!(host_matches("lab-server", "lab-server:8080"));
+    assert!(host_matches("anything", "*"));
+    assert!(!host_matches("example.org", "example.com"));
+    assert!(!host_matches("badexample.com", "example.com"));
+}
+
+#[test]
+fn each_scheme_uses_its_proxy() {
+    let routes = ProxyRoutes {
+        http: Some(Url::parse("http://http-proxy:3128").unwrap()),
+        https: Some(Url::parse("http://https-proxy:3128").unwrap()),
+        bypass: vec![".university.edu".to_string()],
+    };
+
+    let route = |target: &str| routes.route(&Url::parse(target).unwrap()).map(|url| url.host_str().unwrap().to_string());
+    assert_eq!(route("https://raw.githubusercontent.com/a").as_deref(), Some("https-proxy"));
+    assert_eq!(route("http://lab-server:8080/index.toml").as_deref(), Some("http-proxy"));
+    assert_eq!(route("https://files.university.edu/a"), None);
+
+    // Without HTTP_PROXY the LAN servers are reached directly
+    let routes = ProxyRoutes { http: None, ..routes };
+    assert_eq!(routes.route(&Url::parse("http://lab-server:8080/index.toml").unwrap()), None);
+}
+
+#[test]
+fn ca_bundles_are_read() {
+    assert_eq!(read_ca_bundle(&certificates().join("ca.pem")).unwrap().len(), 1);
+
+    let directory = tempfile::tempdir().unwrap();
+    let empty = directory.path().join("empty.pem");
+    std::fs::write(&empty, "no certificate here\n").unwrap();
+    assert!(read_ca_bundle(&empty).unwrap_err().starts_with("No certificates found in"));
+    assert!(read_ca_bundle(&directory.path().join("missing.pem")).unwrap_err().starts_with("Failed to read the CA bundle"));
+
+    let settings = NetworkSettings { ca_bundles: vec![certificates().join("ca.pem")], ..NetworkSettings::default() };
+    assert!(build_client(&settings).is_ok());
+}
@@ -0,0 +1,30 @@
+//! Parse the answers of the typed prompts
+
+use lth::prompt::{parse_confirm, parse_option, parse_options};
+
+/// Options of the select questions
+fn options() -> Vec<String> {
+    ["ieee", "apa", "chicago"].iter().map(|option| option.to_string()).collect()
+}
+
+#[test]
+fn yes_or_no_answers_are_parsed() {
+    assert_eq!(parse_confirm("Y"), Ok(true));
+    assert_eq!(parse_confirm("yes"), Ok(true));
+    assert_eq!(parse_confirm("False"), Ok(false));
+    assert_eq!(parse_confirm("maybe"), Err("Invalid answer 'maybe', answer yes or no".to_string()));
+}
+
+#[test]
+fn options_are_chosen_by_number_or_text() {
+    let options = options();
+    assert_eq!(parse_option("2", &options), Ok(1));
+    assert_eq!(parse_option(" Chicago ", &options), Ok(2));
+    assert_eq!(parse_option("0", &options), Err("Invalid option '0', choose one of: ieee, apa, chicago".to_string()));
+    assert!(parse_option("4", &options).is_err());
+
+    // The repeated options are kept once, in the order of the answer
+    assert_eq!(parse_options("3, ieee,3,", &options), Ok(vec![2, 0]));
+    assert_eq!(parse_options("None", &options), Ok(Vec::new()));
+    assert!(parse_options("1,mla", &options).unwrap_err().contains("'mla'"));
+}
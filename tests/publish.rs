@@ -0,0 +1,99 @@
+//! Publish new versions of a template to a git registry and to `lth serve`
+
+mod common;
+
+use std::path::Path;
+
+use lth::index::Index;
+use lth::publish::{publish, set_version, Bump, Target};
+use lth::serve::Server;
+use lth::versions::{checkout, sha256};
+use lth::SourceFetcher;
+
+use common::{isolate_network, thesis_manifest, write_template};
+
+#[test]
+fn versions_are_bumped_in_the_manifest() {
+    assert_eq!(Bump::Minor.apply(&semver::Version::new(1, 2, 3)).to_string(), "1.3.0");
+    assert_eq!(Bump::Major.apply(&semver::Version::new(1, 2, 3)).to_string(), "2.0.0");
+    assert_eq!(set_version(&thesis_manifest("1.0.0"), "1.0.1").unwrap(), thesis_manifest("1.0.1"));
+    assert_eq!(set_version("name = \"a\"\n[[files]]\nversion = \"x\"\n", "0.1.0").unwrap(), "name = \"a\"\nversion = \"0.1.0\"\n[[files]]\nversion = \"x\"\n");
+
+    assert_eq!(Target::parse("https://example.com/lab/templates.git"), Target::Git("https://example.com/lab/templates.git".to_string()));
+    assert_eq!(Target::parse("http://lab-server:8080/"), Target::Server("http://lab-server:8080".to_string()));
+}
+
+#[test]
+fn templates_are_published_to_a_git_registry() {
+    let directory = tempfile::tempdir().unwrap();
+    let template = directory.path().join("thesis");
+    write_template(&template, "1.0.0");
+    let registry = directory.path().join("registry.git");
+    git2::Repository::init_bare(&registry).unwrap();
+    let target = Target::Git(registry.display().to_string());
+
+    // A dry run doesn't change the manifest
+    let package = publish(&template, Bump::Minor, None, None).unwrap();
+    assert_eq!(package.version, "1.1.0");
+    assert_eq!(std::fs::read_to_string(template.join("template.toml")).unwrap(), thesis_manifest("1.0.0"));
+
+    let package = publish(&template, Bump::Minor, Some(&target), None).unwrap();
+    assert_eq!(package.file, "thesis-1.1.0.tar.gz");
+    assert_eq!(package.sha256, sha256(&package.archive));
+    assert!(std::fs::read_to_string(template.join("template.toml")).unwrap().contains("version = \"1.1.0\""));
+
+    // The registry has the tag, the files and the archive with its checksum
+    let clone = directory.path().join("clone");
+    let repo = git2::Repository::clone(registry.to_str().unwrap(), &clone).unwrap();
+    assert!(repo.find_reference("refs/tags/thesis-v1.1.0").is_ok());
+    assert_eq!(std::fs::read_to_string(clone.join("thesis/main.tex")).unwrap(), "% 1.0.0\n");
+    assert!(std::fs::read_to_string(clone.join("archives/thesis-1.1.0.tar.gz.sha256")).unwrap().starts_with(&package.sha256));
+
+    // The same version can't be published twice, and the manifest is restored
+    let err = publish(&template, Bump::None, Some(&target), None).unwrap_err();
+    assert!(err.contains("already published"), "{}", err);
+    assert!(std::fs::read_to_string(template.join("template.toml")).unwrap().contains("version = \"1.1.0\""));
+}
+
+#[test]
+fn templates_are_uploaded_to_a_server() {
+    isolate_network();
+    let directory = tempfile::tempdir().unwrap();
+    let template = directory.path().join("thesis");
+    write_template(&template, "1.0.0");
+    let served = directory.path().join("served");
+    std::fs::create_dir(&served).unwrap();
+
+    let server = Server::bind(&served, "127.0.0.1:0").unwrap().with_token("secret");
+    let url = server.url();
+    std::thread::spawn(move || server.run());
+    let target = Target::Server(url.clone());
+
+    // Without the token the server refuses the archive
+    let err = publish(&template, Bump::Patch, Some(&target), Some("guess")).unwrap_err();
+    assert!(err.contains("401"), "{}", err);
+    assert_eq!(std::fs::read_to_string(template.join("template.toml")).unwrap(), thesis_manifest("1.0.0"));
+
+    publish(&template, Bump::Patch, Some(&target), Some("secret")).unwrap();
+    std::fs::write(template.join("main.tex"), "% Thesis, second version\n").unwrap();
+    publish(&template, Bump::Patch, Some(&target), Some("secret")).unwrap();
+
+    // The index has the new version, the older one is a release checked with its checksum
+    let index = Index::fetch(&format!("{}/index.toml", url), &SourceFetcher).unwrap();
+    assert_eq!(index.templates[0].version.as_deref(), Some("1.0.2"));
+    let release = &index.templates[0].releases[0];
+    assert_eq!(release.version, "1.0.1");
+    assert!(release.sha256.is_some());
+    assert_eq!(std::fs::read_to_string(served.join("thesis/main.tex")).unwrap(), "% Thesis, second version\n");
+
+    let manifest = index.templates[0].template(&SourceFetcher).unwrap();
+    let old = checkout(&manifest, release, &directory.path().join("cache")).unwrap();
+    let old = old.source.strip_prefix("file://").unwrap().to_string();
+    assert_eq!(std::fs::read_to_string(Path::new(&old).join("main.tex")).unwrap(), "% 1.0.0\n");
+
+    // A release with a wrong checksum is not used
+    let mut tampered = release.clone();
+    tampered.sha256 = Some("0".repeat(64));
+    let err = checkout(&manifest, &tampered, &directory.path().join("other-cache")).unwrap_err();
+    assert!(err.contains("checksum"), "{}", err);
+}
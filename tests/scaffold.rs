@@ -0,0 +1,168 @@
+//! Scaffold every built-in template against the local fixture server
+//! and check the created trees with their snapshots
+
+mod common;
+
+use lth::plan::format_size;
+use lth::{Event, HttpFetcher, Scaffolder, Template, TemplateRegistry};
+
+use common::{assert_snapshot, describe_tree, serve_fixtures};
+
+/// Return a built-in template with its files served by the fixture server
+fn served(name: &str, base: &str) -> Template {
+    let mut template = TemplateRegistry::builtin().resolve(name).expect("Missing built-in template");
+    template.source = format!("{}/{}", base, name);
+    template
+}
+
+/// Create a project with the default answers and check it with its snapshot
+fn scaffold_builtin(name: &str) {
+    let base = serve_fixtures();
+    let directory = tempfile::tempdir().unwrap();
+    let mut events = Vec::new();
+
+    let project = Scaffolder::new(served(name, &base))
+        .path(directory.path().to_str().unwrap())
+        .name("project")
+        .fetcher(HttpFetcher)
+        .on_event(|event| events.push(event.clone()))
+        .create()
+        .unwrap_or_else(|err| panic!("Failed to create the {} project: {}", name, err));
+
+    let root = directory.path().join("project");
+    assert_snapshot(name, &describe_tree(&root));
+
+    // Every template asks for a git repository, yes by default
+    assert!(root.join(".git").is_dir());
+    assert!(project.git_commit.is_some());
+    assert!(events.iter().any(|event| matches!(event, Event::Commit { .. })));
+    assert!(project.skipped.is_empty());
+}
+
+#[test]
+fn scaffolds_math() {
+    scaffold_builtin("math");
+}
+
+#[test]
+fn scaffolds_ieee() {
+    scaffold_builtin("ieee");
+}
+
+#[test]
+fn scaffolds_ieeetec() {
+    scaffold_builtin("ieeetec");
+}
+
+#[test]
+fn scaffolds_apa7tec() {
+    scaffold_builtin("apa7tec");
+}
+
+#[test]
+fn every_builtin_has_a_snapshot() {
+    for template in TemplateRegistry::builtin().templates() {
+        let snapshot = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("tests/snapshots/{}.txt", template.name));
+        assert!(snapshot.is_file(), "Missing snapshot for the {} template", template.name);
+    }
+}
+
+#[test]
+fn normalizes_text_and_keeps_binary_files() {
+    let base = serve_fixtures();
+    let directory = tempfile::tempdir().unwrap();
+
+    Scaffolder::new(served("math", &base))
+        .path(directory.path().to_str().unwrap())
+        .name("notes")
+        .answer("git", "no")
+        .create()
+        .unwrap();
+
+    let macros = std::fs::read(directory.path().join("notes/lib/macros.tex")).unwrap();
+    assert!(!macros.contains(&b'\r'));
+
+    Scaffolder::new(served("ieeetec", &base))
+        .path(directory.path().to_str().unwrap())
+        .name("paper")
+        .answer("git", "no")
+        .create()
+        .unwrap();
+
+    let logo = std::fs::read(directory.path().join("paper/figs/logo.png")).unwrap();
+    assert_eq!(logo, std::fs::read(common::fixtures().join("ieeetec/figs/logo.png")).unwrap());
+}
+
+#[test]
+fn skips_the_disabled_files() {
+    let base = serve_fixtures();
+    let directory = tempfile::tempdir().unwrap();
+
+    let project = Scaffolder::new(served("apa7tec", &base))
+        .path(directory.path().to_str().unwrap())
+        .name("essay")
+        .answer("git", "no")
+        .answer("readme", "n")
+        .create()
+        .unwrap();
+
+    let root = directory.path().join("essay");
+    assert!(!root.join(".git").exists());
+    assert!(!root.join(".gitignore").exists());
+    assert!(!root.join("README.md").exists());
+    assert_eq!(project.git_commit, None);
+}
+
+#[test]
+fn measures_the_plan_with_the_server() {
+    let base = serve_fixtures();
+    let directory = tempfile::tempdir().unwrap();
+
+    let plan = Scaffolder::new(served("ieee", &base))
+        .path(directory.path().to_str().unwrap())
+        .measured_plan()
+        .unwrap();
+
+    let png = plan.files.iter().find(|file| file.path == "images/markdown.png").unwrap();
+    assert_eq!(png.size, Some(std::fs::metadata(common::fixtures().join("ieee/images/markdown.png")).unwrap().len()));
+
+    // A plan writes nothing
+    assert!(!directory.path().join("ieee").exists());
+}
+
+#[test]
+fn formats_the_sizes_of_the_plan() {
+    assert_eq!(format_size(0), "0 B");
+    assert_eq!(format_size(1023), "1023 B");
+    assert_eq!(format_size(1024), "1.0 KB");
+    assert_eq!(format_size(2150), "2.1 KB");
+    assert_eq!(format_size(5 * 1024 * 1024 + 512 * 1024), "5.5 MB");
+}
+
+#[test]
+fn features_add_files_folders_and_lines() {
+    let base = serve_fixtures();
+    let directory = tempfile::tempdir().unwrap();
+
+    Scaffolder::new(served("math", &base))
+        .path(directory.path().to_str().unwrap())
+        .answer("git", "no")
+        .answer("figures", "no")
+        .answer("appendix", "yes")
+        .answer("bibliography", "yes")
+        .answer("glossary", "yes")
+        .fetcher(HttpFetcher)
+        .create()
+        .unwrap();
+
+    let root = directory.path().join("math");
+    let main = std::fs::read_to_string(root.join("main.tex")).unwrap();
+    let preamble = std::fs::read_to_string(root.join("lib/preamble.tex")).unwrap();
+
+    assert!(!root.join("images").exists());
+    assert!(root.join("src/appendix.tex").is_file());
+    assert!(root.join("lib/glossary.tex").is_file());
+    assert!(main.ends_with("\\appendix\n\\input{src/appendix.tex}\n\\printbibliography\n\\printglossaries\n\\end{document}\n"));
+    assert!(preamble.ends_with("\\addbibresource{lib/bibliography.bib}\n\\usepackage[acronym]{glossaries}\n\\makeglossaries\n\\loadglsentries{lib/glossary.tex}\n"));
+    assert!(!preamble.contains("listings"));
+}
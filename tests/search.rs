@@ -0,0 +1,86 @@
+//! Search the templates of the registry and of the published indexes
+
+use lth::index::{combined, Filters, Index};
+use lth::{MemoryFetcher, TemplateRegistry};
+
+const INDEX: &str = r#"
+    name = "Theses"
+
+    [[templates]]
+    name = "thesis-tec"
+    description = "Thesis of the TEC"
+    version = "2.1.0"
+    tags = ["thesis", "tec"]
+    languages = ["es"]
+    document = "thesis"
+    source = "https://example.com/templates/thesis-tec"
+
+    [[templates]]
+    name = "thesis-mit"
+    description = "Thesis of the MIT"
+    tags = ["thesis"]
+    languages = ["en"]
+    document = "thesis"
+
+    [[templates]]
+    name = "math"
+    description = "Replaced by the built-in template"
+"#;
+
+#[test]
+fn indexes_are_searched_with_filters() {
+    let index = Index::parse(INDEX).unwrap();
+    let names = |filters: &Filters, query: &str| -> Vec<String> {
+        index.search(query, filters).iter().map(|entry| entry.name.clone()).collect()
+    };
+
+    assert_eq!(names(&Filters::default(), "thesis"), ["thesis-tec", "thesis-mit"]);
+    assert_eq!(names(&Filters { language: Some("ES".to_string()), ..Filters::default() }, ""), ["thesis-tec"]);
+    assert_eq!(names(&Filters { tags: vec!["thesis".to_string(), "tec".to_string()], ..Filters::default() }, "thesis"), ["thesis-tec"]);
+    assert!(names(&Filters { document: Some("slides".to_string()), ..Filters::default() }, "").is_empty());
+
+    // The same index in JSON
+    let json = serde_json::to_string(&index).unwrap();
+    assert_eq!(Index::parse(&json).unwrap(), index);
+    assert!(Index::parse("templates = 3").unwrap_err().starts_with("Invalid index"));
+}
+
+#[test]
+fn local_templates_come_before_the_indexes() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("index.toml");
+    std::fs::write(&path, INDEX).unwrap();
+
+    let urls = vec![format!("file://{}", path.display()), "file:///missing/index.toml".to_string()];
+    let (index, errors) = combined(&TemplateRegistry::builtin(), &urls);
+
+    assert_eq!(errors.len(), 1);
+    let math: Vec<_> = index.templates.iter().filter(|entry| entry.name == "math").collect();
+    assert_eq!(math.len(), 1);
+    assert_eq!(math[0].document.as_deref(), Some("report"));
+
+    let results = index.search("", &Filters { tags: vec!["paper".to_string()], language: Some("es".to_string()), document: None });
+    let names: Vec<&str> = results.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["ieeetec", "apa7tec"]);
+    assert!(index.templates.iter().any(|entry| entry.name == "thesis-tec"));
+}
+
+#[test]
+fn remote_indexes_can_only_point_to_remote_templates() {
+    let url = "https://example.com/templates/index.toml";
+    let mut fetcher = MemoryFetcher::new();
+
+    // The relative sources start from the folder of the index
+    fetcher.insert(url, "[[templates]]\nname = \"thesis\"\nsource = \"thesis\"\n[[templates]]\nname = \"paper\"\nsource = \"https://example.org/paper\"\n");
+    let index = Index::fetch(url, &fetcher).unwrap();
+    assert_eq!(index.templates[0].source.as_deref(), Some("https://example.com/templates/thesis"));
+    assert_eq!(index.templates[1].source.as_deref(), Some("https://example.org/paper"));
+
+    fetcher.insert(url, "[[templates]]\nname = \"keys\"\nsource = \"file:///home/me/.ssh\"\n");
+    let err = Index::fetch(url, &fetcher).unwrap_err();
+    assert_eq!(err, "https://example.com/templates/index.toml: the template keys points to the local file file:///home/me/.ssh, but the index is not local");
+
+    // The archives of the releases too
+    fetcher.insert(url, "[[templates]]\nname = \"old\"\nsource = \"old\"\n[[templates.releases]]\nversion = \"1.0.0\"\narchive = \"file:///tmp/old.tar.gz\"\n");
+    assert!(Index::fetch(url, &fetcher).unwrap_err().contains("points to the local file file:///tmp/old.tar.gz"));
+}
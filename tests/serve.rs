@@ -0,0 +1,52 @@
+//! Share templates with `lth serve` and create projects from the server
+
+mod common;
+
+use lth::fetch::{Fetcher, SourceFetcher};
+use lth::index::{find_template, Index};
+use lth::serve::Server;
+use lth::versions::{checkout, pack};
+use lth::Scaffolder;
+
+use common::{isolate_network, write_template};
+
+#[test]
+fn projects_are_created_from_a_local_server() {
+    isolate_network();
+    let directory = tempfile::tempdir().unwrap();
+
+    // The current version in its folder, the old one as an archive
+    let served = directory.path().join("served");
+    write_template(&served.join("thesis"), "1.0.0");
+    write_template(&directory.path().join("old"), "0.9.0");
+    std::fs::create_dir(served.join("archives")).unwrap();
+    std::fs::write(served.join("archives/thesis-0.9.0.tar.gz"), pack(&directory.path().join("old"), "thesis-0.9.0").unwrap()).unwrap();
+
+    let server = Server::bind(&served, "127.0.0.1:0").unwrap();
+    let url = server.url();
+    std::thread::spawn(move || server.run());
+
+    // The index points to the server
+    let index = Index::fetch(&format!("{}/index.toml", url), &SourceFetcher).unwrap();
+    assert_eq!(index.templates.len(), 1);
+    assert_eq!(index.templates[0].source.as_deref(), Some(format!("{}/templates/thesis", url).as_str()));
+    assert_eq!(index.templates[0].releases[0].archive.as_deref(), Some(format!("{}/archives/thesis-0.9.0.tar.gz", url).as_str()));
+    assert_eq!(Index::fetch(&format!("{}/index.json", url), &SourceFetcher).unwrap(), index);
+
+    // The client downloads the manifest, then the files
+    let template = find_template(&[format!("{}/index.toml", url)], "thesis", &SourceFetcher).unwrap().unwrap();
+    let projects = directory.path().join("projects");
+    std::fs::create_dir(&projects).unwrap();
+    Scaffolder::new(template.clone()).path(projects.to_str().unwrap()).name("current").create().unwrap();
+    assert_eq!(std::fs::read_to_string(projects.join("current/main.tex")).unwrap(), "% 1.0.0\n");
+
+    // The old version comes from its archive
+    let old = checkout(&template, &template.releases[0], &directory.path().join("cache")).unwrap();
+    Scaffolder::new(old).path(projects.to_str().unwrap()).name("old").create().unwrap();
+    assert_eq!(std::fs::read_to_string(projects.join("old/main.tex")).unwrap(), "% 0.9.0\n");
+
+    // The current version is packed on request, the other files don't exist
+    assert!(SourceFetcher.fetch(&format!("{}/archives/thesis-1.0.0.tar.gz", url)).unwrap().starts_with(&[0x1f, 0x8b]));
+    assert!(SourceFetcher.fetch(&format!("{}/templates/archives/thesis-0.9.0.tar.gz", url)).is_err());
+    assert!(SourceFetcher.fetch(&format!("{}/templates/thesis/missing.tex", url)).is_err());
+}
@@ -0,0 +1,59 @@
+//! Choose the CSL citation style of the pandoc projects
+
+use lth::git::{commit_changes, init_git};
+use lth::preflight::Collision;
+use lth::styles::{builtin_styles, find_style, set_csl, set_style, styles_in};
+
+#[test]
+fn the_csl_field_is_replaced_or_added() {
+    assert_eq!(set_csl("title: Paper\ncsl: lib/bibliography.csl\n", "lib/apa.csl"), "title: Paper\ncsl: lib/apa.csl\n");
+    assert_eq!(set_csl("---\ntitle: Paper\n---\n", "lib/acm.csl"), "---\ntitle: Paper\ncsl: lib/acm.csl\n---\n");
+    assert_eq!(set_csl("title: Paper\nnested:\n  csl: other\n\n", "lib/acm.csl"), "title: Paper\nnested:\n  csl: other\ncsl: lib/acm.csl\n");
+
+    // Every bundled style formats the references, and the aliases find them
+    let styles = builtin_styles();
+    assert_eq!(styles.iter().map(|style| style.name.as_str()).collect::<Vec<_>>(), ["ieee", "apa", "chicago", "acm", "vancouver"]);
+    assert!(styles.iter().all(|style| style.content.contains("<bibliography")));
+    assert!(styles.iter().all(|style| !style.content.contains("zotero.org") && style.content.contains("licenses/by-sa/3.0")));
+    assert_eq!(find_style(&styles, "APA7").unwrap().title, "APA 7 (lth)");
+    assert_eq!(find_style(&styles, "chicago-author-date").unwrap().name, "chicago");
+    assert_eq!(find_style(&styles, "mla").unwrap_err(), "There is no style called mla (available: ieee, apa, chicago, acm, vancouver)");
+}
+
+#[test]
+fn styles_are_installed_in_the_project() {
+    let directory = tempfile::tempdir().unwrap();
+    let local = directory.path().join("styles");
+    std::fs::create_dir(&local).unwrap();
+    std::fs::write(local.join("apa.csl"), "<style><info><title>APA (lab)</title></info></style>").unwrap();
+    std::fs::write(local.join("harvard.csl"), "<style/>").unwrap();
+
+    // The local styles replace the bundled ones, or are added at the end
+    let styles = styles_in(&[local.clone(), directory.path().join("missing")]).unwrap();
+    let apa = find_style(&styles, "apa").unwrap();
+    assert_eq!(apa.title, "APA (lab)");
+    assert_eq!(apa.path.as_deref(), Some(local.join("apa.csl").as_path()));
+    assert_eq!(styles.last().unwrap().title, "harvard");
+
+    // A project without metadata.yaml doesn't use pandoc
+    let project = directory.path().join("paper");
+    std::fs::create_dir(&project).unwrap();
+    assert!(set_style(&project, apa).unwrap_err().starts_with("There is no metadata.yaml in"));
+
+    std::fs::write(project.join("metadata.yaml"), "title: Paper\ncsl: lib/bibliography.csl\n").unwrap();
+    init_git(project.to_str().unwrap(), &[], Collision::Abort).unwrap();
+    std::fs::write(project.join("notes.md"), "Unrelated work\n").unwrap();
+    let files = set_style(&project, apa).unwrap();
+    assert_eq!(files, ["lib/apa.csl", "metadata.yaml"]);
+    assert_eq!(std::fs::read_to_string(project.join("metadata.yaml")).unwrap(), "title: Paper\ncsl: lib/apa.csl\n");
+    assert_eq!(std::fs::read_to_string(project.join("lib/apa.csl")).unwrap(), apa.content);
+
+    // The new style is committed on top of the files of the template, without the other changes
+    commit_changes(project.to_str().unwrap(), "Use the APA (lab) citation style", &files).unwrap();
+    let repo = git2::Repository::open(&project).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.message(), Some("Use the APA (lab) citation style"));
+    assert_eq!(head.parent_count(), 1);
+    assert_eq!(repo.status_file(std::path::Path::new("notes.md")).unwrap(), git2::Status::WT_NEW);
+    assert_eq!(repo.statuses(None).unwrap().len(), 1);
+}
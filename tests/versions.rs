@@ -0,0 +1,129 @@
+//! Pin the templates to a version, fetched from a git tag or an archive
+
+use std::path::Path;
+
+use lth::versions::{available, checkout, parse_spec, select};
+use lth::{Scaffolder, Template, TemplateRegistry};
+
+const MANIFEST: &str = r#"
+    name = "paper"
+    description = "A paper"
+    version = "2.0.0"
+    source = "https://example.com/paper"
+    repository = "REPOSITORY"
+
+    [[files]]
+    path = "main.tex"
+
+    [[releases]]
+    version = "1.2.0"
+    tag = "v1.2.0"
+
+    [[releases]]
+    version = "1.1.0"
+    archive = "ARCHIVE"
+
+    [[releases]]
+    version = "1.0.0"
+    source = "https://example.com/paper-1.0.0/"
+"#;
+
+/// Commit the files of a folder in its git repository
+fn commit(repo: &git2::Repository, message: &str) -> git2::Oid {
+    let mut index = repo.index().unwrap();
+    index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = git2::Signature::now("lth", "lth@localhost").unwrap();
+    let parents: Vec<_> = repo.head().ok().and_then(|head| head.peel_to_commit().ok()).into_iter().collect();
+    let parents: Vec<_> = parents.iter().collect();
+    repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents).unwrap()
+}
+
+/// Write a `.tar.gz` archive with a single folder and the given files
+fn archive(path: &Path, files: &[(&str, &str)]) {
+    let encoder = flate2::write::GzEncoder::new(std::fs::File::create(path).unwrap(), flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    for (name, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, format!("paper-1.1.0/{}", name), content.as_bytes()).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+}
+
+#[test]
+fn versions_are_parsed_and_selected() {
+    let template = Template::parse(MANIFEST).unwrap();
+    let versions: Vec<String> = available(&template).iter().map(ToString::to_string).collect();
+    assert_eq!(versions, ["2.0.0", "1.2.0", "1.1.0", "1.0.0"]);
+
+    let (name, requirement) = parse_spec("paper@1.0").unwrap();
+    assert_eq!(name, "paper");
+    let pinned = select(&template, requirement.as_ref()).unwrap();
+    assert_eq!(pinned.source, "https://example.com/paper-1.0.0");
+    assert_eq!(pinned.version.as_deref(), Some("1.0.0"));
+
+    let (_, requirement) = parse_spec("paper@^2").unwrap();
+    assert_eq!(select(&template, requirement.as_ref()).unwrap().source, "https://example.com/paper");
+
+    let (_, requirement) = parse_spec("paper@3").unwrap();
+    assert_eq!(select(&template, requirement.as_ref()).unwrap_err(), "The template paper has no version matching =3 (available: 2.0.0, 1.2.0, 1.1.0, 1.0.0)");
+    assert!(parse_spec("paper@one").is_err());
+
+    // A parent can be pinned too
+    let mut registry = TemplateRegistry::new();
+    registry.add(template);
+    registry.add_manifest("name = \"notes\"\ndescription = \"\"\nextends = \"paper@1.0\"").unwrap();
+    let notes = registry.resolve("notes").unwrap();
+    assert_eq!(notes.base_of(&notes.files[0]), "https://example.com/paper-1.0.0");
+}
+
+#[test]
+fn releases_come_from_git_tags_and_archives() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = directory.path().join("cache");
+
+    // A repository where main.tex changed after the tag
+    let repository = directory.path().join("repository");
+    let repo = git2::Repository::init(&repository).unwrap();
+    std::fs::write(repository.join("main.tex"), "% 1.2.0\n").unwrap();
+    let id = commit(&repo, "Release 1.2.0");
+    repo.tag_lightweight("v1.2.0", &repo.find_object(id, None).unwrap(), false).unwrap();
+    std::fs::write(repository.join("main.tex"), "% 2.0.0\n").unwrap();
+    commit(&repo, "Breaking change");
+
+    let archive_path = directory.path().join("paper-1.1.0.tar.gz");
+    archive(&archive_path, &[
+        ("template.toml", "name = \"paper\"\ndescription = \"Old paper\"\nversion = \"1.1.0\"\nsource = \"https://example.com/paper\"\n[[files]]\npath = \"paper.tex\"\n"),
+        ("paper.tex", "% 1.1.0\n"),
+    ]);
+
+    let manifest = MANIFEST
+        .replace("REPOSITORY", &repository.display().to_string())
+        .replace("ARCHIVE", &format!("file://{}", archive_path.display()));
+    let template = Template::parse(&manifest).unwrap();
+
+    // The tag has no manifest, so the files of the current manifest are read from the tag
+    let tagged = checkout(&template, &template.releases[0], &cache).unwrap();
+    assert_eq!(tagged.version.as_deref(), Some("1.2.0"));
+    assert_eq!(tagged.origin(), "https://example.com/paper");
+    let project = directory.path().join("projects");
+    std::fs::create_dir(&project).unwrap();
+    Scaffolder::new(tagged).path(project.to_str().unwrap()).name("tagged").create().unwrap();
+    assert_eq!(std::fs::read_to_string(project.join("tagged/main.tex")).unwrap(), "% 1.2.0\n");
+
+    // The archive has its own manifest, which replaces the current one but not its source
+    let archived = checkout(&template, &template.releases[1], &cache).unwrap();
+    assert_eq!(archived.description, "Old paper");
+    assert!(archived.source.starts_with("file://"));
+    assert_eq!(archived.origin(), "https://example.com/paper");
+    Scaffolder::new(archived).path(project.to_str().unwrap()).name("archived").create().unwrap();
+    assert_eq!(std::fs::read_to_string(project.join("archived/paper.tex")).unwrap(), "% 1.1.0\n");
+
+    // The releases are kept in the cache
+    assert!(cache.join("paper/1.2.0/main.tex").is_file());
+    assert!(cache.join("paper/1.1.0/paper-1.1.0/template.toml").is_file());
+}